@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: MIT
+
+//! A simple timing benchmark for parsing report descriptors with large
+//! usage ranges, run with `cargo bench`.
+
+use hidreport::*;
+use std::time::Instant;
+
+const ITERATIONS: u32 = 10_000;
+
+fn bench(name: &str, bytes: &[u8]) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        std::hint::black_box(rdesc);
+    }
+    let elapsed = start.elapsed();
+    println!("{name:<24} {:>10.2?} per parse", elapsed / ITERATIONS);
+}
+
+fn main() {
+    // A keyboard with a 256-usage key array
+    let keyboard: &[u8] = &[
+        0x05, 0x01, // Usage Page (Generic Desktop)
+        0x09, 0x06, // Usage (Keyboard)
+        0xa1, 0x01, // Collection (Application)
+        0x05, 0x07, //  Usage Page (Keyboard)
+        0x19, 0x00, //  Usage Minimum (0)
+        0x2a, 0xff, 0x00, //  Usage Maximum (255)
+        0x15, 0x00, //  Logical Minimum (0)
+        0x26, 0xff, 0x00, //  Logical Maximum (255)
+        0x75, 0x08, //  Report Size (8)
+        0x95, 0x06, //  Report Count (6)
+        0x81, 0x00, //  Input (Data,Arr,Abs)
+        0xc0, // End Collection
+    ];
+    bench("256 keyboard usages", keyboard);
+
+    // A consumer control with a 1024-usage array
+    let consumer: &[u8] = &[
+        0x05, 0x0c, // Usage Page (Consumer)
+        0x09, 0x01, // Usage (Consumer Control)
+        0xa1, 0x01, // Collection (Application)
+        0x19, 0x00, //  Usage Minimum (0)
+        0x2a, 0xff, 0x03, //  Usage Maximum (1023)
+        0x15, 0x00, //  Logical Minimum (0)
+        0x26, 0xff, 0x03, //  Logical Maximum (1023)
+        0x75, 0x10, //  Report Size (16)
+        0x95, 0x01, //  Report Count (1)
+        0x81, 0x00, //  Input (Data,Arr,Abs)
+        0xc0, // End Collection
+    ];
+    bench("1024 consumer usages", consumer);
+}
@@ -30,7 +30,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     fn test_{funcname}() {{
         let bytes: Vec<u8> = std::fs::read({path:?}).unwrap();
         if !bytes.is_empty() {{
-            ReportDescriptor::try_from(&bytes).expect(&format!(\"Failed to parse {filename}\"));
+            let rdesc = ReportDescriptor::try_from(&bytes).expect(\"Failed to parse {filename}\");
+            for r in rdesc.input_reports().iter() {{
+                r.validate().expect(\"Invalid input report in {filename}\");
+            }}
+            for r in rdesc.output_reports().iter() {{
+                r.validate().expect(\"Invalid output report in {filename}\");
+            }}
+            for r in rdesc.feature_reports().iter() {{
+                r.validate().expect(\"Invalid feature report in {filename}\");
+            }}
         }}
     }}
     "
@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: MIT
+//
+// Run with `cargo fuzz run parse_report_descriptor`, optionally with
+// tests/data as seed corpus.
+
+#![no_main]
+
+use hidreport::*;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = hid::parse_items(data);
+    let _ = hid::descriptor_len(data);
+
+    let Ok(rdesc) = ReportDescriptor::try_from(data) else {
+        return;
+    };
+    // Feed each report a report of the right size made from the
+    // descriptor bytes to exercise the extraction paths
+    for report in rdesc.reports() {
+        let _ = report.validate();
+        let mut bytes: Vec<u8> = data
+            .iter()
+            .copied()
+            .cycle()
+            .take(report.size_in_bytes())
+            .collect();
+        if let (Some(id), Some(first)) = (report.report_id(), bytes.first_mut()) {
+            *first = u8::from(*id);
+        }
+        let _ = report.decode(&bytes);
+        for field in report.fields() {
+            match field {
+                Field::Variable(var) => {
+                    let _ = var.extract(&bytes);
+                }
+                Field::Array(arr) => {
+                    let _ = arr.extract_u32(&bytes);
+                }
+                Field::Constant(_) => {}
+            }
+        }
+    }
+});
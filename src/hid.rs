@@ -31,7 +31,10 @@
 use crate::types::*;
 use crate::{ensure, ParserError};
 
-use thiserror::Error;
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::{format, vec, vec::Vec};
+use core::ops::ControlFlow;
 
 /// Convenience function to be extract a single bit as bool from a value
 fn bit(bits: u32, bit: u8) -> bool {
@@ -61,15 +64,24 @@ pub(crate) fn hiddata_signed(bytes: &[u8]) -> Option<i32> {
     }
 }
 
-#[derive(Error, Debug)]
+#[derive(Debug)]
 pub enum HidError {
-    #[error("Invalid data: {message}")]
     InvalidData { message: String },
-    #[error("Insufficient data")]
     InsufficientData,
 }
 
-type Result<T> = std::result::Result<T, HidError>;
+impl core::fmt::Display for HidError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            HidError::InvalidData { message } => write!(f, "Invalid data: {message}"),
+            HidError::InsufficientData => write!(f, "Insufficient data"),
+        }
+    }
+}
+
+impl core::error::Error for HidError {}
+
+type Result<T> = core::result::Result<T, HidError>;
 
 /// The type of a HID item may be one of [MainItem], [GlobalItem], or [LocalItem].
 /// These items comprise the report descriptor and how the report descriptor should
@@ -564,8 +576,9 @@ impl TryFrom<&[u8]> for InputItem {
     type Error = HidError;
 
     fn try_from(bytes: &[u8]) -> Result<Self> {
-        ensure!(bytes.len() >= 2, HidError::InsufficientData);
-        let data = hiddata(&bytes[1..]).unwrap();
+        ensure!(!bytes.is_empty(), HidError::InsufficientData);
+        // A main item without data bytes has all flags unset
+        let data = hiddata(&bytes[1..]).unwrap_or(0);
         Ok(Self {
             is_constant: bit(data, 0),
             is_variable: bit(data, 1),
@@ -584,8 +597,9 @@ impl TryFrom<&[u8]> for OutputItem {
     type Error = HidError;
 
     fn try_from(bytes: &[u8]) -> Result<Self> {
-        ensure!(bytes.len() >= 2, HidError::InsufficientData);
-        let data = hiddata(&bytes[1..]).unwrap();
+        ensure!(!bytes.is_empty(), HidError::InsufficientData);
+        // A main item without data bytes has all flags unset
+        let data = hiddata(&bytes[1..]).unwrap_or(0);
         Ok(Self {
             is_constant: bit(data, 0),
             is_variable: bit(data, 1),
@@ -604,8 +618,9 @@ impl TryFrom<&[u8]> for FeatureItem {
     type Error = HidError;
 
     fn try_from(bytes: &[u8]) -> Result<Self> {
-        ensure!(bytes.len() >= 2, HidError::InsufficientData);
-        let data = hiddata(&bytes[1..]).unwrap();
+        ensure!(!bytes.is_empty(), HidError::InsufficientData);
+        // A main item without data bytes has all flags unset
+        let data = hiddata(&bytes[1..]).unwrap_or(0);
         Ok(Self {
             is_constant: bit(data, 0),
             is_variable: bit(data, 1),
@@ -810,8 +825,8 @@ impl TryFrom<&[u8]> for LocalItem {
 pub trait Item {
     /// The length of this item in bytes, inclusive of the header byte.
     /// For short items this is the length of the data in bytes plus 1 for the header byte.
-    /// For long items this is the lengh of the length of the data plus 4 (header byte, data size
-    /// byte and 2 bytes for a long item tag).
+    /// For long items this is the length of the data plus 3 (header byte, data size
+    /// byte and the long item tag byte).
     fn size(&self) -> usize;
 
     fn item_type(&self) -> ItemType;
@@ -840,7 +855,7 @@ pub trait Item {
     fn bytes(&self) -> &[u8];
 
     /// Return the item's data bytes, if any.
-    fn data(&self) -> Option<ItemData>;
+    fn data(&self) -> Option<ItemData<'_>>;
 }
 
 /// Wraps the data bytes of a single [Item].
@@ -861,7 +876,7 @@ pub struct ItemData<'a> {
     bytes: &'a [u8],
 }
 
-impl<'a> std::ops::Deref for ItemData<'a> {
+impl<'a> core::ops::Deref for ItemData<'a> {
     type Target = [u8];
 
     fn deref(&self) -> &Self::Target {
@@ -911,9 +926,8 @@ impl<'a> TryFrom<&ItemData<'a>> for Vec<u8> {
         ensure!(!data.is_empty(), HidError::InsufficientData);
         match data.len() {
             0 => panic!("Item data with zero bytes must not happen"),
-            3 => panic!("Size of {} cannot happen", data.bytes.len()),
-            1..=4 => Ok(data.bytes.to_owned()),
-            _ => panic!("Size of {} cannot happen", data.bytes.len()),
+            // Long items may have any data length
+            _ => Ok(data.bytes.to_owned()),
         }
     }
 }
@@ -922,8 +936,7 @@ impl<'a> TryFrom<&ItemData<'a>> for Vec<u8> {
 #[derive(Debug)]
 pub struct ReportDescriptorItem {
     offset: usize,
-    // FIXME: this needs to support long items once day
-    item: ShortItem,
+    item: AnyItem,
 }
 
 impl ReportDescriptorItem {
@@ -935,6 +948,12 @@ impl ReportDescriptorItem {
     pub fn item(&self) -> &impl Item {
         &self.item
     }
+
+    /// The bytes of this item, including the item header. This is
+    /// shorthand for [Item::bytes] on [ReportDescriptorItem::item].
+    pub fn bytes(&self) -> &[u8] {
+        self.item.bytes()
+    }
 }
 
 /// A set of items extracted from a report descriptor byte array. This is the
@@ -945,7 +964,7 @@ pub struct ReportDescriptorItems {
     items: Vec<ReportDescriptorItem>,
 }
 
-impl std::ops::Deref for ReportDescriptorItems {
+impl core::ops::Deref for ReportDescriptorItems {
     type Target = [ReportDescriptorItem];
 
     fn deref(&self) -> &Self::Target {
@@ -953,11 +972,210 @@ impl std::ops::Deref for ReportDescriptorItems {
     }
 }
 
+/// Iterate over the items in the order they appear in the report descriptor.
+///
+/// ```
+/// # use hidreport::hid::*;
+/// let bytes: &[u8] = &[0x05, 0x01, 0x09, 0x02, 0xa1, 0x01, 0xc0];
+/// let items = ReportDescriptorItems::try_from(bytes).unwrap();
+/// for rdesc_item in &items {
+///     println!("{:3}: {:02x?}", rdesc_item.offset(), rdesc_item.bytes());
+/// }
+/// ```
+impl<'a> IntoIterator for &'a ReportDescriptorItems {
+    type Item = &'a ReportDescriptorItem;
+    type IntoIter = core::slice::Iter<'a, ReportDescriptorItem>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+impl IntoIterator for ReportDescriptorItems {
+    type Item = ReportDescriptorItem;
+    type IntoIter = alloc::vec::IntoIter<ReportDescriptorItem>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+impl ReportDescriptorItems {
+    /// Format the items in this report descriptor as C array, one line per item,
+    /// with each item's bytes followed by a comment describing the item.
+    /// The comments are indented by the current collection depth.
+    ///
+    /// ```
+    /// # use hidreport::hid::*;
+    /// let bytes: &[u8] = &[0x05, 0x01, 0x09, 0x02, 0xa1, 0x01, 0xc0];
+    /// let items = ReportDescriptorItems::try_from(bytes).unwrap();
+    /// assert_eq!(
+    ///     items.to_c_header(),
+    ///     "\
+    /// 0x05, 0x01,                    // Usage Page (Generic Desktop)
+    /// 0x09, 0x02,                    // Usage (Mouse)
+    /// 0xa1, 0x01,                    // Collection (Application)
+    /// 0xc0,                          // End Collection
+    /// ");
+    /// ```
+    pub fn to_c_header(&self) -> String {
+        let mut usage_pages: Vec<Option<UsagePage>> = vec![None];
+        let mut depth: usize = 0;
+        let mut output = String::new();
+
+        for rdesc_item in self.iter() {
+            let item = rdesc_item.item();
+            let item_type = item.item_type();
+            let usage_page = *usage_pages.last().unwrap();
+            match item_type {
+                ItemType::Global(GlobalItem::UsagePage { usage_page }) => {
+                    *usage_pages.last_mut().unwrap() = Some(usage_page);
+                }
+                ItemType::Global(GlobalItem::Push) => usage_pages.push(usage_page),
+                ItemType::Global(GlobalItem::Pop) if usage_pages.len() > 1 => {
+                    usage_pages.pop();
+                }
+                ItemType::Main(MainItem::EndCollection) => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+
+            let bytes = item
+                .bytes()
+                .iter()
+                .map(|b| format!("0x{b:02x}, "))
+                .collect::<String>();
+            let indent = "  ".repeat(depth);
+            let description = describe_item(&item_type, usage_page);
+            output.push_str(&format!(
+                "{:<31}// {indent}{description}\n",
+                bytes.trim_end()
+            ));
+
+            if let ItemType::Main(MainItem::Collection(_)) = item_type {
+                depth += 1;
+            }
+        }
+
+        output
+    }
+}
+
+/// Returns a human-readable description of this item, e.g. `Usage Page (Generic Desktop)`.
+/// The usage page is used to look up the name of [LocalItem::Usage] items.
+fn describe_item(item_type: &ItemType, usage_page: Option<UsagePage>) -> String {
+    fn flags(item: &impl MainDataItem, is_volatile: bool) -> String {
+        let mut flags = vec![
+            if item.is_constant() { "Cnst" } else { "Data" },
+            if item.is_variable() { "Var" } else { "Arr" },
+            if item.is_relative() { "Rel" } else { "Abs" },
+        ];
+        if item.wraps() {
+            flags.push("Wrap");
+        }
+        if item.is_nonlinear() {
+            flags.push("NonLin");
+        }
+        if item.has_no_preferred_state() {
+            flags.push("NoPref");
+        }
+        if item.has_null_state() {
+            flags.push("Null");
+        }
+        if is_volatile {
+            flags.push("Vol");
+        }
+        if item.is_buffered_bytes() {
+            flags.push("Buff");
+        }
+        flags.join(",")
+    }
+
+    match item_type {
+        ItemType::Main(MainItem::Input(i)) => format!("Input ({})", flags(i, false)),
+        ItemType::Main(MainItem::Output(i)) => format!("Output ({})", flags(i, i.is_volatile())),
+        ItemType::Main(MainItem::Feature(i)) => format!("Feature ({})", flags(i, i.is_volatile())),
+        ItemType::Main(MainItem::Collection(c)) => {
+            let name = match c {
+                CollectionItem::Physical => "Physical".into(),
+                CollectionItem::Application => "Application".into(),
+                CollectionItem::Logical => "Logical".into(),
+                CollectionItem::Report => "Report".into(),
+                CollectionItem::NamedArray => "Named Array".into(),
+                CollectionItem::UsageSwitch => "Usage Switch".into(),
+                CollectionItem::UsageModifier => "Usage Modifier".into(),
+                CollectionItem::Reserved { value } => format!("Reserved 0x{value:02x}"),
+                CollectionItem::VendorDefined { value } => format!("Vendor Defined 0x{value:02x}"),
+            };
+            format!("Collection ({name})")
+        }
+        ItemType::Main(MainItem::EndCollection) => "End Collection".into(),
+        ItemType::Global(g) => match g {
+            GlobalItem::UsagePage { usage_page } => {
+                let page = u16::from(usage_page);
+                match crate::names::usage_page_name(page) {
+                    Some(name) if page >= 0xff00 => format!("Usage Page ({name} 0x{page:04x})"),
+                    Some(name) => format!("Usage Page ({name})"),
+                    None => format!("Usage Page (0x{page:04x})"),
+                }
+            }
+            GlobalItem::LogicalMinimum { minimum } => format!("Logical Minimum ({minimum})"),
+            GlobalItem::LogicalMaximum { maximum } => format!("Logical Maximum ({maximum})"),
+            GlobalItem::PhysicalMinimum { minimum } => format!("Physical Minimum ({minimum})"),
+            GlobalItem::PhysicalMaximum { maximum } => format!("Physical Maximum ({maximum})"),
+            GlobalItem::UnitExponent { exponent } => {
+                format!("Unit Exponent ({})", exponent.exponent())
+            }
+            GlobalItem::Unit { unit } => match format!("{unit}").as_str() {
+                "" => "Unit (None)".into(),
+                u => format!("Unit ({u})"),
+            },
+            GlobalItem::ReportSize { size } => format!("Report Size ({size})"),
+            GlobalItem::ReportId { id } => format!("Report ID ({id})"),
+            GlobalItem::ReportCount { count } => format!("Report Count ({count})"),
+            GlobalItem::Push => "Push".into(),
+            GlobalItem::Pop => "Pop".into(),
+            GlobalItem::Reserved => "Reserved".into(),
+        },
+        ItemType::Local(l) => match l {
+            LocalItem::Usage {
+                usage_page: up,
+                usage_id,
+            } => {
+                let name = up.or(usage_page).and_then(|usage_page| {
+                    crate::names::usage_name(&crate::Usage::from_page_and_id(usage_page, *usage_id))
+                });
+                match (name, up) {
+                    (Some(name), _) => format!("Usage ({name})"),
+                    (None, Some(up)) => {
+                        format!("Usage (0x{:04x}{:04x})", u16::from(up), u16::from(usage_id))
+                    }
+                    (None, None) => format!("Usage (0x{:04x})", u16::from(usage_id)),
+                }
+            }
+            LocalItem::UsageMinimum { minimum } => format!("Usage Minimum ({minimum})"),
+            LocalItem::UsageMaximum { maximum } => format!("Usage Maximum ({maximum})"),
+            LocalItem::DesignatorIndex { index } => format!("Designator Index ({index})"),
+            LocalItem::DesignatorMinimum { minimum } => format!("Designator Minimum ({minimum})"),
+            LocalItem::DesignatorMaximum { maximum } => format!("Designator Maximum ({maximum})"),
+            LocalItem::StringIndex { index } => format!("String Index ({index})"),
+            LocalItem::StringMinimum { minimum } => format!("String Minimum ({minimum})"),
+            LocalItem::StringMaximum { maximum } => format!("String Maximum ({maximum})"),
+            LocalItem::Delimiter { delimiter } => format!("Delimiter ({delimiter})"),
+            LocalItem::Reserved { .. } => "Reserved".into(),
+        },
+        ItemType::Long => "Long Item".into(),
+        ItemType::Reserved => "Reserved".into(),
+    }
+}
+
 impl TryFrom<&[u8]> for ReportDescriptorItems {
     type Error = ParserError;
 
     /// Attempts to itemize the given HID report descriptor into its
     /// set of [ReportDescriptorItem]s.
+    ///
+    /// If the bytes end in the middle of an item this returns
+    /// [ParserError::InvalidData] with the offset of that item.
     fn try_from(bytes: &[u8]) -> crate::Result<Self> {
         itemize(bytes)
     }
@@ -996,7 +1214,7 @@ impl Item for ShortItem {
         &self.bytes
     }
 
-    fn data(&self) -> Option<ItemData> {
+    fn data(&self) -> Option<ItemData<'_>> {
         match self.item_size {
             1 => None,
             2 | 3 | 5 => Some(ItemData {
@@ -1059,10 +1277,14 @@ impl Item for LongItem {
         self.bytes[0]
     }
 
-    fn data(&self) -> Option<ItemData> {
-        Some(ItemData {
-            bytes: &self.bytes[3..],
-        })
+    fn data(&self) -> Option<ItemData<'_>> {
+        if self.bytes.len() > 3 {
+            Some(ItemData {
+                bytes: &self.bytes[3..],
+            })
+        } else {
+            None
+        }
     }
 
     fn bytes(&self) -> &[u8] {
@@ -1075,29 +1297,292 @@ impl TryFrom<&[u8]> for LongItem {
 
     fn try_from(bytes: &[u8]) -> Result<LongItem> {
         ensure!(bytes.len() >= 3, HidError::InsufficientData);
-        if bytes[1] != 0b11111110 {
+        if bytes[0] != 0b11111110 {
             return Err(HidError::InvalidData {
                 message: "Item is not a long item".into(),
             });
         }
-        let size = bytes[1] as usize;
+        let size = bytes[1] as usize + 3;
+        ensure!(bytes.len() >= size, HidError::InsufficientData);
 
         Ok(LongItem {
             size,
-            bytes: bytes[0..size + 3].to_owned(),
+            bytes: bytes[0..size].to_owned(),
         })
     }
 }
 
+/// Either a [ShortItem] or a [LongItem], depending on the header byte.
+#[derive(Debug)]
+enum AnyItem {
+    Short(ShortItem),
+    Long(LongItem),
+}
+
+impl AnyItem {
+    fn as_item(&self) -> &dyn Item {
+        match self {
+            AnyItem::Short(item) => item,
+            AnyItem::Long(item) => item,
+        }
+    }
+}
+
+impl Item for AnyItem {
+    fn is_long_item(&self) -> bool {
+        self.as_item().is_long_item()
+    }
+
+    fn size(&self) -> usize {
+        self.as_item().size()
+    }
+
+    fn item_type(&self) -> ItemType {
+        self.as_item().item_type()
+    }
+
+    fn tag(&self) -> u8 {
+        self.as_item().tag()
+    }
+
+    fn header(&self) -> u8 {
+        self.as_item().header()
+    }
+
+    fn bytes(&self) -> &[u8] {
+        self.as_item().bytes()
+    }
+
+    fn data(&self) -> Option<ItemData<'_>> {
+        self.as_item().data()
+    }
+}
+
+impl TryFrom<&[u8]> for AnyItem {
+    type Error = HidError;
+
+    fn try_from(bytes: &[u8]) -> Result<AnyItem> {
+        ensure!(!bytes.is_empty(), HidError::InsufficientData);
+        match bytes[0] {
+            0b11111110 => Ok(AnyItem::Long(LongItem::try_from(bytes)?)),
+            _ => Ok(AnyItem::Short(ShortItem::try_from(bytes)?)),
+        }
+    }
+}
+
+/// Split the given HID Report Descriptor bytes into its set of items
+/// without interpreting them, e.g. to annotate the original bytes.
+/// This is equivalent to [`ReportDescriptorItems::try_from(bytes)`](ReportDescriptorItems::try_from).
+///
+/// ```
+/// # use hidreport::hid::*;
+/// let bytes: &[u8] = &[0x05, 0x01, 0x09, 0x02, 0xa1, 0x01, 0xc0];
+/// for rdesc_item in parse_items(bytes).unwrap().iter() {
+///     let item = rdesc_item.item();
+///     println!(
+///         "{:04x}: {:02x?} {:?}",
+///         rdesc_item.offset(),
+///         item.bytes(),
+///         item.item_type()
+///     );
+/// }
+/// ```
+pub fn parse_items(bytes: &[u8]) -> crate::Result<ReportDescriptorItems> {
+    itemize(bytes)
+}
+
+/// Returns the length in bytes of the HID Report Descriptor at the start
+/// of the given bytes, e.g. where the report descriptor is embedded in a
+/// larger blob. The report descriptor ends with the End Collection item that
+/// closes its last top-level collection, anything after that is ignored.
+///
+/// A report descriptor without any collections uses all bytes. Where the
+/// bytes end within a top-level collection after a complete top-level
+/// collection, that incomplete collection is treated as trailing data.
+///
+/// ```
+/// # use hidreport::hid::*;
+/// let bytes: &[u8] = &[0x05, 0x01, 0x09, 0x02, 0xa1, 0x01, 0xc0, 0xff, 0xff];
+/// assert_eq!(descriptor_len(bytes).unwrap(), 7);
+/// ```
+///
+/// Returns [ParserError::InvalidData] if the bytes end before the first
+/// top-level collection is closed or an item is truncated or invalid
+/// before that.
+pub fn descriptor_len(bytes: &[u8]) -> crate::Result<usize> {
+    let mut offset = 0;
+    let mut depth = 0usize;
+    let mut end = None;
+    while offset < bytes.len() {
+        let item = match AnyItem::try_from(&bytes[offset..]) {
+            Ok(item) => item,
+            // Trailing garbage after the last collection
+            Err(_) if depth == 0 && end.is_some() => break,
+            Err(HidError::InsufficientData) => {
+                return Err(ParserError::InvalidData {
+                    offset,
+                    message: format!(
+                        "Truncated item, only {} bytes remaining",
+                        bytes.len() - offset
+                    ),
+                });
+            }
+            Err(e) => {
+                return Err(ParserError::InvalidData {
+                    offset,
+                    message: format!("{e}"),
+                });
+            }
+        };
+        offset += item.size();
+        match item.item_type() {
+            ItemType::Main(MainItem::Collection(_)) => depth += 1,
+            ItemType::Main(MainItem::EndCollection) => {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(offset);
+                }
+            }
+            _ => {}
+        }
+    }
+    // A collection opened after the last complete one is trailing garbage
+    if let Some(end) = end {
+        return Ok(end);
+    }
+    ensure!(
+        depth == 0,
+        ParserError::InvalidData {
+            offset: bytes.len(),
+            message: format!("Truncated report descriptor, {depth} collections not closed"),
+        }
+    );
+
+    Ok(bytes.len())
+}
+
+/// Call the given function for each item in the HID Report Descriptor bytes,
+/// in order, without collecting them. Parsing stops early when the function
+/// returns [ControlFlow::Break].
+///
+/// ```
+/// # use hidreport::hid::*;
+/// # use core::ops::ControlFlow;
+/// let bytes: &[u8] = &[0x05, 0x01, 0x09, 0x02, 0xa1, 0x01, 0xc0];
+/// let mut count = 0;
+/// parse_items_with(bytes, |item| {
+///     count += 1;
+///     match item.item_type() {
+///         ItemType::Main(_) => ControlFlow::Break(()),
+///         _ => ControlFlow::Continue(()),
+///     }
+/// })
+/// .unwrap();
+/// assert_eq!(count, 3);
+/// ```
+///
+/// Items after the early exit are not parsed, so errors in those items
+/// are not detected.
+pub fn parse_items_with(
+    bytes: &[u8],
+    mut f: impl FnMut(&dyn Item) -> ControlFlow<()>,
+) -> crate::Result<()> {
+    walk_items(bytes, |_, item| f(item.as_item()))
+}
+
+/// Returns the HID Report Descriptors in the given blob that starts with a
+/// USB configuration descriptor, e.g. from a firmware image or a capture.
+///
+/// A USB configuration descriptor does not contain the report descriptors
+/// themselves, only their lengths in the `wDescriptorLength` fields of each
+/// interface's HID class descriptor. This function walks the descriptor
+/// chain up to `wTotalLength` and expects the report descriptors to follow
+/// the configuration descriptor in the order of their HID class descriptors.
+///
+/// Report descriptors that do not fit into the remaining bytes are
+/// skipped, as is anything after the last report descriptor. A malformed
+/// descriptor chain ends the walk.
+pub fn find_report_descriptors(config: &[u8]) -> Vec<&[u8]> {
+    const USB_DT_CONFIG: u8 = 0x02;
+    const USB_DT_HID: u8 = 0x21;
+    const USB_DT_REPORT: u8 = 0x22;
+
+    let total_len = match config {
+        [_, USB_DT_CONFIG, lo, hi, ..] => {
+            (u16::from_le_bytes([*lo, *hi]) as usize).min(config.len())
+        }
+        _ => config.len(),
+    };
+
+    let mut lengths: Vec<usize> = Vec::new();
+    let mut offset = 0;
+    while offset + 2 <= total_len {
+        let len = config[offset] as usize;
+        if len < 2 || offset + len > total_len {
+            break;
+        }
+        let desc = &config[offset..offset + len];
+        // bLength, bDescriptorType, bcdHID, bCountryCode, bNumDescriptors,
+        // then bDescriptorType and wDescriptorLength for each descriptor
+        if desc[1] == USB_DT_HID && len >= 6 {
+            lengths.extend(
+                desc[6..]
+                    .chunks_exact(3)
+                    .take(desc[5] as usize)
+                    .filter(|d| d[0] == USB_DT_REPORT)
+                    .map(|d| u16::from_le_bytes([d[1], d[2]]) as usize),
+            );
+        }
+        offset += len;
+    }
+
+    let mut rest = &config[total_len..];
+    let mut rdescs = Vec::new();
+    for len in lengths {
+        if len > rest.len() {
+            break;
+        }
+        let (rdesc, remainder) = rest.split_at(len);
+        rdescs.push(rdesc);
+        rest = remainder;
+    }
+    rdescs
+}
+
 /// Split the HID Report Descriptor represented by bytes into its set of
 /// items.
 fn itemize(bytes: &[u8]) -> crate::Result<ReportDescriptorItems> {
-    let mut offset = 0;
     let mut items: Vec<ReportDescriptorItem> = Vec::new();
+    walk_items(bytes, |offset, item| {
+        items.push(ReportDescriptorItem { offset, item });
+        ControlFlow::Continue(())
+    })?;
+    Ok(ReportDescriptorItems { items })
+}
+
+/// Parse the items in the given bytes one-by-one and pass each item
+/// and its offset to the given function until the function returns
+/// [ControlFlow::Break] or the bytes are exhausted.
+fn walk_items(
+    bytes: &[u8],
+    mut f: impl FnMut(usize, AnyItem) -> ControlFlow<()>,
+) -> crate::Result<()> {
+    let mut offset = 0;
     loop {
-        // FIXME: this will break if we ever get long items
-        let item = match ShortItem::try_from(&bytes[offset..]) {
+        let item = match AnyItem::try_from(&bytes[offset..]) {
             Ok(item) => item,
+            Err(HidError::InsufficientData) => {
+                return Err(ParserError::InvalidData {
+                    offset,
+                    message: format!(
+                        "Truncated item, only {} bytes remaining",
+                        bytes.len() - offset
+                    ),
+                });
+            }
             Err(e) => {
                 return Err(ParserError::InvalidData {
                     offset,
@@ -1107,12 +1592,11 @@ fn itemize(bytes: &[u8]) -> crate::Result<ReportDescriptorItems> {
         };
         let off = offset;
         offset += item.size();
-        items.push(ReportDescriptorItem { offset: off, item });
-        if offset >= bytes.len() {
+        if f(off, item).is_break() || offset >= bytes.len() {
             break;
         }
     }
-    Ok(ReportDescriptorItems { items })
+    Ok(())
 }
 
 #[cfg(test)]
@@ -1149,19 +1633,17 @@ mod tests {
         assert!(matches!(item.item_type(), ItemType::Main { .. }));
         match item.item_type() {
             ItemType::Main(mi) => match mi {
-                MainItem::Output(o) => match o {
-                    _ => {
-                        assert!(o.is_constant == false);
-                        assert!(o.is_variable == true);
-                        assert!(o.is_relative == false);
-                        assert!(o.wraps == true);
-                        assert!(o.is_nonlinear == false);
-                        assert!(o.has_no_preferred_state == true);
-                        assert!(o.has_null_state == false);
-                        assert!(o.is_volatile == true);
-                        assert!(o.is_buffered_bytes == true);
-                    }
-                },
+                MainItem::Output(o) => {
+                    assert!(!o.is_constant);
+                    assert!(o.is_variable);
+                    assert!(!o.is_relative);
+                    assert!(o.wraps);
+                    assert!(!o.is_nonlinear);
+                    assert!(o.has_no_preferred_state);
+                    assert!(!o.has_null_state);
+                    assert!(o.is_volatile);
+                    assert!(o.is_buffered_bytes);
+                }
                 _ => panic!("Failed match against MainItem"),
             },
             _ => panic!("Wrong item type"),
@@ -1178,4 +1660,349 @@ mod tests {
         let item_data = ItemData { bytes: &bytes[..4] };
         assert_eq!(u32::try_from(&item_data).unwrap(), 0x04030201);
     }
+
+    #[test]
+    fn c_header() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, 0x09, 0x02, 0xa1, 0x01, 0x09, 0x01, 0xa1, 0x00, 0x05, 0x09, 0x19, 0x01,
+            0x29, 0x03, 0x15, 0x00, 0x25, 0x01, 0x95, 0x03, 0x75, 0x01, 0x81, 0x02, 0x95, 0x01,
+            0x75, 0x05, 0x81, 0x01, 0x05, 0x01, 0x09, 0x30, 0x09, 0x31, 0x15, 0x81, 0x25, 0x7f,
+            0x75, 0x08, 0x95, 0x02, 0x81, 0x06, 0xc0, 0xc0,
+        ];
+        let items = ReportDescriptorItems::try_from(bytes).unwrap();
+        let expected = "\
+0x05, 0x01,                    // Usage Page (Generic Desktop)
+0x09, 0x02,                    // Usage (Mouse)
+0xa1, 0x01,                    // Collection (Application)
+0x09, 0x01,                    //   Usage (Pointer)
+0xa1, 0x00,                    //   Collection (Physical)
+0x05, 0x09,                    //     Usage Page (Button)
+0x19, 0x01,                    //     Usage Minimum (1)
+0x29, 0x03,                    //     Usage Maximum (3)
+0x15, 0x00,                    //     Logical Minimum (0)
+0x25, 0x01,                    //     Logical Maximum (1)
+0x95, 0x03,                    //     Report Count (3)
+0x75, 0x01,                    //     Report Size (1)
+0x81, 0x02,                    //     Input (Data,Var,Abs)
+0x95, 0x01,                    //     Report Count (1)
+0x75, 0x05,                    //     Report Size (5)
+0x81, 0x01,                    //     Input (Cnst,Arr,Abs)
+0x05, 0x01,                    //     Usage Page (Generic Desktop)
+0x09, 0x30,                    //     Usage (X)
+0x09, 0x31,                    //     Usage (Y)
+0x15, 0x81,                    //     Logical Minimum (-127)
+0x25, 0x7f,                    //     Logical Maximum (127)
+0x75, 0x08,                    //     Report Size (8)
+0x95, 0x02,                    //     Report Count (2)
+0x81, 0x06,                    //     Input (Data,Var,Rel)
+0xc0,                          //   End Collection
+0xc0,                          // End Collection
+";
+        assert_eq!(items.to_c_header(), expected);
+    }
+
+    #[test]
+    fn parse_items() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x02, // Usage (Mouse)
+            0xa1, 0x01, // Collection (Application)
+            0x16, 0x01, 0x80, // Logical Minimum (-32767)
+            0x27, 0xff, 0xff, 0x00, 0x00, // Logical Maximum (65535)
+            0xc0, // End Collection
+        ];
+        let items = super::parse_items(bytes).unwrap();
+        let offsets: Vec<(usize, usize)> = items
+            .iter()
+            .map(|i| (i.offset(), i.item().size()))
+            .collect();
+        assert_eq!(
+            offsets,
+            vec![(0, 2), (2, 2), (4, 2), (6, 3), (9, 5), (14, 1)]
+        );
+
+        for rdesc_item in items.iter() {
+            let item = rdesc_item.item();
+            let offset = rdesc_item.offset();
+            assert_eq!(item.bytes(), &bytes[offset..offset + item.size()]);
+        }
+        assert!(matches!(
+            items[3].item().item_type(),
+            ItemType::Global(GlobalItem::LogicalMinimum { .. })
+        ));
+        assert!(matches!(
+            items[5].item().item_type(),
+            ItemType::Main(MainItem::EndCollection)
+        ));
+    }
+
+    #[test]
+    fn long_item() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0xfe, 0x05, 0xf0, 0x01, 0x02, 0x03, 0x04, 0x05, // Long Item, tag 0xf0
+            0x09, 0x02, // Usage (Mouse)
+        ];
+        let items = super::parse_items(bytes).unwrap();
+        assert_eq!(items.len(), 3);
+
+        let long = &items[1];
+        assert_eq!(long.offset(), 2);
+        let item = long.item();
+        assert!(item.is_long_item());
+        assert!(matches!(item.item_type(), ItemType::Long));
+        assert_eq!(item.size(), 8);
+        assert_eq!(item.header(), 0xfe);
+        assert_eq!(item.tag(), 0xf0);
+        let data = Vec::<u8>::try_from(&item.data().unwrap()).unwrap();
+        assert_eq!(data, vec![0x01, 0x02, 0x03, 0x04, 0x05]);
+
+        let usage = &items[2];
+        assert_eq!(usage.offset(), 10);
+        assert!(matches!(
+            usage.item().item_type(),
+            ItemType::Local(LocalItem::Usage { .. })
+        ));
+
+        // Data size exceeds the remaining bytes
+        assert!(super::parse_items(&[0xfe, 0x05, 0xf0, 0x01]).is_err());
+    }
+
+    #[test]
+    fn unit_exponent_item() {
+        for (bytes, expected) in [
+            (&[0x55u8, 0x05][..], 5),
+            (&[0x55, 0x0f][..], -1),
+            (&[0x55, 0xfe][..], -2),
+            (&[0x56, 0xfd, 0xff][..], -3),
+        ] {
+            let item = ShortItem::try_from(bytes).unwrap();
+            match item.item_type() {
+                ItemType::Global(GlobalItem::UnitExponent { exponent }) => {
+                    assert_eq!(exponent.exponent(), expected)
+                }
+                t => panic!("Unexpected item type {t:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x02, // Usage (Mouse)
+            0xa1, 0x01, // Collection (Application)
+            0x17, 0x00, 0x00, 0x00, 0x80, // Logical Minimum (i32::MIN)
+            0x26, 0xff, 0x7f, // Logical Maximum (32767)
+            0xc0, // End Collection
+        ];
+        // Item boundaries
+        for len in [2, 4, 6, 11, 14, 15] {
+            assert!(ReportDescriptorItems::try_from(&bytes[..len]).is_ok());
+        }
+        // Mid-item, the error points to the start of the truncated item
+        for (len, item_offset) in [(1, 0), (3, 2), (7, 6), (9, 6), (10, 6), (12, 11), (13, 11)] {
+            match ReportDescriptorItems::try_from(&bytes[..len]) {
+                Err(ParserError::InvalidData { offset, message }) => {
+                    assert_eq!(offset, item_offset);
+                    assert_eq!(
+                        message,
+                        format!("Truncated item, only {} bytes remaining", len - offset)
+                    );
+                }
+                r => panic!("Unexpected result for length {len}: {r:?}"),
+            }
+        }
+        assert!(ReportDescriptorItems::try_from(&[][..]).is_err());
+    }
+
+    #[test]
+    fn descriptor_len() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x02, // Usage (Mouse)
+            0xa1, 0x01, // Collection (Application)
+            0x09, 0x01, // Usage (Pointer)
+            0xa1, 0x00, // Collection (Physical)
+            0xc0, // End Collection
+            0xc0, // End Collection
+            0x09, 0x06, // Usage (Keyboard)
+            0xa1, 0x01, // Collection (Application)
+            0xc0, // End Collection
+        ];
+        assert_eq!(super::descriptor_len(bytes).unwrap(), bytes.len());
+
+        for garbage in [
+            &[0x00, 0x00, 0x00, 0x00][..],
+            &[0xc0, 0xa1],
+            &[0x27, 0xff],
+            &[0x09, 0x01, 0xa1, 0x01],
+        ] {
+            let mut data = bytes.to_vec();
+            data.extend_from_slice(garbage);
+            assert_eq!(super::descriptor_len(&data).unwrap(), bytes.len());
+        }
+
+        // Truncated in the first collection
+        for len in [7, 9] {
+            assert!(matches!(
+                super::descriptor_len(&bytes[..len]),
+                Err(ParserError::InvalidData { .. })
+            ));
+        }
+        match super::descriptor_len(&bytes[..11]) {
+            Err(ParserError::InvalidData { offset, message }) => {
+                assert_eq!(offset, 11);
+                assert_eq!(
+                    message,
+                    "Truncated report descriptor, 1 collections not closed"
+                );
+            }
+            r => panic!("Unexpected result {r:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_items_with() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x02, // Usage (Mouse)
+            0xa1, 0x01, // Collection (Application)
+            0x09, 0x30, // Usage (X)
+            0x75, 0x08, // Report Size (8)
+            0x95, 0x01, // Report Count (1)
+            0x81, 0x06, // Input (Data,Var,Rel)
+            0x09, 0x31, // Usage (Y)
+            0x81, 0x06, // Input (Data,Var,Rel)
+            0xc0, // End Collection
+            0x27, // Truncated Logical Maximum
+        ];
+        let mut headers = vec![];
+        let result = super::parse_items_with(bytes, |item| {
+            headers.push(item.header());
+            match item.item_type() {
+                ItemType::Main(MainItem::Input(_)) => ControlFlow::Break(()),
+                _ => ControlFlow::Continue(()),
+            }
+        });
+        // The truncated item is never reached
+        assert!(result.is_ok());
+        assert_eq!(headers, vec![0x05, 0x09, 0xa1, 0x09, 0x75, 0x95, 0x81]);
+
+        let mut count = 0;
+        let result = super::parse_items_with(bytes, |_| {
+            count += 1;
+            ControlFlow::Continue(())
+        });
+        assert_eq!(count, 10);
+        assert!(matches!(
+            result,
+            Err(ParserError::InvalidData { offset: 19, .. })
+        ));
+    }
+
+    #[test]
+    fn find_report_descriptors_in_config() {
+        // Configuration descriptor (wTotalLength 59) with a boot keyboard and
+        // a boot mouse interface, each with a HID class descriptor and an
+        // endpoint, followed by the 7 and 9 byte report descriptors.
+        let config: &[u8] = &[
+            0x09, 0x02, 0x3b, 0x00, 0x02, 0x01, 0x00, 0xa0, 0x32, 0x09, 0x04, 0x00, 0x00, 0x01,
+            0x03, 0x01, 0x01, 0x00, 0x09, 0x21, 0x11, 0x01, 0x00, 0x01, 0x22, 0x07, 0x00, 0x07,
+            0x05, 0x81, 0x03, 0x08, 0x00, 0x0a, 0x09, 0x04, 0x01, 0x00, 0x01, 0x03, 0x01, 0x02,
+            0x00, 0x09, 0x21, 0x11, 0x01, 0x00, 0x01, 0x22, 0x09, 0x00, 0x07, 0x05, 0x82, 0x03,
+            0x04, 0x00, 0x0a, 0x05, 0x01, 0x09, 0x06, 0xa1, 0x01, 0xc0, 0x05, 0x01, 0x09, 0x02,
+            0xa1, 0x01, 0x09, 0x01, 0xc0,
+        ];
+        let rdescs = find_report_descriptors(config);
+        assert_eq!(rdescs.len(), 2);
+        assert_eq!(rdescs[0], &config[59..66]);
+        assert_eq!(rdescs[1], &config[66..75]);
+        for rdesc in rdescs {
+            assert_eq!(super::descriptor_len(rdesc).unwrap(), rdesc.len());
+        }
+
+        // Without the appended report descriptors there is nothing to return
+        assert!(find_report_descriptors(&config[..59]).is_empty());
+        // Truncated chain
+        assert!(find_report_descriptors(&config[..20]).is_empty());
+    }
+
+    #[test]
+    fn main_item_without_data() {
+        use crate::Report;
+
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x02, // Usage (Mouse)
+            0xa1, 0x01, // Collection (Application)
+            0x05, 0x09, //  Usage Page (Button)
+            0x19, 0x01, //  Usage Minimum (1)
+            0x29, 0x03, //  Usage Maximum (3)
+            0x15, 0x00, //  Logical Minimum (0)
+            0x25, 0x03, //  Logical Maximum (3)
+            0x75, 0x08, //  Report Size (8)
+            0x95, 0x01, //  Report Count (1)
+            0x80, //  Input (Data,Arr,Abs)
+            0x19, 0x01, //  Usage Minimum (1)
+            0x29, 0x03, //  Usage Maximum (3)
+            0x90, //  Output (Data,Arr,Abs)
+            0x19, 0x01, //  Usage Minimum (1)
+            0x29, 0x03, //  Usage Maximum (3)
+            0xb0, //  Feature (Data,Arr,Abs)
+            0xc0, // End Collection
+        ];
+        let items = super::parse_items(bytes).unwrap();
+        let ItemType::Main(MainItem::Input(input)) = items[10].item().item_type() else {
+            panic!("Expected an Input item");
+        };
+        assert!(input.is_data());
+        assert!(input.is_array());
+        assert!(input.is_absolute());
+        assert!(!input.has_null_state());
+        assert!(!input.is_buffered_bytes());
+        let ItemType::Main(MainItem::Output(output)) = items[13].item().item_type() else {
+            panic!("Expected an Output item");
+        };
+        assert!(output.is_data());
+        assert!(output.is_non_volatile());
+
+        let rdesc = crate::ReportDescriptor::try_from(bytes).unwrap();
+        for report in rdesc.reports() {
+            assert!(matches!(report.fields()[0], crate::Field::Array(_)));
+        }
+        assert_eq!(rdesc.len(), 3);
+    }
+
+    #[test]
+    fn iterate_items() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x02, // Usage (Mouse)
+            0xa1, 0x01, // Collection (Application)
+            0x26, 0xff, 0x00, // Logical Maximum (255)
+            0xc0, // End Collection
+        ];
+        let items = ReportDescriptorItems::try_from(bytes).unwrap();
+
+        let mut offsets = vec![];
+        let mut concatenated = vec![];
+        for rdesc_item in &items {
+            offsets.push(rdesc_item.offset());
+            assert_eq!(rdesc_item.bytes(), rdesc_item.item().bytes());
+            concatenated.extend_from_slice(rdesc_item.bytes());
+        }
+        assert_eq!(offsets, vec![0, 2, 4, 6, 9]);
+        assert_eq!(concatenated, bytes);
+
+        let mut count = 0;
+        for rdesc_item in items {
+            assert!(matches!(
+                rdesc_item.item().item_type(),
+                ItemType::Main(_) | ItemType::Global(_) | ItemType::Local(_)
+            ));
+            count += 1;
+        }
+        assert_eq!(count, 5);
+    }
 }
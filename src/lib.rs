@@ -39,12 +39,29 @@
 //!
 //! In this document and unless stated otherwise, a reference to "Section a.b.c" refers to the
 //! [HID Device Class Definition for HID 1.11](https://www.usb.org/document-library/device-class-definition-hid-111).
+//!
+//! # Features
+//!
+//! The `std` feature is enabled by default. Without it, this crate is `no_std` and only
+//! requires `alloc`; helpers that access the filesystem like
+//! `ReportDescriptor::from_sysfs()` are not available.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
 
-use std::hash::{Hash, Hasher};
-use std::ops::Range;
-use thiserror::Error;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::{format, vec, vec::Vec};
+use core::hash::{Hash, Hasher};
+use core::ops::{Range, RangeInclusive};
 
 pub mod hid;
+mod names;
+pub mod pages;
 pub mod types;
 
 pub use hid::CollectionItem as CollectionType;
@@ -87,7 +104,7 @@ fn extract_u32(bytes: &[u8], bits: &Range<usize>) -> u32 {
     let nbits = bits.len();
     assert_ne!(nbits, 0);
     assert!(nbits <= 32);
-    let bytecount = bits.end / 8 - bits.start / 8 + 1;
+    let bytecount = (bits.end - 1) / 8 - bits.start / 8 + 1;
     let base_index = bits.start / 8;
     let bytes = &bytes[base_index..base_index + bytecount];
     let value: u64 = Range {
@@ -143,6 +160,81 @@ fn extract_i8(bytes: &[u8], bits: &Range<usize>) -> i8 {
     v.twos_comp(nbits)
 }
 
+/// Extract the bit range from the given byte array as [i64], sign-extended
+/// from the range's MSB if `signed` is true. This is the extraction used for
+/// both [VariableField]s and the elements of [ArrayField]s.
+///
+/// Returns [ParserError::InvalidReport] for a range of more than 32 bits.
+fn extract_bits(bytes: &[u8], bits: &Range<usize>, signed: bool) -> Result<i64> {
+    let v = match (bits.len(), signed) {
+        (1..=8, false) => i64::from(extract_u8(bytes, bits)),
+        (1..=8, true) => i64::from(extract_i8(bytes, bits)),
+        (9..=16, false) => i64::from(extract_u16(bytes, bits)),
+        (9..=16, true) => i64::from(extract_i16(bytes, bits)),
+        (17..=32, false) => i64::from(extract_u32(bytes, bits)),
+        (17..=32, true) => i64::from(extract_i32(bytes, bits)),
+        (n, _) => {
+            return Err(ParserError::InvalidReport {
+                message: format!("Unable to extract a value of {n} bits"),
+            })
+        }
+    };
+    Ok(v)
+}
+
+/// Returns the logical range as [i64]. A maximum that does not fit into
+/// an [i32] (e.g. 0xffffffff) is stored as its two's complement, so where
+/// the minimum is not negative the maximum is interpreted as unsigned.
+fn logical_range(minimum: LogicalMinimum, maximum: LogicalMaximum) -> RangeInclusive<i64> {
+    let min = i32::from(minimum);
+    let max = i32::from(maximum);
+    if min >= 0 {
+        i64::from(min)..=i64::from(max as u32)
+    } else {
+        i64::from(min)..=i64::from(max)
+    }
+}
+
+/// Returns the physical range as [i64], see [logical_range].
+fn physical_range(minimum: PhysicalMinimum, maximum: PhysicalMaximum) -> RangeInclusive<i64> {
+    let min = i32::from(minimum);
+    let max = i32::from(maximum);
+    if min >= 0 {
+        i64::from(min)..=i64::from(max as u32)
+    } else {
+        i64::from(min)..=i64::from(max)
+    }
+}
+
+/// The byte order of a multi-byte value in a report, see
+/// [VariableField::extract_with_order]. HID reports are always
+/// [ByteOrder::LittleEndian] on the wire.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ByteOrder {
+    #[default]
+    LittleEndian,
+    BigEndian,
+}
+
+/// Insert the value into the bit range of the given byte array, the
+/// inverse of [extract_u32]. Bits of the value that do not fit into the
+/// bit range are discarded, bits outside the bit range are left as-is.
+///
+/// The number of bits in the range must be less or equal to 32.
+fn insert_u32(bytes: &mut [u8], bits: &Range<usize>, value: u32) {
+    let nbits = bits.len();
+    assert_ne!(nbits, 0);
+    assert!(nbits <= 32);
+    for (i, bit) in bits.clone().enumerate() {
+        let mask = 1u8 << (bit % 8);
+        if value & (1 << i) != 0 {
+            bytes[bit / 8] |= mask;
+        } else {
+            bytes[bit / 8] &= !mask;
+        }
+    }
+}
+
 /// Calculates the two's complement for a value with
 /// a given number of of bits.
 trait TwosComplement<To> {
@@ -219,11 +311,23 @@ impl TwosComplement<i32> for u32 {
 /// }
 /// ```
 ///
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct ReportDescriptor {
     input_reports: Vec<RDescReport>,
     output_reports: Vec<RDescReport>,
     feature_reports: Vec<RDescReport>,
+    warnings: Vec<ParseWarning>,
+    usage_resolver: Option<UsageResolver>,
+}
+
+/// The resolver passed to [ReportDescriptor::parse_with_resolver]
+#[derive(Clone)]
+struct UsageResolver(Arc<dyn Fn(u16, u16) -> Option<String> + Send + Sync>);
+
+impl core::fmt::Debug for UsageResolver {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "UsageResolver")
+    }
 }
 
 impl<'a> ReportDescriptor {
@@ -272,9 +376,123 @@ impl<'a> ReportDescriptor {
         &self.feature_reports
     }
 
-    fn find_report(&'a self, list: &'a [RDescReport], prefix: u8) -> Option<&impl Report> {
+    /// Returns an iterator over all input, output and feature
+    /// reports, in that order.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// let nfields: usize = rdesc.reports().map(|r| r.fields().len()).sum();
+    /// for report in rdesc.reports() {
+    ///     println!("{:?} report {:?}", report.direction(), report.report_id());
+    ///     for field in report.fields() {
+    ///         println!("  bits {:?}", field.bits());
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn reports(&self) -> impl Iterator<Item = &impl Report> {
+        self.input_reports
+            .iter()
+            .chain(self.output_reports.iter())
+            .chain(self.feature_reports.iter())
+    }
+
+    /// Returns the report that contains the given field, e.g. a field
+    /// returned by [ReportDescriptor::fields_where], or [None] if the field
+    /// is not from this report descriptor. The field must be a reference
+    /// into this report descriptor, an identical field from another
+    /// report descriptor is not found.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// for field in rdesc.fields_for_usage(pages::usages::X) {
+    ///     let report = rdesc.report_of(field).unwrap();
+    ///     println!("X is in a report of {} bytes", report.size_in_bytes());
+    /// }
+    /// # }
+    /// ```
+    pub fn report_of(&self, field: &Field) -> Option<&impl Report> {
+        self.input_reports
+            .iter()
+            .chain(self.output_reports.iter())
+            .chain(self.feature_reports.iter())
+            .find(|r| r.fields.iter().any(|f| core::ptr::eq(f, field)))
+    }
+
+    /// Returns the total number of input, output and feature reports.
+    pub fn len(&self) -> usize {
+        self.input_reports.len() + self.output_reports.len() + self.feature_reports.len()
+    }
+
+    /// Returns true if this report descriptor has no reports.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the sorted list of distinct Report IDs of the
+    /// input reports or the empty list if the input reports do
+    /// not use Report IDs.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// for report_id in rdesc.input_report_ids() {
+    ///     println!("Input report with Report ID {}", u8::from(report_id));
+    /// }
+    /// # }
+    /// ```
+    pub fn input_report_ids(&self) -> Vec<ReportId> {
+        report_ids(&self.input_reports)
+    }
+
+    /// Returns the sorted list of distinct Report IDs of the
+    /// output reports or the empty list if the output reports do
+    /// not use Report IDs.
+    pub fn output_report_ids(&self) -> Vec<ReportId> {
+        report_ids(&self.output_reports)
+    }
+
+    /// Returns the sorted list of distinct Report IDs of the
+    /// feature reports or the empty list if the feature reports do
+    /// not use Report IDs.
+    pub fn feature_report_ids(&self) -> Vec<ReportId> {
+        report_ids(&self.feature_reports)
+    }
+
+    /// Returns the size in bytes of the largest input report,
+    /// including the Report ID byte (if any), or zero if there
+    /// are no input reports. A buffer of this size can hold any
+    /// input report of this device.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// let mut buffer = vec![0u8; rdesc.max_input_report_size()];
+    /// # }
+    /// ```
+    pub fn max_input_report_size(&self) -> usize {
+        max_report_size(&self.input_reports)
+    }
+
+    /// Returns the size in bytes of the largest output report,
+    /// including the Report ID byte (if any), or zero if there
+    /// are no output reports.
+    pub fn max_output_report_size(&self) -> usize {
+        max_report_size(&self.output_reports)
+    }
+
+    /// Returns the size in bytes of the largest feature report,
+    /// including the Report ID byte (if any), or zero if there
+    /// are no feature reports.
+    pub fn max_feature_report_size(&self) -> usize {
+        max_report_size(&self.feature_reports)
+    }
+
+    fn find_report(&'a self, list: &'a [RDescReport], prefix: u8) -> Option<&'a impl Report> {
         let first = list.first()?;
-        let rid = Some(ReportId(prefix));
+        let rid = ReportId::try_from(prefix).ok();
         // Do we have report IDs? If not, the first report is what we want.
         match first.report_id() {
             None => Some(first),
@@ -317,7 +535,7 @@ impl<'a> ReportDescriptor {
     /// ReportDescriptors with multiple reports require a report
     /// to have a single byte prefix specifying the [ReportId].
     pub fn find_output_report(&self, bytes: &[u8]) -> Option<&impl Report> {
-        self.find_report(&self.input_reports, bytes[0])
+        self.find_report(&self.output_reports, bytes[0])
     }
 
     /// Find the feature report that matches this byte sequence.
@@ -336,947 +554,4120 @@ impl<'a> ReportDescriptor {
     /// ReportDescriptors with multiple reports require a report
     /// to have a single byte prefix specifying the [ReportId].
     pub fn find_feature_report(&self, bytes: &[u8]) -> Option<&impl Report> {
-        self.find_report(&self.input_reports, bytes[0])
+        self.find_report(&self.feature_reports, bytes[0])
     }
-}
 
-impl TryFrom<&[u8]> for ReportDescriptor {
-    type Error = ParserError;
-
-    /// Try to parse the given byte array as a report descriptor.
-    fn try_from(bytes: &[u8]) -> Result<ReportDescriptor> {
-        parse_report_descriptor(bytes)
+    /// Like [find_report](Self::find_report) but checks that the bytes
+    /// are long enough for the report.
+    fn report_for(&'a self, list: &'a [RDescReport], bytes: &[u8]) -> Result<&'a RDescReport> {
+        ensure!(!bytes.is_empty(), ParserError::OutOfBounds);
+        let first = list.first().ok_or(ParserError::MismatchingReportId)?;
+        let report = match first.report_id() {
+            None => first,
+            Some(_) => list
+                .iter()
+                .find(|r| r.report_id() == &ReportId::try_from(bytes[0]).ok())
+                .ok_or(ParserError::MismatchingReportId)?,
+        };
+        ensure!(
+            bytes.len() >= report.size_in_bytes(),
+            ParserError::OutOfBounds
+        );
+        Ok(report)
     }
-}
-
-impl TryFrom<&Vec<u8>> for ReportDescriptor {
-    type Error = ParserError;
 
-    /// Try to parse the given byte array as a report descriptor.
-    fn try_from(bytes: &Vec<u8>) -> Result<ReportDescriptor> {
-        parse_report_descriptor(bytes)
+    /// Find the feature report that matches this byte sequence, e.g. as
+    /// returned by a `HIDIOCGFEATURE` ioctl or a USB GET_REPORT request.
+    ///
+    /// Unlike [find_feature_report](Self::find_feature_report) this returns
+    /// [ParserError::MismatchingReportId] if no report matches and
+    /// [ParserError::OutOfBounds] if the bytes are too short for the report.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(bytes: &[u8], rdesc: &ReportDescriptor) {
+    /// let report = rdesc.feature_report_for(bytes).unwrap();
+    /// for (usage, value) in report.decode(bytes).unwrap() {
+    ///     println!("{usage:?}: {value}");
+    /// }
+    /// # }
+    /// ```
+    pub fn feature_report_for(&self, bytes: &[u8]) -> Result<&impl Report> {
+        self.report_for(&self.feature_reports, bytes)
     }
-}
-
-#[derive(Copy, Clone, Debug)]
-enum Direction {
-    Input,
-    Output,
-    Feature,
-}
 
-/// A HID Input, Output or Feature Report.
-///
-/// Where a report contains the [Report::report_id] the first
-/// byte of the report is always that Report ID, followed
-/// by the data in the sequence announced in the HID [ReportDescriptor].
-///
-/// Use [`size_in_bits()`][Report::size_in_bits] or
-/// [`size_in_bytes()`](Report::size_in_bytes) to
-/// get the length of this report.
-///
-/// Note that each of Input, Output and Feature Reports
-/// have their own enumeration of Report IDs, i.e. an Input Report
-/// with a Report ID of e.g. 1 may have a different size and/or [Field]s
-/// to an Output Report with a Report ID of 1.
-///
-/// The Report ID has no meaning other than to distinguish
-/// different reports. See Section 6.2.2.7 for details.
-pub trait Report {
-    /// Returns the HID Report ID for this report, if any.
-    fn report_id(&self) -> &Option<ReportId>;
+    /// Split bytes that contain one or more concatenated input reports,
+    /// e.g. as delivered by some transports, into the individual reports.
+    /// Each report is looked up by its leading [ReportId] (if any) and
+    /// returned with its [size_in_bytes](Report::size_in_bytes) bytes.
+    ///
+    /// Returns [ParserError::MismatchingReportId] if no input report matches
+    /// and [ParserError::OutOfBounds] if the remaining bytes are too short
+    /// for the report.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(bytes: &[u8], rdesc: &ReportDescriptor) {
+    /// for (report, bytes) in rdesc.split_input_reports(bytes).unwrap() {
+    ///     println!("{:?}", report.decode(bytes).unwrap());
+    /// }
+    /// # }
+    /// ```
+    pub fn split_input_reports<'b>(
+        &self,
+        bytes: &'b [u8],
+    ) -> Result<Vec<(&impl Report, &'b [u8])>> {
+        let mut reports = vec![];
+        let mut remainder = bytes;
+        while !remainder.is_empty() {
+            let report = self.report_for(&self.input_reports, remainder)?;
+            let (report_bytes, rest) = remainder.split_at(report.size_in_bytes());
+            reports.push((report, report_bytes));
+            remainder = rest;
+        }
+        Ok(reports)
+    }
 
-    /// Returns the parsed HID Fields ID for this report. A caller should
-    /// iterate through these fields to find the ones it is interested
-    /// in and use the [Field::bits] to extract the data from future
-    /// reports.
-    fn fields(&self) -> &[Field];
+    /// Returns all fields in all input, output and feature reports
+    /// where the given predicate returns true for the field's [Usage].
+    /// An [ArrayField] matches if any of its usages match the predicate.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// // All Digitizer usages from Tip Pressure onwards
+    /// let fields = rdesc.fields_where(|u| {
+    ///     u16::from(u.usage_page) == 0x0D && u16::from(u.usage_id) >= 0x30
+    /// });
+    /// # }
+    /// ```
+    pub fn fields_where<F: Fn(&Usage) -> bool>(&self, pred: F) -> Vec<&Field> {
+        self.input_reports
+            .iter()
+            .chain(self.output_reports.iter())
+            .chain(self.feature_reports.iter())
+            .flat_map(|r| r.fields())
+            .filter(|f| f.usages().iter().any(|u| pred(&u)))
+            .collect()
+    }
 
-    /// The size in bits for this report.
-    fn size_in_bits(&self) -> usize;
+    /// Returns all fields in all input, output and feature reports
+    /// that carry the given [Usage]. An [ArrayField] matches if the
+    /// usage is one of its usages.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// let x = Usage::from_page_and_id(UsagePage::from(0x01), UsageId::from(0x30));
+    /// for field in rdesc.fields_for_usage(x) {
+    ///     println!("X axis is in bits {:?}", field.bits());
+    /// }
+    /// # }
+    /// ```
+    pub fn fields_for_usage(&self, usage: Usage) -> Vec<&Field> {
+        self.fields_where(|u| *u == usage)
+    }
 
-    /// The size in bytes for this object.
+    /// Returns the usages of all top-level Application collections in
+    /// this report descriptor, in the order they appear.
     ///
-    /// Where [`size_in_bits()`](Report::size_in_bits) is
-    /// not a multiple of 8, the [`size_in_bytes()`](Report::size_in_bytes) rounds up
-    /// fit all bits.
-    fn size_in_bytes(&self) -> usize {
-        (self.size_in_bits() + 7) / 8
+    /// A composite device, e.g. a keyboard with multimedia keys, usually
+    /// has more than one Application collection.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// for usage in rdesc.application_usages() {
+    ///     println!("Application collection: {usage:?}");
+    /// }
+    /// # }
+    /// ```
+    pub fn application_usages(&self) -> Vec<Usage> {
+        let mut seen = BTreeSet::new();
+        let mut applications: Vec<(&CollectionId, Usage)> = self
+            .input_reports
+            .iter()
+            .chain(self.output_reports.iter())
+            .chain(self.feature_reports.iter())
+            .flat_map(|r| r.fields())
+            .filter_map(|f| f.collections().first())
+            .filter(|c| c.collection_type() == CollectionType::Application)
+            .filter(|c| seen.insert(c.id()))
+            .filter_map(|c| c.usages().first().map(|u| (c.id(), *u)))
+            .collect();
+        applications.sort_by_key(|(id, _)| *id);
+        applications.into_iter().map(|(_, u)| u).collect()
     }
-}
 
-/// A HID Input, Output or Feature Report.
-///
-/// Where a report contains the [Report::report_id] the first
-/// byte of the report is always that Report ID, followed
-/// by the data in the sequence announced in the HID [ReportDescriptor].
-///
-/// Note that each of Input, Output and Feature Reports
-/// have their own enumeration of Report IDs, i.e. an Input Report
-/// with a Report ID of e.g. 1 may have a different size and/or [Field]s
-/// to an Output Report with a Report ID of 1.
-///
-/// The Report ID has no meaning other than to distinguish
-/// different reports. See Section 6.2.2.7 for details.
-#[derive(Debug)]
-struct RDescReport {
-    /// The report ID, if any
-    id: Option<ReportId>,
-    /// The size of this report in bits
-    size: usize,
-    /// The fields present in this report
-    fields: Vec<Field>,
-}
+    /// Returns a summary of each top-level Application collection in this
+    /// report descriptor, in the order they appear. See
+    /// [ReportDescriptor::application_usages].
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// for app in rdesc.applications() {
+    ///     println!("{:?}: {:?} with Report IDs {:?}", app.usage, app.directions, app.report_ids);
+    /// }
+    /// # }
+    /// ```
+    pub fn applications(&self) -> Vec<ApplicationInfo> {
+        let mut applications: BTreeMap<&CollectionId, ApplicationInfo> = BTreeMap::new();
+        for report in self
+            .input_reports
+            .iter()
+            .chain(self.output_reports.iter())
+            .chain(self.feature_reports.iter())
+        {
+            let Some(collection) = report
+                .fields
+                .iter()
+                .find_map(|f| f.collections().first())
+                .filter(|c| c.collection_type() == CollectionType::Application)
+            else {
+                continue;
+            };
+            let Some(usage) = collection.usages().first() else {
+                continue;
+            };
+            let info = applications
+                .entry(collection.id())
+                .or_insert_with(|| ApplicationInfo {
+                    usage: *usage,
+                    directions: vec![],
+                    report_ids: vec![],
+                });
+            if !info.directions.contains(&report.direction) {
+                info.directions.push(report.direction);
+            }
+            if let Some(id) = report.id {
+                if !info.report_ids.contains(&id) {
+                    info.report_ids.push(id);
+                }
+            }
+        }
+        applications
+            .into_values()
+            .map(|mut info| {
+                info.report_ids.sort();
+                info
+            })
+            .collect()
+    }
 
-impl Report for RDescReport {
-    fn report_id(&self) -> &Option<ReportId> {
-        &self.id
+    /// Split this report descriptor into one [ReportDescriptor] per
+    /// top-level Application collection, in the order the collections
+    /// appear. Each report is assigned to the Application collection
+    /// of its fields.
+    ///
+    /// This allows treating the logical devices of a composite device,
+    /// e.g. a keyboard with a built-in touchpad, separately. Reports without
+    /// any collection (e.g. only padding) are grouped into a separate
+    /// first [ReportDescriptor].
+    ///
+    /// The returned report descriptors do not have any
+    /// [warnings](ReportDescriptor::warnings).
+    pub fn split_by_application(&self) -> Vec<ReportDescriptor> {
+        let application = |r: &RDescReport| {
+            r.fields()
+                .iter()
+                .find_map(|f| f.collections().first())
+                .map(|c| c.id().clone())
+        };
+        let mut split: BTreeMap<Option<CollectionId>, ReportDescriptor> = BTreeMap::new();
+        for r in &self.input_reports {
+            split
+                .entry(application(r))
+                .or_default()
+                .input_reports
+                .push(r.clone());
+        }
+        for r in &self.output_reports {
+            split
+                .entry(application(r))
+                .or_default()
+                .output_reports
+                .push(r.clone());
+        }
+        for r in &self.feature_reports {
+            split
+                .entry(application(r))
+                .or_default()
+                .feature_reports
+                .push(r.clone());
+        }
+        split
+            .into_values()
+            .map(|rdesc| ReportDescriptor {
+                usage_resolver: self.usage_resolver.clone(),
+                ..rdesc
+            })
+            .collect()
     }
 
-    fn fields(&self) -> &[Field] {
-        &self.fields
+    /// Returns the report that needs to be sent to the device to set the
+    /// given usage, e.g. to switch on an LED. Output reports are searched
+    /// first, followed by the feature reports. Only [VariableField]s and
+    /// [ArrayField]s are considered, a [ConstantField] cannot be set.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// let capslock = Usage::from_page_and_id(pages::LED, UsageId::from(0x02));
+    /// if let Some(report) = rdesc.output_report_for_usage(capslock) {
+    ///     println!("Caps Lock is set via report ID {:?}", report.report_id());
+    /// }
+    /// # }
+    /// ```
+    pub fn output_report_for_usage(&self, usage: Usage) -> Option<&impl Report> {
+        self.output_reports
+            .iter()
+            .chain(self.feature_reports.iter())
+            .find(|r| {
+                r.fields()
+                    .iter()
+                    .any(|f| !f.is_constant() && f.usages().contains(&usage))
+            })
     }
 
-    /// The size of this HID report on the wire, in bits
-    fn size_in_bits(&self) -> usize {
-        self.size
+    /// Decode the given input report bytes into the Report ID (if any)
+    /// and the list of values for each usage in the report, in the order
+    /// they appear in the report.
+    ///
+    /// For a [VariableField] the value is the field's value, for an [ArrayField]
+    /// each element that refers to a usage is returned as that usage with a
+    /// value of 1. Elements outside the logical range or referring to the
+    /// reserved usage ID 0, e.g. Keyboard "No Event" in an unused slot of
+    /// a keyboard's key array, are skipped. [ConstantField]s are skipped.
+    ///
+    /// The bytes may be longer than the report, any extra bytes are ignored.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(bytes: &[u8], rdesc: &ReportDescriptor) {
+    /// let (report_id, values) = rdesc.decode_input(bytes).unwrap();
+    /// for (usage, value) in values {
+    ///     println!("{usage:?}: {value}");
+    /// }
+    /// # }
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn decode_input(&self, data: &[u8]) -> Result<(Option<ReportId>, Vec<(Usage, i64)>)> {
+        let report = self.report_for(&self.input_reports, data)?;
+        Ok((*report.report_id(), report.decode(data)?))
     }
-}
 
-/// The usage of a [Field] defines the interpretation of a
-/// data value. See the `hut` crate for a list of known Usages.
-///
-/// A Usage comprises of a 16 bit [UsagePage] and a 16 bit [UsageId].
-///
-/// ```
-/// # use hidreport::*;
-/// let up = UsagePage::from(0x01); // Generic Desktop
-/// let uid = UsageId::from(0x02); // Mouse
-/// let usage = Usage::from_page_and_id(up, uid);
-/// ```
-/// For known named usages see the `hut` crate.
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Usage {
-    pub usage_page: UsagePage,
-    pub usage_id: UsageId,
-}
-
-impl Usage {
-    /// Create a [Usage] from a [UsagePage] and a [UsageId].
-    pub fn from_page_and_id(usage_page: UsagePage, usage_id: UsageId) -> Usage {
-        Usage {
-            usage_page,
-            usage_id,
-        }
+    /// Decode the value of the [VariableField] with the given usage from
+    /// the given input report bytes.
+    ///
+    /// Returns [None] if the report for these bytes (as identified by the
+    /// Report ID in the first byte, if any) does not have a variable field
+    /// with this usage. The usage may still exist in a different report.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(bytes: &[u8], rdesc: &ReportDescriptor) {
+    /// let wheel = Usage::from_page_and_id(UsagePage::from(0x01), UsageId::from(0x38));
+    /// if let Ok(Some(value)) = rdesc.value_for_usage(wheel, bytes) {
+    ///     println!("Wheel moved by {value}");
+    /// }
+    /// # }
+    /// ```
+    pub fn value_for_usage(&self, usage: Usage, bytes: &[u8]) -> Result<Option<i64>> {
+        let report = self.report_for(&self.input_reports, bytes)?;
+        report
+            .fields()
+            .iter()
+            .find_map(|f| match f {
+                Field::Variable(var) if var.usage == usage => Some(var),
+                _ => None,
+            })
+            .map(|var| var.extract(bytes))
+            .transpose()
     }
-}
 
-impl From<u32> for Usage {
-    fn from(u: u32) -> Usage {
-        Usage {
-            usage_page: UsagePage::from((u >> 16) as u16),
-            usage_id: UsageId::from((u & 0xffff) as u16),
-        }
+    /// Returns the absolute bit range of the first [VariableField] with the
+    /// given usage in any report of the given direction, or [None] if there
+    /// is no such field or it has no bits. See [VariableField::absolute_bits].
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// let wheel = Usage::from_page_and_id(UsagePage::from(0x01), UsageId::from(0x38));
+    /// if let Some(bits) = rdesc.bit_offset_of(wheel, Direction::Input) {
+    ///     println!("Wheel is in bits {} to {}", bits.start(), bits.end());
+    /// }
+    /// # }
+    /// ```
+    pub fn bit_offset_of(&self, usage: Usage, dir: Direction) -> Option<RangeInclusive<usize>> {
+        let reports = match dir {
+            Direction::Input => &self.input_reports,
+            Direction::Output => &self.output_reports,
+            Direction::Feature => &self.feature_reports,
+        };
+        reports
+            .iter()
+            .flat_map(|r| r.fields())
+            .find_map(|f| match f {
+                Field::Variable(var) if var.usage == usage => Some(var),
+                _ => None,
+            })
+            .and_then(|var| var.absolute_bits())
     }
-}
 
-impl From<&Usage> for u32 {
-    fn from(u: &Usage) -> u32 {
-        (u16::from(u.usage_page) as u32) << 16 | u16::from(u.usage_id) as u32
+    /// Returns the usage and absolute bit range of every LED (usage page
+    /// 0x08) [VariableField] in the output reports, e.g. Num Lock, Caps Lock
+    /// and Scroll Lock on a keyboard.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// for (usage, bits) in rdesc.led_usages() {
+    ///     println!("LED {:?} is bit {}", usage.usage_id, bits.start());
+    /// }
+    /// # }
+    /// ```
+    pub fn led_usages(&self) -> Vec<(Usage, RangeInclusive<usize>)> {
+        self.output_reports
+            .iter()
+            .flat_map(|r| r.fields())
+            .filter_map(|f| match f {
+                Field::Variable(var) if var.usage.usage_page == pages::LED => {
+                    var.absolute_bits().map(|bits| (var.usage, bits))
+                }
+                _ => None,
+            })
+            .collect()
     }
-}
-
-impl_from_without_ref!(Usage, u32, u32);
 
-impl From<&Usage> for UsageMinimum {
-    fn from(u: &Usage) -> UsageMinimum {
-        UsageMinimum(u32::from(u))
+    /// Returns the distinct [UsagePage]s of the usages of all fields in all
+    /// reports, sorted by value.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// if rdesc.usage_pages().contains(&pages::LED) {
+    ///     println!("This device has LEDs");
+    /// }
+    /// # }
+    /// ```
+    pub fn usage_pages(&self) -> Vec<UsagePage> {
+        self.reports()
+            .flat_map(|r| r.fields())
+            .flat_map(|f| f.usages())
+            .map(|u| u16::from(u.usage_page))
+            .collect::<BTreeSet<u16>>()
+            .into_iter()
+            .map(UsagePage)
+            .collect()
     }
-}
 
-impl_from_without_ref!(Usage, UsageMinimum, UsageMinimum);
-
-impl From<&Usage> for UsageMaximum {
-    fn from(u: &Usage) -> UsageMaximum {
-        UsageMaximum(u32::from(u))
+    /// Returns the distinct vendor-defined [UsagePage]s (`0xFF00..=0xFFFF`)
+    /// of the usages of all fields in all reports, sorted by value.
+    /// See [ReportDescriptor::usage_pages].
+    pub fn vendor_usage_pages(&self) -> Vec<UsagePage> {
+        self.usage_pages()
+            .into_iter()
+            .filter(|p| p.0 >= 0xff00)
+            .collect()
     }
-}
-
-impl_from_without_ref!(Usage, UsageMaximum, UsageMaximum);
-
-/// A unique (within this report descriptor) identifier for a [Field].
-#[derive(Clone, Copy, Debug, PartialEq, Hash, PartialOrd)]
-pub struct FieldId(u32);
 
-impl From<&FieldId> for u32 {
-    fn from(f: &FieldId) -> u32 {
-        f.0
+    /// Returns true if this report descriptor has a FIDO U2F Authenticator
+    /// Device (usage page 0xF1D0, usage 0x01) application collection,
+    /// e.g. a security key.
+    pub fn is_fido(&self) -> bool {
+        self.reports()
+            .flat_map(|r| r.fields())
+            .filter_map(|f| f.collections().first())
+            .any(|c| {
+                c.collection_type() == CollectionType::Application
+                    && c.usages()
+                        .contains(&pages::usages::U2F_AUTHENTICATOR_DEVICE)
+            })
     }
-}
-
-impl_from_without_ref!(FieldId, u32, u32);
-
-/// A single field inside a [Report].
-///
-/// Fields may be [Field::Variable] and represent a
-/// single element of data or they may be
-/// a [Field::Array] that represent
-/// multiple elements.
-///
-/// Fields of type [Field::Constant] should be ignored by
-/// the caller.
-#[derive(Clone, Debug)]
-pub enum Field {
-    Variable(VariableField),
-    Array(ArrayField),
-    Constant(ConstantField),
-}
 
-impl Field {
-    pub fn id(&self) -> FieldId {
-        match self {
-            Field::Variable(f) => f.id,
-            Field::Array(f) => f.id,
-            Field::Constant(f) => f.id,
-        }
-    }
-    /// Returns the bit range that make up this field.
-    pub fn bits(&self) -> &Range<usize> {
-        match self {
-            Field::Variable(f) => &f.bits,
-            Field::Array(f) => &f.bits,
-            Field::Constant(f) => &f.bits,
-        }
+    /// Returns the sum of the sizes in bits of all input reports, not
+    /// counting the Report ID byte of each report.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// println!("All input reports carry {} bits of data", rdesc.total_input_bits());
+    /// # }
+    /// ```
+    pub fn total_input_bits(&self) -> usize {
+        self.input_reports
+            .iter()
+            .map(|r| match r.id {
+                Some(_) => r.size - 8,
+                None => r.size,
+            })
+            .sum()
     }
 
-    /// Returns the Report ID this field belongs to, if any.
-    fn report_id(&self) -> &Option<ReportId> {
-        match self {
-            Field::Variable(f) => &f.report_id,
-            Field::Array(f) => &f.report_id,
-            Field::Constant(f) => &f.report_id,
+    /// Compare this report descriptor to another one, e.g. the report
+    /// descriptor of a newer firmware version, and return the differences.
+    ///
+    /// Reports are matched by direction and [ReportId], fields within a
+    /// report by their (first) [Usage] and, where a usage is used more than
+    /// once, by the order of the fields. [ConstantField]s are ignored.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(old: &ReportDescriptor, new: &ReportDescriptor) {
+    /// for diff in old.diff(new) {
+    ///     println!("{diff:?}");
+    /// }
+    /// # }
+    /// ```
+    pub fn diff(&self, other: &ReportDescriptor) -> Vec<DescriptorDiff> {
+        let mut diffs = vec![];
+        for (direction, old, new) in [
+            (Direction::Input, &self.input_reports, &other.input_reports),
+            (
+                Direction::Output,
+                &self.output_reports,
+                &other.output_reports,
+            ),
+            (
+                Direction::Feature,
+                &self.feature_reports,
+                &other.feature_reports,
+            ),
+        ] {
+            diff_reports(direction, old, new, &mut diffs);
         }
+        diffs
     }
 
-    fn update_bit_offset(&mut self, offset: usize) {
-        let r = self.bits();
-        let r = (offset + r.start)..(offset + r.end);
-        match self {
-            Field::Variable(f) => f.bits = r,
-            Field::Array(f) => f.bits = r,
-            Field::Constant(f) => f.bits = r,
-        };
+    /// Returns the list of non-fatal issues found while parsing
+    /// this report descriptor.
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
     }
 
-    /// The length of the field in bits
-    fn len(&self) -> usize {
-        return self.bits().len();
+    /// Returns true if two reports of the same direction (Input, Output or
+    /// Feature) have the same Report ID.
+    ///
+    /// This cannot happen for a report descriptor parsed by this crate
+    /// and serves as a sanity check only.
+    pub fn has_duplicate_report_ids(&self) -> bool {
+        [
+            &self.input_reports,
+            &self.output_reports,
+            &self.feature_reports,
+        ]
+        .iter()
+        .any(|reports| !duplicate_report_ids(reports).is_empty())
     }
 
-    pub fn collections(&self) -> &[Collection] {
-        match self {
-            Field::Variable(f) => &f.collections,
-            Field::Array(f) => &f.collections,
-            Field::Constant(..) => &[],
+    /// Change the [ReportId] of the report of the given direction with the
+    /// `old` Report ID to `new`, see [Report::set_id].
+    ///
+    /// Returns [ParserError::InvalidReport] and leaves this report
+    /// descriptor unchanged if there is no such report, if another report
+    /// of the same direction already uses the `new` Report ID, or if the
+    /// change would result in reports both with and without a Report ID.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &mut ReportDescriptor) -> Result<(), ParserError> {
+    /// rdesc.set_report_id(Direction::Input, ReportId::try_from(1).ok(), ReportId::try_from(5).ok())?;
+    /// let bytes = rdesc.to_bytes();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_report_id(
+        &mut self,
+        direction: Direction,
+        old: Option<ReportId>,
+        new: Option<ReportId>,
+    ) -> Result<()> {
+        let reports = match direction {
+            Direction::Input => &mut self.input_reports,
+            Direction::Output => &mut self.output_reports,
+            Direction::Feature => &mut self.feature_reports,
+        };
+        let Some(idx) = reports.iter().position(|r| r.id == old) else {
+            return Err(ParserError::InvalidReport {
+                message: format!(
+                    "No {direction:?} report with Report ID {}",
+                    old.map(u8::from).unwrap_or(0)
+                ),
+            });
+        };
+        if old == new {
+            return Ok(());
         }
+        let others = || {
+            reports
+                .iter()
+                .enumerate()
+                .filter(move |(i, _)| *i != idx)
+                .map(|(_, r)| r)
+        };
+        ensure!(
+            others().all(|r| r.id.is_some() == new.is_some()),
+            ParserError::InvalidReport {
+                message: format!("{direction:?} reports both with and without a Report ID"),
+            }
+        );
+        ensure!(
+            others().all(|r| r.id != new),
+            ParserError::InvalidReport {
+                message: format!(
+                    "{direction:?} Report ID {} is used by more than one report",
+                    new.map(u8::from).unwrap_or(0)
+                ),
+            }
+        );
+        reports[idx].set_id(new);
+        Ok(())
     }
-}
 
-/// A [VariableField] represents a single physical control.
-#[derive(Clone, Debug)]
-pub struct VariableField {
-    id: FieldId,
-    report_id: Option<ReportId>,
-    pub bits: Range<usize>,
-    pub usage: Usage,
-    pub logical_minimum: LogicalMinimum,
-    pub logical_maximum: LogicalMaximum,
-    pub physical_minimum: Option<PhysicalMinimum>,
-    pub physical_maximum: Option<PhysicalMaximum>,
-    pub unit: Option<Unit>,
-    pub unit_exponent: Option<UnitExponent>,
-    pub collections: Vec<Collection>,
-}
+    /// Merge adjacent [ConstantField]s in every report of this report
+    /// descriptor, see [Report::coalesce_padding].
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &mut ReportDescriptor) {
+    /// rdesc.coalesce_padding();
+    /// let bytes = rdesc.to_bytes();
+    /// # }
+    /// ```
+    pub fn coalesce_padding(&mut self) {
+        self.input_reports
+            .iter_mut()
+            .chain(self.output_reports.iter_mut())
+            .chain(self.feature_reports.iter_mut())
+            .for_each(|r| r.coalesce_padding());
+    }
 
-impl VariableField {
-    /// Returns true if this field contains signed values,
-    /// i.e. the LogicalMinimum is less than zero.
-    pub fn is_signed(&self) -> bool {
-        self.logical_minimum < LogicalMinimum(0)
+    /// Returns the first input [VariableField] with the Battery Strength usage
+    /// (Generic Device Controls page), if any.
+    pub fn battery_strength_field(&self) -> Option<&VariableField> {
+        self.input_reports
+            .iter()
+            .find_map(|r| battery_strength_field(r))
     }
 
-    /// Extract this field's value as [u32] from a report's bytes.
-    /// The value is extracted as its correct bit size but upcasted
-    /// if need be into a [u32]. IOW it is safe to call this function
-    /// on e.g. an 8 bit unsigned field in the report.
+    /// Returns the first feature [VariableField] with the Resolution
+    /// Multiplier usage (Generic Desktop), if any. This is the multiplier of
+    /// the first entry of [resolution_multipliers](Self::resolution_multipliers),
+    /// use that for devices with more than one multiplier.
     ///
-    /// Check [VariableField::is_signed] first to see if you should be
-    /// using [VariableField::extract_i32] instead.
-    pub fn extract_u32(&self, bytes: &[u8]) -> Result<u32> {
-        if let Some(report_id) = self.report_id {
-            if ReportId(bytes[0]) != report_id {
-                return Err(ParserError::MismatchingReportId);
-            }
-        }
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// if rdesc.resolution_multiplier_field().is_some() {
+    ///     println!("This device supports high-resolution scrolling");
+    /// }
+    /// # }
+    /// ```
+    pub fn resolution_multiplier_field(&self) -> Option<&VariableField> {
+        self.feature_reports
+            .iter()
+            .flat_map(|r| r.fields())
+            .find_map(|f| match f {
+                Field::Variable(v) if v.usage == pages::usages::RESOLUTION_MULTIPLIER => Some(v),
+                _ => None,
+            })
+    }
 
-        let v = match self.bits.len() {
-            1..=8 => extract_u8(bytes, &self.bits) as u32,
-            9..=16 => extract_u16(bytes, &self.bits) as u32,
-            17..=32 => extract_u32(bytes, &self.bits),
-            n => panic!("invalid data length {n}"),
-        };
+    /// Returns each feature [VariableField] with the Resolution Multiplier
+    /// usage (Generic Desktop) together with the [scroll fields](Self::scroll_fields)
+    /// it applies to, i.e. those in the same collection as the multiplier.
+    /// Mice typically have one multiplier per Logical collection, one for
+    /// the wheel and one for the horizontal wheel (AC Pan).
+    ///
+    /// Mice with high-resolution scrolling use these fields to switch the
+    /// scroll fields from one value per detent to multiple values per
+    /// detent. The multiplier applied is the field's physical value.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// for (multiplier, scroll_fields) in rdesc.resolution_multipliers() {
+    ///     for field in scroll_fields {
+    ///         println!("{:?} has a resolution multiplier at bits {:?}", field.usage, multiplier.bits);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn resolution_multipliers(&self) -> Vec<(&VariableField, Vec<&VariableField>)> {
+        let scroll_fields = self.scroll_fields();
+        self.feature_reports
+            .iter()
+            .flat_map(|r| r.fields())
+            .filter_map(|f| match f {
+                Field::Variable(v) if v.usage == pages::usages::RESOLUTION_MULTIPLIER => Some(v),
+                _ => None,
+            })
+            .map(|multiplier| {
+                let applies = |field: &VariableField| match multiplier.collections.last() {
+                    Some(c) => field.collections.iter().any(|fc| fc.id() == c.id()),
+                    None => true,
+                };
+                let fields = scroll_fields
+                    .iter()
+                    .copied()
+                    .filter(|f| applies(f))
+                    .collect();
+                (multiplier, fields)
+            })
+            .collect()
+    }
 
-        Ok(v)
+    /// Returns all input [VariableField]s with the Wheel (Generic Desktop)
+    /// or AC Pan (Consumer) usage, in report order.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// for field in rdesc.scroll_fields() {
+    ///     println!("Scroll field {:?} at bits {:?}", field.usage, field.bits);
+    /// }
+    /// # }
+    /// ```
+    pub fn scroll_fields(&self) -> Vec<&VariableField> {
+        self.input_reports
+            .iter()
+            .flat_map(|r| r.fields())
+            .filter_map(|f| match f {
+                Field::Variable(v)
+                    if v.usage == pages::usages::WHEEL || v.usage == pages::usages::AC_PAN =>
+                {
+                    Some(v)
+                }
+                _ => None,
+            })
+            .collect()
     }
 
-    /// Extract this field's value as [i32] from a report's bytes.
-    /// The value is extracted as its correct bit size but upcasted
-    /// if need be into a [i32]. IOW it is safe to call this function
-    /// on e.g. an 8 bit signed field in the report.
+    /// Decode the Battery Strength from the given input report bytes and
+    /// return it as percentage in the range `0..=100`, mapped from the field's
+    /// logical range.
     ///
-    /// Check [VariableField::is_signed] first to see if you should be
-    /// using [VariableField::extract_u32] instead.
-    pub fn extract_i32(&self, bytes: &[u8]) -> Result<i32> {
-        if let Some(report_id) = self.report_id {
-            if ReportId(bytes[0]) != report_id {
-                return Err(ParserError::MismatchingReportId);
-            }
+    /// Returns [None] if the report for the given bytes does not have a
+    /// battery field. Where the device has more than one report with a
+    /// battery field, the one in the report for the given bytes is used.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(bytes: &[u8], rdesc: &ReportDescriptor) {
+    /// if let Some(percent) = rdesc.battery_percentage(bytes) {
+    ///     println!("Battery is at {percent}%");
+    /// }
+    /// # }
+    /// ```
+    pub fn battery_percentage(&self, data: &[u8]) -> Option<u8> {
+        if data.is_empty() {
+            return None;
         }
+        let report = self.find_input_report(data)?;
+        if data.len() < report.size_in_bytes() {
+            return None;
+        }
+        let battery = battery_strength_field(report)?;
 
-        let v = match self.bits.len() {
-            1..=8 => extract_i8(bytes, &self.bits) as i32,
-            9..=16 => extract_i16(bytes, &self.bits) as i32,
-            17..=32 => extract_i32(bytes, &self.bits),
-            n => panic!("invalid data length {n}"),
-        };
+        let (min, max) =
+            logical_range(battery.logical_minimum, battery.logical_maximum).into_inner();
+        if max <= min {
+            return None;
+        }
+        let value = battery.extract(data).ok()?;
+        let percent = ((value - min) * 100 / (max - min)).clamp(0, 100);
 
-        Ok(v)
+        Some(percent as u8)
     }
-}
 
-/// Wrapper around the commonly used [UsageMinimum] and [UsageMaximum].
-#[derive(Debug)]
-pub struct UsageRange {
-    usage_page: UsagePage,
-    minimum: UsageMinimum,
-    maximum: UsageMaximum,
-}
-
-impl UsageRange {
-    /// The [UsageMinimum]. Note that in reports and report descriptors
-    /// the Usage Minimum may or may not include the Usage Page. The
-    /// minimum returned here always includes the [UsagePage].
-    pub fn minimum(&self) -> UsageMinimum {
-        self.minimum
+    /// Returns true if this report descriptor has a Generic Desktop Keyboard
+    /// application with an input report in the layout of the boot protocol
+    /// keyboard (Appendix B.1): without a [ReportId], 8 modifier bits,
+    /// a reserved byte and an array of 6 keys of 8 bits each.
+    ///
+    /// A device with this report may be used as-is by a BIOS or other
+    /// host that only supports the boot protocol.
+    pub fn is_boot_keyboard(&self) -> bool {
+        self.input_reports.iter().any(is_boot_keyboard_report)
     }
 
-    /// The [UsageMaximum]. Note that in reports and report descriptors
-    /// the Usage Maximum may or may not include the Usage Page. The
-    /// maximum returned here always includes the [UsagePage].
-    pub fn maximum(&self) -> UsageMaximum {
-        self.maximum
+    /// Returns true if this report descriptor has a Generic Desktop Mouse
+    /// application with an input report in the layout of the boot protocol
+    /// mouse (Appendix B.2): without a [ReportId], 3 buttons in the first
+    /// byte followed by 8-bit X and Y axes. Additional fields after those,
+    /// e.g. a wheel, are permitted.
+    pub fn is_boot_mouse(&self) -> bool {
+        self.input_reports.iter().any(is_boot_mouse_report)
     }
 
-    /// If the given usage falls within this usage range (i.e. it is of the
-    /// same [UsagePage] and it within the inclusive [UsageMinimum]/[UsageMaximum])
-    /// return the provided usage as [Option].
-    pub fn lookup_usage<'a>(&self, usage: &'a Usage) -> Option<&'a Usage> {
-        if usage.usage_page == self.usage_page
-            && usage.usage_id >= self.minimum.usage_id()
-            && usage.usage_id <= self.maximum.usage_id()
-        {
-            Some(usage)
-        } else {
-            None
+    /// Returns a best guess of the type of device this report descriptor
+    /// belongs to, based on its top-level Application collections and,
+    /// where those are inconclusive, its fields.
+    ///
+    /// Touchscreens, touchpads and other digitizers take precedence over
+    /// other Application collections since those devices commonly have
+    /// an additional Mouse or Keyboard collection. Otherwise the first
+    /// recognized Application collection determines the type.
+    ///
+    /// This is a heuristic only, suitable for triage and logging.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// if rdesc.guess_device_type() == DeviceType::Touchpad {
+    ///     println!("This looks like a touchpad");
+    /// }
+    /// # }
+    /// ```
+    pub fn guess_device_type(&self) -> DeviceType {
+        let usages: Vec<Usage> = self
+            .reports()
+            .flat_map(|r| r.fields())
+            .flat_map(|f| f.usages())
+            .collect();
+        let has_usage = |usage: Usage| usages.contains(&usage);
+        let has_page = |page: UsagePage| usages.iter().any(|u| u.usage_page == page);
+        let types: Vec<DeviceType> = self
+            .application_usages()
+            .into_iter()
+            .map(|usage| match usage {
+                pages::usages::TOUCH_SCREEN => DeviceType::Touchscreen,
+                pages::usages::TOUCH_PAD => DeviceType::Touchpad,
+                u if u.usage_page == pages::DIGITIZERS => DeviceType::Digitizer,
+                pages::usages::GAMEPAD | pages::usages::JOYSTICK => DeviceType::Gamepad,
+                pages::usages::MOUSE | pages::usages::POINTER
+                    if has_usage(pages::usages::CONTACT_IDENTIFIER) =>
+                {
+                    DeviceType::Touchpad
+                }
+                pages::usages::MOUSE | pages::usages::POINTER => DeviceType::Mouse,
+                pages::usages::KEYBOARD | pages::usages::KEYPAD => DeviceType::Keyboard,
+                pages::usages::CONSUMER_CONTROL => DeviceType::ConsumerControl,
+                u if u16::from(u.usage_page) >= 0xff00 => DeviceType::Vendor,
+                _ => DeviceType::Unknown,
+            })
+            .collect();
+
+        let digitizer = [
+            DeviceType::Touchscreen,
+            DeviceType::Touchpad,
+            DeviceType::Digitizer,
+        ]
+        .into_iter()
+        .find(|t| types.contains(t));
+        let recognized = types
+            .iter()
+            .find(|t| !matches!(t, DeviceType::Vendor | DeviceType::Unknown));
+        if let Some(t) = digitizer.or(recognized.copied()) {
+            return t;
         }
-    }
 
-    /// Look up the given [UsageId] and return the corresponding
-    /// [Usage], if any. The [UsageId] is assumed to be in the same
-    /// [UsagePage] as this range, use [lookup_usage()][Self::lookup_usage] if you need
-    /// a check for the [UsagePage] as well.
-    pub fn lookup_id(&self, id: UsageId) -> Option<Usage> {
-        if id >= self.minimum.usage_id() && id <= self.maximum.usage_id() {
-            Some(Usage::from_page_and_id(self.usage_page, id))
+        // No (recognized) Application collection, look at the fields
+        if has_page(pages::KEYBOARD) {
+            DeviceType::Keyboard
+        } else if has_usage(pages::usages::X) && has_page(pages::BUTTON) {
+            DeviceType::Mouse
+        } else if types.contains(&DeviceType::Vendor) {
+            DeviceType::Vendor
         } else {
-            None
+            DeviceType::Unknown
         }
     }
 }
 
-/// An [ArrayField] represents a group of physical controls,
-/// see section 6.2.2.5.
-///
-/// > An array provides an alternate means for
-/// > describing the data returned from a group of
-/// > buttons. Arrays are more efficient, if less flexible
-/// > than variable items. Rather than returning a single
-/// > bit for each button in the group, an array returns an
-/// > index in each field that corresponds to the pressed
-/// > button
-#[derive(Clone, Debug)]
-pub struct ArrayField {
-    id: FieldId,
-    report_id: Option<ReportId>,
-    pub bits: Range<usize>,
-    usages: Vec<Usage>,
-    pub report_count: ReportCount,
-    pub logical_minimum: LogicalMinimum,
-    pub logical_maximum: LogicalMaximum,
-    pub physical_minimum: Option<PhysicalMinimum>,
-    pub physical_maximum: Option<PhysicalMaximum>,
-    pub unit: Option<Unit>,
-    pub unit_exponent: Option<UnitExponent>,
-    pub collections: Vec<Collection>,
-}
-
-impl ArrayField {
-    /// Returns the set of usages for this field. This is the
-    /// inclusive range of [UsageMinimum]`..=`[UsageMaximum]
-    /// as defined for this field.
+impl ReportDescriptor {
+    /// Build a report descriptor from the given items, e.g. after editing
+    /// the items returned by [hid::parse_items]. This is equivalent to
+    /// [ReportDescriptor::try_from] on the bytes of those items.
     ///
-    /// In most cases it's better to use [usage_range()](Self::usage_range)
-    /// instead.
-    pub fn usages(&self) -> &[Usage] {
-        &self.usages
-    }
-
-    /// Returns the [UsageRange] for this field.
-    pub fn usage_range(&self) -> UsageRange {
-        let min = self.usages.first().unwrap();
-        let max = self.usages.last().unwrap();
-
-        UsageRange {
-            usage_page: min.usage_page,
-            minimum: UsageMinimum::from(min),
-            maximum: UsageMaximum::from(max),
-        }
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(bytes: &[u8]) {
+    /// let items = hid::parse_items(bytes).unwrap();
+    /// let rdesc = ReportDescriptor::from_items(&items).unwrap();
+    /// # }
+    /// ```
+    ///
+    /// Errors refer to the [offset](hid::ReportDescriptorItem::offset) of
+    /// the respective item.
+    pub fn from_items(items: &hid::ReportDescriptorItems) -> Result<ReportDescriptor> {
+        ensure!(!items.is_empty(), "Empty report descriptor");
+        build_report_descriptor(items, ParseOptions::default())
     }
 
-    /// Returns true if this field contains signed values,.
-    /// i.e. the LogicalMinimum is less than zero.
+    /// Parse the report descriptor with the given [ParseOptions]. With the
+    /// default options this is equivalent to [ReportDescriptor::try_from].
+    ///
     /// ```
-    /// # use hidreport::ArrayField;
-    /// # fn func(field: &ArrayField, bytes: &[u8]) {
-    /// if field.is_signed() {
-    ///     println!("Signed values: {:?}", field.extract_i32(bytes).unwrap());
-    /// } else {
-    ///     println!("Unsigned values: {:?}", field.extract_u32(bytes).unwrap());
+    /// # use hidreport::*;
+    /// # fn func(bytes: &[u8]) {
+    /// let options = ParseOptions::default().strict(true);
+    /// match ReportDescriptor::parse_with_options(bytes, options) {
+    ///     Ok(rdesc) => println!("{rdesc}"),
+    ///     Err(e) => println!("Invalid report descriptor: {e}"),
     /// }
-    ///
     /// # }
     /// ```
-    pub fn is_signed(&self) -> bool {
-        self.logical_minimum < LogicalMinimum(0)
+    pub fn parse_with_options(bytes: &[u8], options: ParseOptions) -> Result<ReportDescriptor> {
+        ensure!(!bytes.is_empty(), "Empty report descriptor");
+        let items = hid::ReportDescriptorItems::try_from(bytes)?;
+        let rdesc = build_report_descriptor(&items, options)?;
+        if options.strict {
+            rdesc.check_validity()?;
+        }
+        Ok(rdesc)
     }
 
-    /// Extract this field's values as [u32]s from a report's bytes.
-    /// The values are extracted at their correct bit size but upcasted
-    /// if need be into a [u32]. IOW it is safe to call this function
-    /// on e.g. an 8 bit unsigned field in the report.
+    /// Returns the boot protocol keyboard report descriptor from Appendix
+    /// B.1: 8 modifier keys, a reserved byte and an array of 6 keys in the
+    /// input report and 5 LEDs in the output report. See
+    /// [ReportDescriptor::is_boot_keyboard].
     ///
-    /// Check [ArrayField::is_signed] first to see if you should be
-    /// using [ArrayField::extract_i32] instead.
-    pub fn extract_u32(&self, bytes: &[u8]) -> Result<Vec<u32>> {
-        if let Some(report_id) = self.report_id {
-            if ReportId(bytes[0]) != report_id {
-                return Err(ParserError::MismatchingReportId);
-            }
-        }
-        let count = usize::from(self.report_count);
-        let values: Result<Vec<u32>> = (0..count)
-            .map(|idx| self.extract_one_u32(bytes, idx))
-            .collect();
+    /// ```
+    /// # use hidreport::*;
+    /// let rdesc = ReportDescriptor::boot_keyboard();
+    /// assert_eq!(rdesc.input_reports()[0].size_in_bytes(), 8);
+    /// ```
+    pub fn boot_keyboard() -> ReportDescriptor {
+        ReportDescriptor::try_from(BOOT_KEYBOARD_RDESC).unwrap()
+    }
 
-        values
+    /// Returns the boot protocol mouse report descriptor from Appendix
+    /// B.2: 3 buttons followed by relative 8-bit X and Y axes. See
+    /// [ReportDescriptor::is_boot_mouse].
+    pub fn boot_mouse() -> ReportDescriptor {
+        ReportDescriptor::try_from(BOOT_MOUSE_RDESC).unwrap()
     }
 
-    /// Extract this field's values as [i32]s from a report's bytes.
-    /// The values are extracted at their correct bit size but upcasted
-    /// if need be into a [i32]. IOW it is safe to call this function
-    /// on e.g. an 8 bit signed field in the report.
+    /// Parse the report descriptor like [ReportDescriptor::try_from] and use
+    /// the resolver to name usages, e.g. on vendor-defined usage pages, in the
+    /// [Display](core::fmt::Display) output. The resolver is called with the
+    /// usage page and usage id of every usage without a built-in name and
+    /// may return the full description of that usage.
     ///
-    /// Check [ArrayField::is_signed] first to see if you should be
-    /// using [ArrayField::extract_u32] instead.
-    pub fn extract_i32(&self, bytes: &[u8]) -> Result<Vec<i32>> {
-        if let Some(report_id) = self.report_id {
-            if ReportId(bytes[0]) != report_id {
-                return Err(ParserError::MismatchingReportId);
-            }
-        }
-
-        let count = usize::from(self.report_count);
-        let values: Result<Vec<i32>> = (0..count)
-            .map(|idx| self.extract_one_i32(bytes, idx))
-            .collect();
-
-        values
+    /// Only the [Display](core::fmt::Display) output of the [ReportDescriptor]
+    /// uses the resolver, [Report::to_field_map] and [Report::explain] do not
+    /// have access to it and always use the built-in names.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(bytes: &[u8]) {
+    /// let rdesc = ReportDescriptor::parse_with_resolver(bytes, |page, id| match (page, id) {
+    ///     (0xff00, 0x01) => Some("Acme/Brightness".into()),
+    ///     _ => None,
+    /// })
+    /// .unwrap();
+    /// println!("{rdesc}");
+    /// # }
+    /// ```
+    pub fn parse_with_resolver(
+        bytes: &[u8],
+        resolver: impl Fn(u16, u16) -> Option<String> + Send + Sync + 'static,
+    ) -> Result<ReportDescriptor> {
+        let mut rdesc = ReportDescriptor::try_from(bytes)?;
+        rdesc.usage_resolver = Some(UsageResolver(Arc::new(resolver)));
+        Ok(rdesc)
     }
 
-    /// Extract a single value from this array. See [ArrayField::extract_u32].
+    /// Parse the report descriptor and check the resulting reports, returning
+    /// the first issue as [ParserError::InvalidReport]. In addition to
+    /// [ReportDescriptor::try_from] this checks that
+    /// - no data main item has a Report Count of zero, this is returned
+    ///   as [ParserError::InvalidData] at the offset of that item,
+    /// - no data main item has a Logical Maximum that does not fit into
+    ///   its Report Size, likewise returned as [ParserError::InvalidData],
+    /// - every report passes [Report::validate],
+    /// - no two reports of the same direction have the same [ReportId],
+    /// - no report uses the reserved [ReportId] 0.
     ///
-    /// The index must be less than [Self::report_count].
-    pub fn extract_one_u32(&self, bytes: &[u8], idx: usize) -> Result<u32> {
-        if idx >= usize::from(self.report_count) {
-            return Err(ParserError::OutOfBounds);
+    /// Use [ReportDescriptor::try_from] to inspect report descriptors that
+    /// fail these checks.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(bytes: &[u8]) {
+    /// match ReportDescriptor::parse_validated(bytes) {
+    ///     Ok(rdesc) => println!("{rdesc}"),
+    ///     Err(e) => println!("Invalid report descriptor: {e}"),
+    /// }
+    /// # }
+    /// ```
+    pub fn parse_validated(bytes: &[u8]) -> Result<ReportDescriptor> {
+        let rdesc = ReportDescriptor::try_from(bytes)?;
+        rdesc.check_validity()?;
+        Ok(rdesc)
+    }
+
+    /// The checks of [ReportDescriptor::parse_validated].
+    fn check_validity(&self) -> Result<()> {
+        for warning in self.warnings() {
+            match warning {
+                ParseWarning::ZeroReportCount { offset } => {
+                    return Err(ParserError::InvalidData {
+                        offset: *offset,
+                        message: "Report Count of 0 for a data main item".into(),
+                    });
+                }
+                ParseWarning::LogicalMaximumTooLarge { offset, .. } => {
+                    return Err(ParserError::InvalidData {
+                        offset: *offset,
+                        message: "Logical Maximum does not fit into the Report Size".into(),
+                    });
+                }
+                ParseWarning::DuplicateReportId { .. } => {}
+            }
         }
-        if let Some(report_id) = self.report_id {
-            if ReportId(bytes[0]) != report_id {
-                return Err(ParserError::MismatchingReportId);
+        for (direction, reports) in [
+            (Direction::Input, &self.input_reports),
+            (Direction::Output, &self.output_reports),
+            (Direction::Feature, &self.feature_reports),
+        ] {
+            for report in reports {
+                report.validate()?;
+                ensure!(
+                    report.id != Some(ReportId(0)),
+                    ParserError::InvalidReport {
+                        message: format!("{direction:?} report uses the reserved Report ID 0"),
+                    }
+                );
+            }
+            if let Some(report_id) = duplicate_report_ids(reports).first() {
+                return Err(ParserError::InvalidReport {
+                    message: format!(
+                        "{direction:?} Report ID {} is used by more than one report",
+                        report_id.map(u8::from).unwrap_or(0)
+                    ),
+                });
             }
         }
-
-        let count = usize::from(self.report_count);
-        let bits_per_report = self.bits.len() / count;
-
-        let offset = self.bits.start + bits_per_report * idx;
-        let bits = offset..offset + bits_per_report;
-        let v = match bits.len() {
-            1..=8 => extract_u8(bytes, &bits) as u32,
-            9..=16 => extract_u16(bytes, &bits) as u32,
-            17..=32 => extract_u32(bytes, &bits),
-            n => panic!("invalid data length {n}"),
-        };
-
-        Ok(v)
+        Ok(())
     }
 
-    /// Extract a single value from this array. See [ArrayField::extract_i32].
+    /// Compile this report descriptor back into the bytes of a HID
+    /// report descriptor, e.g. to save a modified report descriptor.
     ///
-    /// The index must be less than [Self::report_count].
-    pub fn extract_one_i32(&self, bytes: &[u8], idx: usize) -> Result<i32> {
-        if idx >= usize::from(self.report_count) {
-            return Err(ParserError::OutOfBounds);
-        }
-        if let Some(report_id) = self.report_id {
-            if ReportId(bytes[0]) != report_id {
-                return Err(ParserError::MismatchingReportId);
-            }
+    /// The returned bytes are not byte-identical to the report descriptor
+    /// this [ReportDescriptor] was parsed from but parsing them results in
+    /// a [ReportDescriptor] with the same reports and fields.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// let bytes = rdesc.to_bytes();
+    /// let copy = ReportDescriptor::try_from(&bytes).unwrap();
+    /// assert_eq!(rdesc.input_reports().len(), copy.input_reports().len());
+    /// # }
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut serializer = Serializer::new();
+        for (direction, reports) in [
+            (Direction::Input, &self.input_reports),
+            (Direction::Output, &self.output_reports),
+            (Direction::Feature, &self.feature_reports),
+        ] {
+            reports
+                .iter()
+                .flat_map(|r| r.fields())
+                .for_each(|f| serializer.field(f, direction));
         }
+        serializer.finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl ReportDescriptor {
+    /// Read and parse the report descriptor from the given file, typically
+    /// the sysfs file of a hidraw device on Linux, e.g.
+    /// `/sys/class/hidraw/hidraw0/device/report_descriptor`.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func() -> std::io::Result<()> {
+    /// let rdesc = ReportDescriptor::from_sysfs("/sys/class/hidraw/hidraw0/device/report_descriptor")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// A [ParserError] is converted into an [std::io::Error] of kind
+    /// [std::io::ErrorKind::InvalidData].
+    pub fn from_sysfs(path: impl AsRef<std::path::Path>) -> std::io::Result<ReportDescriptor> {
+        let bytes = std::fs::read(path)?;
+        Ok(ReportDescriptor::try_from(&bytes)?)
+    }
 
-        let count = usize::from(self.report_count);
-        let bits_per_report = self.bits.len() / count;
+    /// Read the given stream to its end and parse the bytes as
+    /// report descriptor, e.g. to read a report descriptor from stdin.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func() -> Result<(), ParserError> {
+    /// let rdesc = ReportDescriptor::from_reader(std::io::stdin())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Any error reading from the stream is returned as [ParserError::Io].
+    pub fn from_reader(mut reader: impl std::io::Read) -> Result<ReportDescriptor> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        ReportDescriptor::try_from(&bytes)
+    }
+}
 
-        let offset = self.bits.start + bits_per_report * idx;
-        let bits = offset..offset + bits_per_report;
-        let v = match bits.len() {
-            1..=8 => extract_i8(bytes, &bits) as i32,
-            9..=16 => extract_i16(bytes, &bits) as i32,
-            17..=32 => extract_i32(bytes, &bits),
-            n => panic!("invalid data length {n}"),
-        };
+impl TryFrom<&[u8]> for ReportDescriptor {
+    type Error = ParserError;
 
-        Ok(v)
+    /// Try to parse the given byte array as a report descriptor.
+    fn try_from(bytes: &[u8]) -> Result<ReportDescriptor> {
+        parse_report_descriptor(bytes)
     }
 }
 
-/// A [ConstantField] is one that represents a [hid::MainItem]
-/// with Constant data, see Section 6.2.2.4.
-///
-/// > Constant indicates the item is a static read-only field in a
-/// > report and cannot be modified (written) by the
-/// > host.
-///
-/// Data in a [ConstantField] should be ignored by the caller, it
-/// is merely used as padding, usually to align the subsequent
-/// value on a byte boundary.
-#[derive(Clone, Debug)]
-pub struct ConstantField {
-    id: FieldId,
-    report_id: Option<ReportId>,
-    pub bits: Range<usize>,
-    usages: Vec<Usage>,
+impl TryFrom<&Vec<u8>> for ReportDescriptor {
+    type Error = ParserError;
+
+    /// Try to parse the given byte array as a report descriptor.
+    fn try_from(bytes: &Vec<u8>) -> Result<ReportDescriptor> {
+        ReportDescriptor::try_from(bytes.as_slice())
+    }
 }
 
-impl ConstantField {
-    pub fn usages(&self) -> &[Usage] {
-        &self.usages
+impl TryFrom<Vec<u8>> for ReportDescriptor {
+    type Error = ParserError;
+
+    /// Try to parse the given byte array as a report descriptor, e.g.
+    /// the bytes as read from a file.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+    /// let rdesc = ReportDescriptor::try_from(std::fs::read(path)?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn try_from(bytes: Vec<u8>) -> Result<ReportDescriptor> {
+        ReportDescriptor::try_from(bytes.as_slice())
     }
 }
 
-/// A unique (within this report descriptor) identifier for a collection.
+/// Prints a listing of all reports and their fields, e.g.
 ///
-/// A device may have multiple collections that are otherwise identical
-/// (in particular logical collections), the collection ID serves
-/// to identify whether two fields are part of the same collection.
-#[derive(Clone, Debug, PartialEq, Hash, PartialOrd)]
-pub struct CollectionId(u32);
-
-/// Collections group [Fields](Field) together into logical or physical
-/// groups.
-///
-/// For example, a set of buttons and x/y axes may be grouped
-/// together to represent a Mouse device.
-/// Each [Field] may belong to a number of collections.
-///
-/// ```
-/// # use hidreport::*;
-/// # fn func(field: &VariableField) {
-/// let collection = field.collections.first().unwrap();
-/// match collection.collection_type() {
-///     CollectionType::Physical => println!("This field is part of a physical collection"),
-///     _ => {},
-/// }
-/// # }
+/// ```text
+/// Input Report (ID 1), 32 bits
+///   bits  8..9    Variable Button/Button 1, logical 0..=1
+///   bits 11..16   Constant
+///   bits 16..24   Variable Generic Desktop/X, logical -127..=127
 /// ```
-///
-#[derive(Clone, Debug)]
-pub struct Collection {
-    id: CollectionId,
-    collection_type: CollectionType,
-    usages: Vec<Usage>,
+impl core::fmt::Display for ReportDescriptor {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let resolver: names::Resolver = match &self.usage_resolver {
+            Some(r) => Some(&*r.0),
+            None => None,
+        };
+        for (name, reports) in [
+            ("Input", &self.input_reports),
+            ("Output", &self.output_reports),
+            ("Feature", &self.feature_reports),
+        ] {
+            for report in reports {
+                match report.id {
+                    Some(id) => writeln!(f, "{name} Report (ID {id}), {} bits", report.size)?,
+                    None => writeln!(f, "{name} Report, {} bits", report.size)?,
+                }
+                for field in report.fields() {
+                    let bits = field.bits();
+                    let bits = format!("{:>2}..{}", bits.start, bits.end);
+                    write!(f, "  bits {bits:<8}")?;
+                    match field {
+                        Field::Variable(v) => {
+                            write!(f, " Variable {}", names::describe_usage(&v.usage, resolver))?;
+                            fmt_values(
+                                f,
+                                v.logical_minimum,
+                                v.logical_maximum,
+                                v.physical_minimum.zip(v.physical_maximum),
+                                v.unit,
+                                v.unit_exponent,
+                            )?;
+                        }
+                        Field::Array(a) => {
+                            let usage_range = names::describe_usage_range(a.usages(), resolver);
+                            write!(f, " Array[{}] {usage_range}", a.report_count)?;
+                            fmt_values(
+                                f,
+                                a.logical_minimum,
+                                a.logical_maximum,
+                                a.physical_minimum.zip(a.physical_maximum),
+                                a.unit,
+                                a.unit_exponent,
+                            )?;
+                        }
+                        Field::Constant(_) => write!(f, " Constant")?,
+                    }
+                    writeln!(f)?;
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
-impl Collection {
-    /// Returns the unique ID for this collection
-    pub fn id(&self) -> &CollectionId {
-        &self.id
+/// Helper for the [ReportDescriptor] Display implementation
+fn fmt_values(
+    f: &mut core::fmt::Formatter<'_>,
+    logical_minimum: LogicalMinimum,
+    logical_maximum: LogicalMaximum,
+    physical: Option<(PhysicalMinimum, PhysicalMaximum)>,
+    unit: Option<Unit>,
+    unit_exponent: Option<UnitExponent>,
+) -> core::fmt::Result {
+    let (min, max) = logical_range(logical_minimum, logical_maximum).into_inner();
+    write!(f, ", logical {min}..={max}")?;
+    if let Some((min, max)) = physical {
+        let (min, max) = physical_range(min, max).into_inner();
+        write!(f, ", physical {min}..={max}")?;
     }
-    /// Return the type of this collection (e.g. physical, logical, ...)
-    pub fn collection_type(&self) -> CollectionType {
-        self.collection_type
+    if let Some(unit) = unit.filter(|u| !format!("{u}").is_empty()) {
+        write!(f, ", unit {unit}")?;
     }
-
-    /// Returns the usages assigned to this collection
-    pub fn usages(&self) -> &[Usage] {
-        &self.usages
+    if let Some(exponent) = unit_exponent.filter(|e| e.exponent() != 0) {
+        write!(f, ", exponent {}", exponent.exponent())?;
     }
+    Ok(())
 }
 
-impl PartialEq for Collection {
-    fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
-    }
+/// The type of device as guessed by [ReportDescriptor::guess_device_type].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DeviceType {
+    Keyboard,
+    Mouse,
+    /// A gamepad or joystick
+    Gamepad,
+    Touchpad,
+    Touchscreen,
+    /// A digitizer other than a touchpad or touchscreen, e.g. a pen tablet
+    Digitizer,
+    /// A device with only a Consumer Control collection, e.g. a remote
+    /// control or the multimedia keys of a keyboard
+    ConsumerControl,
+    /// A device with only vendor-defined collections
+    Vendor,
+    Unknown,
 }
 
-impl Eq for Collection {}
-
-impl Hash for Collection {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.id.hash(state);
-    }
+/// The direction of a report: Input reports are sent by the device,
+/// Output reports are sent to the device and Feature reports
+/// may go either way.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Direction {
+    #[default]
+    Input,
+    Output,
+    Feature,
 }
 
-#[derive(Error, Debug)]
-pub enum ParserError {
-    #[error("Invalid data at offset {offset}: {message}")]
-    InvalidData { offset: usize, message: String },
-    #[error("Parsing would lead to out-of-bounds")]
-    OutOfBounds,
-    #[error("Mismatching Report ID")]
-    MismatchingReportId,
-}
+/// A HID Input, Output or Feature Report.
+///
+/// Where a report contains the [Report::report_id] the first
+/// byte of the report is always that Report ID, followed
+/// by the data in the sequence announced in the HID [ReportDescriptor].
+///
+/// Use [`size_in_bits()`][Report::size_in_bits] or
+/// [`size_in_bytes()`](Report::size_in_bytes) to
+/// get the length of this report.
+///
+/// Note that each of Input, Output and Feature Reports
+/// have their own enumeration of Report IDs, i.e. an Input Report
+/// with a Report ID of e.g. 1 may have a different size and/or [Field]s
+/// to an Output Report with a Report ID of 1.
+///
+/// The Report ID has no meaning other than to distinguish
+/// different reports. See Section 6.2.2.7 for details.
+///
+/// This trait is sealed and cannot be implemented outside this crate.
+pub trait Report: private::Sealed {
+    /// Returns whether this is an Input, Output or Feature report.
+    fn direction(&self) -> Direction;
 
-type Result<T> = std::result::Result<T, ParserError>;
+    /// Returns the HID Report ID for this report, if any.
+    fn report_id(&self) -> &Option<ReportId>;
 
-#[derive(Clone, Copy, Debug, Default)]
-struct Globals {
-    usage_page: Option<UsagePage>,
-    logical_minimum: Option<LogicalMinimum>,
-    logical_maximum: Option<LogicalMaximum>,
-    physical_minimum: Option<PhysicalMinimum>,
-    physical_maximum: Option<PhysicalMaximum>,
-    unit_exponent: Option<UnitExponent>,
-    unit: Option<Unit>,
-    report_size: Option<ReportSize>,
-    report_id: Option<ReportId>,
-    report_count: Option<ReportCount>,
-}
+    /// Returns the parsed HID Fields ID for this report. A caller should
+    /// iterate through these fields to find the ones it is interested
+    /// in and use the [Field::bits] to extract the data from future
+    /// reports.
+    fn fields(&self) -> &[Field];
 
-/// Special struct for the [Locals] because the usage_page
-/// is optional for those, unlike our [Usage] struct which is
-/// the finalized one.
-#[derive(Clone, Copy, Debug)]
-struct LocalUsage {
-    usage_page: Option<UsagePage>,
-    usage_id: UsageId,
-}
+    /// The size in bits for this report.
+    fn size_in_bits(&self) -> usize;
 
-#[derive(Clone, Debug, Default)]
-struct Locals {
-    usage: Vec<LocalUsage>,
-    // FIXME: needs the same LocalUsage treatment
-    usage_minimum: Option<UsageMinimum>,
-    usage_maximum: Option<UsageMaximum>,
-    designator_index: Option<DesignatorIndex>,
-    designator_minimum: Option<DesignatorMinimum>,
-    designator_maximum: Option<DesignatorMaximum>,
-    string_index: Option<StringIndex>,
-    string_minimum: Option<StringMinimum>,
-    string_maximum: Option<StringMaximum>,
-    delimiter: Option<Delimiter>,
-}
+    /// Change the [ReportId] of this report and all its [Field]s. Where the
+    /// report gains or loses its Report ID, the report size and the
+    /// [bits](Field::bits) of all fields change by 8 bits for the Report ID
+    /// byte.
+    ///
+    /// Use [ReportDescriptor::set_report_id] to change the Report ID of a
+    /// report in a [ReportDescriptor].
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// let mut report = ReportBuilder::new(None).add_constant(8).build();
+    /// report.set_id(ReportId::try_from(3).ok());
+    /// assert_eq!(report.size_in_bytes(), 2);
+    /// ```
+    fn set_id(&mut self, id: Option<ReportId>);
 
-#[derive(Debug)]
-struct Stack {
-    globals: Vec<Globals>,
-    locals: Vec<Locals>,
-    pub collections: Vec<Collection>,
-}
+    /// Merge adjacent [ConstantField]s of this report into a single
+    /// [ConstantField], e.g. several 1-bit padding fields into one. This
+    /// does not change the layout of the report, only the number of
+    /// [Field]s.
+    ///
+    /// Use [ReportDescriptor::coalesce_padding] to merge the padding of all
+    /// reports in a [ReportDescriptor].
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// let mut report = ReportBuilder::new(None)
+    ///     .add_constant(1)
+    ///     .add_constant(7)
+    ///     .build();
+    /// report.coalesce_padding();
+    /// assert_eq!(report.fields().len(), 1);
+    /// ```
+    fn coalesce_padding(&mut self);
+
+    /// Returns the [Field]s of this report sorted by the start of their
+    /// [bits](Field::bits). For a report parsed by this crate this is the
+    /// same order as [fields()](Report::fields).
+    fn fields_by_offset(&self) -> Vec<&Field> {
+        let mut fields: Vec<&Field> = self.fields().iter().collect();
+        fields.sort_by_key(|f| f.bits().start);
+        fields
+    }
 
-impl Stack {
-    fn new() -> Self {
-        Stack {
-            globals: vec![Globals::default()],
-            locals: vec![Locals::default()],
-            collections: vec![],
-        }
+    /// Returns the [VariableField]s of this report in the order of
+    /// [fields()](Report::fields), skipping all other fields.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(report: &impl Report) {
+    /// for field in report.variable_fields() {
+    ///     println!("{:?} at bits {:?}", field.usage, field.bits);
+    /// }
+    /// # }
+    /// ```
+    fn variable_fields(&self) -> impl Iterator<Item = &VariableField>
+    where
+        Self: Sized,
+    {
+        self.fields().iter().filter_map(|f| match f {
+            Field::Variable(v) => Some(v),
+            _ => None,
+        })
     }
 
-    fn push(&mut self) {
-        let current = self.globals.last().unwrap();
-        self.globals.push(*current);
+    /// Returns the [ArrayField]s of this report in the order of
+    /// [fields()](Report::fields), skipping all other fields.
+    fn array_fields(&self) -> impl Iterator<Item = &ArrayField>
+    where
+        Self: Sized,
+    {
+        self.fields().iter().filter_map(|f| match f {
+            Field::Array(a) => Some(a),
+            _ => None,
+        })
+    }
 
-        // FIXME: this clones the Usage vector which is likely to mess us up, I don't
-        // think they transfer across push/pop
-        let current = self.locals.last().unwrap().clone();
-        self.locals.push(current);
+    /// The size in bytes for this object.
+    ///
+    /// Where [`size_in_bits()`](Report::size_in_bits) is
+    /// not a multiple of 8, the [`size_in_bytes()`](Report::size_in_bytes) rounds up
+    /// fit all bits.
+    fn size_in_bytes(&self) -> usize {
+        self.size_in_bits().div_ceil(8)
     }
 
-    fn pop(&mut self) -> Result<()> {
-        ensure!(
-            !self.globals.is_empty() && !self.locals.is_empty(),
-            ParserError::InvalidData {
-                offset: 0,
-                message: "Too many Pops".into(),
+    /// Check that the [Field]s of this report are sorted by their bits,
+    /// do not overlap, leave no gaps and cover the report up to
+    /// [`size_in_bits()`](Report::size_in_bits). The first field starts
+    /// at bit 8 if the report has a [ReportId], otherwise at bit 0.
+    ///
+    /// Any report parsed by this crate passes this check, it serves
+    /// as a sanity check for reports from other sources. Checks of the
+    /// field values, e.g. a Logical Maximum that does not fit into the
+    /// Report Size, are not part of this check, see
+    /// [ReportDescriptor::warnings] and [ReportDescriptor::parse_validated].
+    fn validate(&self) -> Result<()> {
+        let start = if self.report_id().is_some() { 8 } else { 0 };
+        let mut expected = Range { start, end: start };
+        let mut prev: Option<&Range<usize>> = None;
+        for field in self.fields() {
+            let bits = field.bits();
+            if bits.start != expected.end {
+                let message = match prev {
+                    None => format!("first field at bits {bits:?} does not start at bit {start}"),
+                    Some(prev) if bits.start < prev.start => {
+                        format!("field at bits {bits:?} is before field at bits {prev:?}")
+                    }
+                    Some(prev) if bits.start < prev.end => {
+                        format!("field at bits {bits:?} overlaps field at bits {prev:?}")
+                    }
+                    Some(prev) => {
+                        format!("gap between field at bits {prev:?} and field at bits {bits:?}")
+                    }
+                };
+                return Err(ParserError::InvalidReport { message });
             }
-        );
-        self.globals.pop();
-        self.locals.pop();
+            expected = bits.clone();
+            prev = Some(bits);
+        }
         ensure!(
-            !self.globals.is_empty() && !self.locals.is_empty(),
-            ParserError::InvalidData {
-                offset: 0,
-                message: "Too many Pops, not enough Pushes".into(),
+            expected.end == self.size_in_bits(),
+            ParserError::InvalidReport {
+                message: format!(
+                    "fields end at bit {} but the report size is {} bits",
+                    expected.end,
+                    self.size_in_bits()
+                )
             }
         );
         Ok(())
     }
 
-    fn reset_locals(&mut self) {
-        self.locals.pop();
-        self.locals.push(Locals::default());
+    /// Returns the bit ranges of this report that are not covered by any
+    /// [Field], i.e. gaps before, between or after the fields. Explicit
+    /// padding is a [ConstantField] and not part of these ranges.
+    ///
+    /// Any report parsed by this crate has no gaps, see
+    /// [validate](Report::validate).
+    fn padding_ranges(&self) -> Vec<RangeInclusive<usize>> {
+        let start = if self.report_id().is_some() { 8 } else { 0 };
+        let mut gaps = vec![];
+        let mut next = start;
+        for field in self.fields() {
+            let bits = field.bits();
+            if bits.start > next {
+                gaps.push(next..=bits.start - 1);
+            }
+            next = next.max(bits.end);
+        }
+        if self.size_in_bits() > next {
+            gaps.push(next..=self.size_in_bits() - 1);
+        }
+        gaps
     }
 
-    fn globals(&mut self) -> &mut Globals {
-        self.globals.last_mut().unwrap()
+    /// Returns a flat description of each [Field] of this report, e.g. to
+    /// generate documentation for a device's reports.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) {
+    /// for report in rdesc.input_reports() {
+    ///     for info in report.to_field_map() {
+    ///         println!("{:?} bit {}: {:?}", info.direction, info.bit_offset, info.usage_name);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    fn to_field_map(&self) -> Vec<FieldInfo> {
+        let logical_range = |min, max| Some(logical_range(min, max));
+        let unit = |unit: Option<Unit>| unit.map(|u| format!("{u}")).filter(|u| !u.is_empty());
+        self.fields()
+            .iter()
+            .map(|field| {
+                let (usage_name, is_signed, logical_range, unit) = match field {
+                    Field::Variable(v) => (
+                        Some(names::describe_usage(&v.usage, None)),
+                        v.is_signed(),
+                        logical_range(v.logical_minimum, v.logical_maximum),
+                        unit(v.unit),
+                    ),
+                    Field::Array(a) => (
+                        Some(names::describe_usage_range(a.usages(), None)),
+                        a.is_signed(),
+                        logical_range(a.logical_minimum, a.logical_maximum),
+                        unit(a.unit),
+                    ),
+                    Field::Constant(_) => (None, false, None, None),
+                };
+                FieldInfo {
+                    direction: self.direction(),
+                    usage_name,
+                    bit_offset: field.bits().start,
+                    bit_width: field.bits().len(),
+                    is_signed,
+                    logical_range,
+                    unit,
+                }
+            })
+            .collect()
     }
 
-    fn locals(&mut self) -> &mut Locals {
-        self.locals.last_mut().unwrap()
+    /// Returns the fields of this report grouped by their collections. The
+    /// returned root node has no collection, its children are the top-level
+    /// (usually Application) collections. [ConstantField]s are not in any
+    /// collection and thus always in the root node.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(report: &impl Report) {
+    /// fn print(node: &CollectionNode, indent: usize) {
+    ///     if let Some(c) = node.collection {
+    ///         println!("{:indent$}{:?}", "", c.collection_type());
+    ///     }
+    ///     for field in &node.fields {
+    ///         println!("{:indent$}  {:?}", "", field.bits());
+    ///     }
+    ///     for child in &node.children {
+    ///         print(child, indent + 2);
+    ///     }
+    /// }
+    /// print(&report.tree(), 0);
+    /// # }
+    /// ```
+    fn tree(&self) -> CollectionNode<'_> {
+        let mut root = CollectionNode::default();
+        for field in self.fields() {
+            let mut node = &mut root;
+            for collection in field.collections() {
+                let idx = match node
+                    .children
+                    .iter()
+                    .position(|c| c.collection.map(|c| c.id()) == Some(collection.id()))
+                {
+                    Some(idx) => idx,
+                    None => {
+                        node.children.push(CollectionNode {
+                            collection: Some(collection),
+                            ..Default::default()
+                        });
+                        node.children.len() - 1
+                    }
+                };
+                node = &mut node.children[idx];
+            }
+            node.fields.push(field);
+        }
+        root
     }
 
-    // Should be globals and globals_mut but i'd have to
-    // update the update_stack macro for that.
-    fn globals_const(&self) -> &Globals {
-        self.globals.last().unwrap()
+    /// Returns one [ByteCell] per byte of this report with the [Field]s
+    /// occupying that byte, e.g. to draw the layout of a report as grid.
+    /// Bits of a byte not occupied by any field are not covered by any
+    /// of its ranges.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(report: &impl Report) {
+    /// for cell in report.byte_map() {
+    ///     let bits: Vec<_> = cell.fields.iter().map(|(_, bits)| bits).collect();
+    ///     println!("byte {}: {bits:?}", cell.index);
+    /// }
+    /// # }
+    /// ```
+    fn byte_map(&self) -> Vec<ByteCell<'_>> {
+        let mut cells: Vec<ByteCell> = (0..self.size_in_bytes())
+            .map(|index| ByteCell {
+                index,
+                is_report_id: index == 0 && self.report_id().is_some(),
+                fields: vec![],
+            })
+            .collect();
+        for field in self.fields() {
+            let bits = field.bits();
+            for index in bits.start / 8..bits.end.div_ceil(8) {
+                let start = bits.start.max(index * 8) - index * 8;
+                let end = bits.end.min(index * 8 + 8) - index * 8;
+                if let Some(cell) = cells.get_mut(index) {
+                    cell.fields.push((field, start..end));
+                }
+            }
+        }
+        cells
     }
 
-    fn locals_const(&self) -> &Locals {
-        self.locals.last().unwrap()
+    /// Decode the given bytes of this report into a list of usages
+    /// and their values. This works for Input, Output and Feature reports.
+    ///
+    /// For a [VariableField] the value is the field's value, for an [ArrayField]
+    /// each element that refers to a usage is returned as that usage with a
+    /// value of 1. Elements outside the logical range or referring to the
+    /// reserved usage ID 0, e.g. Keyboard "No Event" in an unused slot of
+    /// a keyboard's key array, are skipped. [ConstantField]s are skipped.
+    ///
+    /// The bytes must start with the [ReportId] if the report has one
+    /// and may be longer than the report, any extra bytes are ignored.
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<(Usage, i64)>> {
+        check_report_bytes(self, bytes)?;
+
+        let mut values = vec![];
+        for field in self.fields() {
+            match field {
+                Field::Variable(var) => values.push((var.usage, var.extract(bytes)?)),
+                Field::Array(arr) => {
+                    for idx in 0..arr.element_count() {
+                        let value = arr.extract_one(bytes, idx)?;
+                        if let Some(usage) = arr.active_usage(value) {
+                            values.push((usage, 1));
+                        }
+                    }
+                }
+                Field::Constant(_) => {}
+            }
+        }
+
+        Ok(values)
     }
-}
 
-fn compile_usages(globals: &Globals, locals: &Locals) -> Result<Vec<Usage>> {
-    // Prefer UsageMinimum/Maximum over Usage because the latter may be set from an earlier call
-    match locals.usage_minimum {
-        Some(_) => {
-            let Some(min) = locals.usage_minimum else {
-                return Err(ParserError::InvalidData {
-                    offset: 0,
-                    message: "Missing UsageMinimum in locals".into(),
-                });
-            };
-            let Some(max) = locals.usage_maximum else {
-                return Err(ParserError::InvalidData {
-                    offset: 0,
-                    message: "Missing UsageMaximum in locals".into(),
-                });
-            };
-            let Some(usage_page) = globals.usage_page else {
-                return Err(ParserError::InvalidData {
-                    offset: 0,
-                    message: "Missing UsagePage in globals".into(),
-                });
-            };
-            let min: u32 = min.into();
-            let max: u32 = max.into();
+    /// Decode the given bytes of this report into a map of usages to their
+    /// values, see [decode](Report::decode). Where a usage appears more than
+    /// once, e.g. in two [VariableField]s or in multiple elements of an
+    /// [ArrayField], only the first value is kept.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor, bytes: &[u8]) {
+    /// let report = rdesc.find_input_report(bytes).unwrap();
+    /// let values = report.extract_all(bytes).unwrap();
+    /// if let Some(x) = values.get(&pages::usages::X) {
+    ///     println!("X is {x}");
+    /// }
+    /// # }
+    /// ```
+    fn extract_all(&self, bytes: &[u8]) -> Result<BTreeMap<Usage, i64>> {
+        let mut values = BTreeMap::new();
+        for (usage, value) in self.decode(bytes)? {
+            values.entry(usage).or_insert(value);
+        }
+        Ok(values)
+    }
 
-            let usages = (min..=max)
-                .map(|u| Usage {
-                    usage_page: UsagePage(usage_page.into()),
-                    usage_id: UsageId(u as u16),
-                })
-                .collect();
-            Ok(usages)
+    /// Returns a human-readable dump of the given bytes of this report: a
+    /// line with the bytes in hex followed by one line per [Field] with its
+    /// bits and decoded value, e.g. `bits 16-23: Generic Desktop/X = -1`.
+    /// Each element of an [ArrayField] is on a separate line.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor, bytes: &[u8]) {
+    /// let report = rdesc.find_input_report(bytes).unwrap();
+    /// println!("{}", report.explain(bytes).unwrap());
+    /// # }
+    /// ```
+    fn explain(&self, bytes: &[u8]) -> Result<String> {
+        check_report_bytes(self, bytes)?;
+        let hex: Vec<String> = bytes[..self.size_in_bytes()]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        let mut lines = vec![hex.join(" ")];
+        for field in self.fields() {
+            let bits = field.bits();
+            match field {
+                Field::Variable(var) => {
+                    let value = var.extract(bytes)?;
+                    let usage = names::describe_usage(&var.usage, None);
+                    let null = if var.is_null(value) { " (null)" } else { "" };
+                    lines.push(format!(
+                        "bits {}-{}: {usage} = {value}{null}",
+                        bits.start,
+                        bits.end - 1
+                    ));
+                }
+                Field::Array(arr) => {
+                    let count = arr.element_count();
+                    let element_bits = arr.element_bits();
+                    for idx in 0..count {
+                        let start = bits.start + idx * element_bits;
+                        let end = start + element_bits - 1;
+                        let value = arr.extract_one(bytes, idx)?;
+                        let usage = match arr.active_usage(value) {
+                            Some(usage) => format!(" ({})", names::describe_usage(&usage, None)),
+                            None => String::new(),
+                        };
+                        lines.push(format!("bits {start}-{end}: Array[{idx}] = {value}{usage}"));
+                    }
+                }
+                Field::Constant(_) => {
+                    lines.push(format!("bits {}-{}: Constant", bits.start, bits.end - 1));
+                }
+            }
         }
-        None => {
-            let usages = locals
-                .usage
-                .iter()
-                .map(|usage| match usage {
-                    // local item's Usage had a Usage Page included
-                    LocalUsage {
-                        usage_page: Some(up),
-                        usage_id,
-                    } => Usage {
-                        usage_page: *up,
-                        usage_id: *usage_id,
-                    },
-                    // Usage Page comes from the global item
-                    LocalUsage {
-                        usage_page: None,
-                        usage_id,
-                    } => {
-                        let usage_page = globals.usage_page.expect("Missing UsagePage in globals");
-                        Usage {
-                            usage_page,
-                            usage_id: *usage_id,
-                        }
+        Ok(lines.join("\n"))
+    }
+
+    /// Encode the given usages and their values into the bytes of this
+    /// report, the inverse of [decode](Report::decode). The returned bytes
+    /// start with the [ReportId] if the report has one and are
+    /// [size_in_bytes](Report::size_in_bytes) long.
+    ///
+    /// For a [VariableField] the value is [clamped](VariableField::clamp) to
+    /// the logical range. For an [ArrayField] each usage with a nonzero
+    /// value is written into the next free element, usages beyond the
+    /// array's report count are dropped. Usages not present in this report
+    /// are ignored and all other bits are zero.
+    ///
+    /// Returns [ParserError::InvalidReport] if a value is given for a field
+    /// (or array element) of more than 32 bits.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(rdesc: &ReportDescriptor) -> Result<(), ParserError> {
+    /// let report = rdesc.feature_reports().first().unwrap();
+    /// let bytes = report.encode(&report.decode(&vec![0; report.size_in_bytes()])?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn encode(&self, values: &[(Usage, i64)]) -> Result<Vec<u8>> {
+        fn ensure_bits(bits: &Range<usize>) -> Result<()> {
+            ensure!(
+                bits.len() <= 32,
+                ParserError::InvalidReport {
+                    message: format!("Unable to encode a value of {} bits", bits.len()),
+                }
+            );
+            Ok(())
+        }
+
+        let mut bytes = vec![0; self.size_in_bytes()];
+        if let Some(report_id) = self.report_id() {
+            bytes[0] = u8::from(report_id);
+        }
+
+        for field in self.fields() {
+            match field {
+                Field::Variable(var) => {
+                    if let Some((_, value)) = values.iter().find(|(u, _)| *u == var.usage) {
+                        ensure_bits(&var.bits)?;
+                        insert_u32(&mut bytes, &var.bits, var.clamp(*value) as u32);
                     }
-                })
-                .collect();
-            Ok(usages)
+                }
+                Field::Array(arr) => {
+                    let count = arr.element_count();
+                    let bits_per_element = arr.element_bits();
+                    let minimum = *logical_range(arr.logical_minimum, arr.logical_maximum).start();
+                    let indices = values
+                        .iter()
+                        .filter(|(_, value)| *value != 0)
+                        .filter_map(|(u, _)| arr.usages().position(u));
+                    for (idx, index) in indices.take(count).enumerate() {
+                        let offset = arr.bits.start + bits_per_element * idx;
+                        let bits = offset..offset + bits_per_element;
+                        ensure_bits(&bits)?;
+                        insert_u32(&mut bytes, &bits, (minimum + index as i64) as u32);
+                    }
+                }
+                Field::Constant(_) => {}
+            }
         }
+
+        Ok(bytes)
     }
 }
 
-fn handle_main_item(item: &MainItem, stack: &mut Stack, base_id: u32) -> Result<Vec<Field>> {
-    let globals = stack.globals_const();
-    let locals = stack.locals_const();
+/// A node in the tree of collections of a report, see [Report::tree].
+#[derive(Clone, Debug, Default)]
+pub struct CollectionNode<'a> {
+    /// The collection of this node, [None] for the root node
+    pub collection: Option<&'a Collection>,
+    /// The fields directly in this collection, in report order
+    pub fields: Vec<&'a Field>,
+    /// The collections nested in this collection, in the order
+    /// of their first field
+    pub children: Vec<CollectionNode<'a>>,
+}
 
-    let report_id = globals.report_id;
+/// A flat description of a [Field], see [Report::to_field_map].
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldInfo {
+    /// The direction of the report this field is in
+    pub direction: Direction,
+    /// The usage name of a [VariableField] or the usage range of an
+    /// [ArrayField], [None] for a [ConstantField]
+    pub usage_name: Option<String>,
+    /// The first bit of this field in the report, including the
+    /// Report ID byte (if any)
+    pub bit_offset: usize,
+    /// The number of bits of this field, for an [ArrayField] this is
+    /// the size of all elements combined
+    pub bit_width: usize,
+    /// True if the values of this field are signed
+    pub is_signed: bool,
+    /// The logical range, [None] for a [ConstantField]
+    pub logical_range: Option<RangeInclusive<i64>>,
+    /// The [Unit] as string, if any
+    pub unit: Option<String>,
+}
 
-    let (is_constant, is_variable) = match item {
-        MainItem::Input(i) => (i.is_constant(), i.is_variable()),
-        MainItem::Output(i) => (i.is_constant(), i.is_variable()),
-        MainItem::Feature(i) => (i.is_constant(), i.is_variable()),
-        _ => panic!("Invalid item for handle_main_item()"),
-    };
+/// A summary of a top-level Application collection, see
+/// [ReportDescriptor::applications].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApplicationInfo {
+    /// The usage of the Application collection, e.g. Generic Desktop Mouse
+    pub usage: Usage,
+    /// The directions of the reports in this collection, in the order
+    /// Input, Output, Feature
+    pub directions: Vec<Direction>,
+    /// The Report IDs of the reports in this collection, sorted and
+    /// empty if the reports do not have a Report ID
+    pub report_ids: Vec<ReportId>,
+}
 
-    let bit_offset = 0;
-    // We have HID report descriptors in the wild that do not set a report size/count/whatever.
-    // Since the most important implementations so far have been in C-like languages, they
-    // will likely default to zero for unset values (unlike our None).
-    // Let's do this here to be as compatible as possible.
-    let report_size = globals.report_size.unwrap_or(ReportSize(0));
-    let report_count = globals.report_count.unwrap_or(ReportCount(0));
+/// A single byte of a report and the [Field]s that occupy it, see
+/// [Report::byte_map].
+#[derive(Clone, Debug, Default)]
+pub struct ByteCell<'a> {
+    /// The index of this byte in the report, including the Report ID
+    /// byte (if any)
+    pub index: usize,
+    /// True if this byte is the [ReportId]
+    pub is_report_id: bool,
+    /// The fields in this byte with the bits of this byte they occupy,
+    /// e.g. `0..3` for a field in the lowest 3 bits. A field spanning
+    /// multiple bytes is in each of those [ByteCell]s.
+    pub fields: Vec<(&'a Field, Range<usize>)>,
+}
 
-    if report_count == ReportCount(0) || report_size == ReportSize(0) {
-        return Ok(vec![]);
+/// A HID Input, Output or Feature Report.
+///
+/// Where a report contains the [Report::report_id] the first
+/// byte of the report is always that Report ID, followed
+/// by the data in the sequence announced in the HID [ReportDescriptor].
+///
+/// Note that each of Input, Output and Feature Reports
+/// have their own enumeration of Report IDs, i.e. an Input Report
+/// with a Report ID of e.g. 1 may have a different size and/or [Field]s
+/// to an Output Report with a Report ID of 1.
+///
+/// The Report ID has no meaning other than to distinguish
+/// different reports. See Section 6.2.2.7 for details.
+#[derive(Clone, Debug, Default)]
+struct RDescReport {
+    /// Whether this is an Input, Output or Feature report
+    direction: Direction,
+    /// The report ID, if any
+    id: Option<ReportId>,
+    /// The size of this report in bits
+    size: usize,
+    /// The fields present in this report
+    fields: Vec<Field>,
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+impl private::Sealed for RDescReport {}
+
+impl Report for RDescReport {
+    fn direction(&self) -> Direction {
+        self.direction
     }
 
-    if is_constant {
-        let nbits = usize::from(report_size) * usize::from(report_count);
-        let bits = bit_offset..(bit_offset + nbits);
+    fn report_id(&self) -> &Option<ReportId> {
+        &self.id
+    }
 
-        let field = ConstantField {
-            id: FieldId(base_id + bit_offset as u32),
-            bits,
-            report_id,
-            usages: vec![],
-        };
-        return Ok(vec![Field::Constant(field)]);
+    fn fields(&self) -> &[Field] {
+        &self.fields
     }
 
-    let logical_minimum = globals.logical_minimum.unwrap_or(LogicalMinimum(0));
-    let logical_maximum = globals.logical_maximum.unwrap_or(LogicalMaximum(0));
+    /// The size of this HID report on the wire, in bits
+    fn size_in_bits(&self) -> usize {
+        self.size
+    }
 
-    // Some report descriptors are missing either phys min or max, assume zero
-    // where one of them is not None
-    let physical_maximum: Option<PhysicalMaximum>;
-    let physical_minimum: Option<PhysicalMinimum>;
-    if globals.physical_minimum.is_some() != globals.physical_maximum.is_some() {
-        physical_maximum = globals.physical_maximum.or(Some(PhysicalMaximum(0)));
-        physical_minimum = globals.physical_minimum.or(Some(PhysicalMinimum(0)));
-    } else {
-        physical_maximum = globals.physical_maximum;
-        physical_minimum = globals.physical_minimum;
+    fn set_id(&mut self, id: Option<ReportId>) {
+        let gained = self.id.is_none() && id.is_some();
+        let lost = self.id.is_some() && id.is_none();
+        if gained {
+            self.size += 8;
+        } else if lost {
+            self.size -= 8;
+        }
+        for field in self.fields.iter_mut() {
+            field.set_report_id(id);
+            let bits = field.bits().clone();
+            if gained {
+                field.set_bits(bits.start + 8..bits.end + 8);
+            } else if lost {
+                field.set_bits(bits.start - 8..bits.end - 8);
+            }
+        }
+        self.id = id;
     }
 
-    let unit = globals.unit;
-    let unit_exponent = globals.unit_exponent;
+    fn coalesce_padding(&mut self) {
+        let mut fields: Vec<Field> = Vec::with_capacity(self.fields.len());
+        for field in self.fields.drain(..) {
+            if let (Some(Field::Constant(prev)), Field::Constant(next)) =
+                (fields.last_mut(), &field)
+            {
+                if prev.report_id == next.report_id && prev.bits.end == next.bits.start {
+                    prev.bits.end = next.bits.end;
+                    prev.usages.extend_from_slice(&next.usages);
+                    continue;
+                }
+            }
+            fields.push(field);
+        }
+        self.fields = fields;
+    }
+}
 
-    let usages = compile_usages(globals, locals)?;
-    ensure!(!usages.is_empty(), "Missing Usages for main item");
+/// Check that the bytes start with the report's [ReportId] (if any)
+/// and are long enough for the report.
+fn check_report_bytes<R: Report + ?Sized>(report: &R, bytes: &[u8]) -> Result<()> {
+    if let Some(report_id) = report.report_id() {
+        ensure!(!bytes.is_empty(), ParserError::OutOfBounds);
+        ensure!(
+            ReportId(bytes[0]) == *report_id,
+            ParserError::MismatchingReportId
+        );
+    }
+    ensure!(
+        bytes.len() >= report.size_in_bytes(),
+        ParserError::OutOfBounds
+    );
+    Ok(())
+}
 
-    // This may be an empty vec
-    let collections = stack.collections.clone();
-    let fields: Vec<Field> = if is_variable {
-        let mut bit_offset = 0;
-        Range {
-            start: 0,
-            end: usize::from(report_count),
+/// A builder to synthesize a [Report], e.g. for testing.
+///
+/// Fields are appended in the order they are added, the
+/// bit offsets are calculated by the builder.
+///
+/// ```
+/// # use hidreport::*;
+/// let x = Usage::from_page_and_id(UsagePage::from(0x01), UsageId::from(0x30));
+/// let report = ReportBuilder::new(ReportId::try_from(1).ok())
+///     .add_variable(x, 16, -32767..=32767)
+///     .add_constant(8)
+///     .build();
+/// assert_eq!(report.size_in_bytes(), 4);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ReportBuilder {
+    report: RDescReport,
+}
+
+impl ReportBuilder {
+    /// Create a new builder for a report with the given Report ID.
+    pub fn new(report_id: Option<ReportId>) -> Self {
+        ReportBuilder {
+            report: RDescReport {
+                direction: Direction::Input,
+                id: report_id,
+                size: if report_id.is_some() { 8 } else { 0 },
+                fields: vec![],
+            },
         }
-        .map(|c| {
-            let nbits = usize::from(report_size);
-            let bits = bit_offset..(bit_offset + nbits);
-            bit_offset += nbits;
+    }
 
-            let usage = usages.get(c).or_else(|| usages.last()).unwrap();
-            let field = VariableField {
-                id: FieldId(base_id + bit_offset as u32),
-                usage: *usage,
-                bits,
-                logical_minimum,
-                logical_maximum,
-                physical_minimum,
-                physical_maximum,
-                unit,
-                unit_exponent,
-                collections: collections.clone(),
-                report_id,
-            };
-            Field::Variable(field)
-        })
-        .collect()
-    } else {
-        let bit_offset = 0;
-        let nbits = usize::from(report_size) * usize::from(report_count);
-        let bits = bit_offset..(bit_offset + nbits);
+    /// Set the [Direction] of the report, the default is [Direction::Input].
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.report.direction = direction;
+        self
+    }
 
-        let field = ArrayField {
-            id: FieldId(base_id + bit_offset as u32),
-            usages,
-            bits,
-            logical_minimum,
-            logical_maximum,
-            physical_minimum,
-            physical_maximum,
-            unit,
-            unit_exponent,
-            collections,
-            report_id,
-            report_count,
-        };
+    /// Append a [VariableField] with the given usage and size in bits.
+    pub fn add_variable(
+        self,
+        usage: Usage,
+        bits: usize,
+        logical_range: RangeInclusive<i32>,
+    ) -> Self {
+        let mut field = VariableField::new(
+            usage,
+            self.next_bits(bits),
+            LogicalMinimum(*logical_range.start()),
+            LogicalMaximum(*logical_range.end()),
+        );
+        field.id = self.next_id();
+        field.report_id = self.report.id;
+        self.push(Field::Variable(field))
+    }
 
-        vec![Field::Array(field)]
-    };
+    /// Append a [ConstantField] (padding) of the given size in bits.
+    pub fn add_constant(self, bits: usize) -> Self {
+        let mut field = ConstantField::new(self.next_bits(bits));
+        field.id = self.next_id();
+        field.report_id = self.report.id;
+        self.push(Field::Constant(field))
+    }
 
-    Ok(fields)
+    /// Build the [Report].
+    pub fn build(self) -> impl Report {
+        self.report
+    }
+
+    fn next_bits(&self, bits: usize) -> Range<usize> {
+        self.report.size..self.report.size + bits
+    }
+
+    fn next_id(&self) -> FieldId {
+        FieldId(self.report.fields.len() as u32)
+    }
+
+    fn push(mut self, field: Field) -> Self {
+        self.report.size = field.bits().end;
+        self.report.fields.push(field);
+        self
+    }
 }
 
-macro_rules! update_stack {
-    ($stack:ident, $class:ident, $which:ident, $from:ident) => {
-        //println!("Updating {} with value {:?}", stringify!($which), &$from);
-        let state = $stack.$class();
-        state.$which = Some($from);
-    };
+/// The usage of a [Field] defines the interpretation of a
+/// data value. See the `hut` crate for a list of known Usages.
+///
+/// A Usage comprises of a 16 bit [UsagePage] and a 16 bit [UsageId].
+///
+/// ```
+/// # use hidreport::*;
+/// let up = UsagePage::from(0x01); // Generic Desktop
+/// let uid = UsageId::from(0x02); // Mouse
+/// let usage = Usage::from_page_and_id(up, uid);
+/// ```
+/// For known named usages see the `hut` crate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Usage {
+    pub usage_page: UsagePage,
+    pub usage_id: UsageId,
 }
 
-fn parse_report_descriptor(bytes: &[u8]) -> Result<ReportDescriptor> {
-    ensure!(!bytes.is_empty(), "Empty report descriptor");
-    let items = hid::ReportDescriptorItems::try_from(bytes)?;
+impl Usage {
+    /// Create a [Usage] from a [UsagePage] and a [UsageId].
+    pub fn from_page_and_id(usage_page: UsagePage, usage_id: UsageId) -> Usage {
+        Usage {
+            usage_page,
+            usage_id,
+        }
+    }
 
-    let mut stack = Stack::new();
-    let mut rdesc = ReportDescriptor::default();
+    /// Returns the button number if this usage is on the Button
+    /// usage page (0x09). Buttons are numbered starting at 1, a
+    /// button number of 0 means "no button pressed".
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// let usage = Usage::from_page_and_id(UsagePage::from(0x09), UsageId::from(0x02));
+    /// assert_eq!(usage.as_button(), Some(2));
+    /// ```
+    pub fn as_button(&self) -> Option<u16> {
+        match u16::from(self.usage_page) {
+            0x09 => Some(u16::from(self.usage_id)),
+            _ => None,
+        }
+    }
+}
 
-    for rdesc_item in items.iter() {
-        //println!("Handling offset {}", rdesc_item.offset());
-        let item = rdesc_item.item();
-        match item.item_type() {
-            ItemType::Main(MainItem::Collection(i)) => {
-                let globals = stack.globals_const();
+impl From<u32> for Usage {
+    fn from(u: u32) -> Usage {
+        Usage {
+            usage_page: UsagePage::from((u >> 16) as u16),
+            usage_id: UsageId::from((u & 0xffff) as u16),
+        }
+    }
+}
+
+/// Create a [Usage] from a `(usage page, usage id)` tuple.
+/// ```
+/// # use hidreport::*;
+/// let x = Usage::from((0x01, 0x30));
+/// assert_eq!(x, (0x01, 0x30));
+/// ```
+impl From<(u16, u16)> for Usage {
+    fn from((usage_page, usage_id): (u16, u16)) -> Usage {
+        Usage {
+            usage_page: UsagePage(usage_page),
+            usage_id: UsageId(usage_id),
+        }
+    }
+}
+
+/// Compare a [Usage] against a `(usage page, usage id)` tuple.
+impl PartialEq<(u16, u16)> for Usage {
+    fn eq(&self, other: &(u16, u16)) -> bool {
+        (self.usage_page.0, self.usage_id.0) == *other
+    }
+}
+
+impl From<&Usage> for u32 {
+    fn from(u: &Usage) -> u32 {
+        (u16::from(u.usage_page) as u32) << 16 | u16::from(u.usage_id) as u32
+    }
+}
+
+impl_from_without_ref!(Usage, u32, u32);
+
+impl From<&Usage> for UsageMinimum {
+    fn from(u: &Usage) -> UsageMinimum {
+        UsageMinimum(u32::from(u))
+    }
+}
+
+impl_from_without_ref!(Usage, UsageMinimum, UsageMinimum);
+
+impl From<&Usage> for UsageMaximum {
+    fn from(u: &Usage) -> UsageMaximum {
+        UsageMaximum(u32::from(u))
+    }
+}
+
+impl_from_without_ref!(Usage, UsageMaximum, UsageMaximum);
+
+/// A unique (within this report descriptor) identifier for a [Field].
+#[derive(Clone, Copy, Debug, PartialEq, Hash, PartialOrd)]
+pub struct FieldId(u32);
+
+impl From<&FieldId> for u32 {
+    fn from(f: &FieldId) -> u32 {
+        f.0
+    }
+}
+
+impl_from_without_ref!(FieldId, u32, u32);
+
+/// A single field inside a [Report].
+///
+/// Fields may be [Field::Variable] and represent a
+/// single element of data or they may be
+/// a [Field::Array] that represent
+/// multiple elements.
+///
+/// Fields of type [Field::Constant] should be ignored by
+/// the caller.
+#[derive(Clone, Debug)]
+pub enum Field {
+    Variable(VariableField),
+    Array(ArrayField),
+    Constant(ConstantField),
+}
+
+impl Field {
+    pub fn id(&self) -> FieldId {
+        match self {
+            Field::Variable(f) => f.id,
+            Field::Array(f) => f.id,
+            Field::Constant(f) => f.id,
+        }
+    }
+    /// Returns the bit range that make up this field.
+    pub fn bits(&self) -> &Range<usize> {
+        match self {
+            Field::Variable(f) => &f.bits,
+            Field::Array(f) => &f.bits,
+            Field::Constant(f) => &f.bits,
+        }
+    }
+
+    /// Returns the Report ID this field belongs to, if any.
+    fn report_id(&self) -> &Option<ReportId> {
+        match self {
+            Field::Variable(f) => &f.report_id,
+            Field::Array(f) => &f.report_id,
+            Field::Constant(f) => &f.report_id,
+        }
+    }
+
+    fn update_bit_offset(&mut self, offset: usize) {
+        let r = self.bits();
+        let r = (offset + r.start)..(offset + r.end);
+        self.set_bits(r);
+    }
+
+    fn set_bits(&mut self, bits: Range<usize>) {
+        match self {
+            Field::Variable(f) => f.bits = bits,
+            Field::Array(f) => f.bits = bits,
+            Field::Constant(f) => f.bits = bits,
+        };
+    }
+
+    fn set_report_id(&mut self, report_id: Option<ReportId>) {
+        match self {
+            Field::Variable(f) => f.report_id = report_id,
+            Field::Array(f) => f.report_id = report_id,
+            Field::Constant(f) => f.report_id = report_id,
+        };
+    }
+
+    /// Returns true if this is a [Field::Constant]
+    fn is_constant(&self) -> bool {
+        matches!(self, Field::Constant(_))
+    }
+
+    /// The logical range of a [Field::Variable] or [Field::Array],
+    /// a [Field::Constant] has a range of 0..=0.
+    fn logical_range(&self) -> RangeInclusive<i64> {
+        match self {
+            Field::Variable(f) => logical_range(f.logical_minimum, f.logical_maximum),
+            Field::Array(f) => logical_range(f.logical_minimum, f.logical_maximum),
+            Field::Constant(_) => 0..=0,
+        }
+    }
+
+    /// The physical range of a [Field::Variable] or [Field::Array], if any
+    fn physical_range(&self) -> Option<RangeInclusive<i64>> {
+        let (min, max) = match self {
+            Field::Variable(f) => f.physical_minimum.zip(f.physical_maximum)?,
+            Field::Array(f) => f.physical_minimum.zip(f.physical_maximum)?,
+            Field::Constant(_) => return None,
+        };
+        Some(physical_range(min, max))
+    }
+
+    /// Returns the usages of this field, for a [Field::Variable]
+    /// this is exactly one usage.
+    fn usages(&self) -> Usages<'_> {
+        match self {
+            Field::Variable(f) => Usages(UsagesInner::List(core::slice::from_ref(&f.usage))),
+            Field::Array(f) => f.usages(),
+            Field::Constant(f) => Usages(UsagesInner::List(&f.usages)),
+        }
+    }
+
+    /// The length of the field in bits
+    fn len(&self) -> usize {
+        self.bits().len()
+    }
+
+    pub fn collections(&self) -> &[Collection] {
+        match self {
+            Field::Variable(f) => &f.collections,
+            Field::Array(f) => &f.collections,
+            Field::Constant(..) => &[],
+        }
+    }
+}
+
+/// The flags of the Input, Output or Feature main item that created
+/// a field, see Section 6.2.2.5. The Constant and Variable flags are
+/// represented by the type of [Field] instead.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MainItemFlags {
+    /// The data is relative to the previous report (otherwise absolute)
+    pub is_relative: bool,
+    /// The data wraps around at the logical minimum/maximum
+    pub wraps: bool,
+    /// The data was pre-processed on the device and is not linear
+    pub is_nonlinear: bool,
+    /// The control does not return to a preferred state
+    pub has_no_preferred_state: bool,
+    /// The control has a null state where it does not send meaningful data
+    pub has_null_state: bool,
+    /// The control value may be changed by the device without host
+    /// interaction. Always false for Input items.
+    pub is_volatile: bool,
+    /// The field is a fixed size stream of bytes (otherwise a bit field)
+    pub is_buffered_bytes: bool,
+}
+
+impl MainItemFlags {
+    fn from_item(item: &impl MainDataItem, is_volatile: bool) -> Self {
+        MainItemFlags {
+            is_relative: item.is_relative(),
+            wraps: item.wraps(),
+            is_nonlinear: item.is_nonlinear(),
+            has_no_preferred_state: item.has_no_preferred_state(),
+            has_null_state: item.has_null_state(),
+            is_volatile,
+            is_buffered_bytes: item.is_buffered_bytes(),
+        }
+    }
+
+    /// The flags as bits of the main item's data
+    fn bits(&self) -> u32 {
+        [
+            (self.is_relative, 1 << 2),
+            (self.wraps, 1 << 3),
+            (self.is_nonlinear, 1 << 4),
+            (self.has_no_preferred_state, 1 << 5),
+            (self.has_null_state, 1 << 6),
+            (self.is_volatile, 1 << 7),
+            (self.is_buffered_bytes, 1 << 8),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .fold(0, |acc, (_, bit)| acc | bit)
+    }
+}
+
+/// A [VariableField] represents a single physical control.
+#[derive(Clone, Debug)]
+pub struct VariableField {
+    id: FieldId,
+    report_id: Option<ReportId>,
+    flags: MainItemFlags,
+    /// The bits of this field within the report. If the report has a
+    /// [ReportId], the first byte is the Report ID and these bits start
+    /// at bit 8, i.e. they index into the report bytes as sent by the device.
+    pub bits: Range<usize>,
+    pub usage: Usage,
+    pub logical_minimum: LogicalMinimum,
+    pub logical_maximum: LogicalMaximum,
+    pub physical_minimum: Option<PhysicalMinimum>,
+    pub physical_maximum: Option<PhysicalMaximum>,
+    pub unit: Option<Unit>,
+    pub unit_exponent: Option<UnitExponent>,
+    string_index: Option<StringIndex>,
+    designator_index: Option<DesignatorIndex>,
+    pub collections: Vec<Collection>,
+}
+
+impl VariableField {
+    /// Returns the flags of the main item that created this field.
+    pub fn flags(&self) -> MainItemFlags {
+        self.flags
+    }
+
+    /// Returns the String Index of this field, if any. This is an index
+    /// into the device's string descriptors (Section 6.2.2.8).
+    pub fn string_index(&self) -> Option<StringIndex> {
+        self.string_index
+    }
+
+    /// Returns the index into the device's Physical Descriptor sets that
+    /// describes which body part is used to control this field's usage, if
+    /// any (Section 6.2.2.8).
+    ///
+    /// Where the main item has a Designator Minimum and Designator Maximum
+    /// instead of a Designator Index, the designators are assigned to the
+    /// fields in order like the usages.
+    pub fn designator(&self) -> Option<DesignatorIndex> {
+        self.designator_index
+    }
+
+    /// Returns true if this field's values are relative to the previous
+    /// value (e.g. mouse movement), false if they are absolute (e.g. a
+    /// touch coordinate).
+    pub fn is_relative(&self) -> bool {
+        self.flags.is_relative
+    }
+
+    /// True if this field has a null state, i.e. the device may send a
+    /// value outside the logical range to indicate that there is no
+    /// meaningful data, e.g. a hat switch that is centered.
+    pub fn has_null_state(&self) -> bool {
+        self.flags.has_null_state
+    }
+
+    /// Create a new [VariableField] for the given usage and bits with
+    /// all other properties unset.
+    ///
+    /// The bits must include the Report ID byte (if any), see
+    /// [VariableField::bits]. Use a [ReportBuilder] to calculate
+    /// the bits automatically.
+    pub fn new(
+        usage: Usage,
+        bits: Range<usize>,
+        logical_minimum: LogicalMinimum,
+        logical_maximum: LogicalMaximum,
+    ) -> Self {
+        VariableField {
+            id: FieldId(bits.start as u32),
+            report_id: None,
+            bits,
+            usage,
+            logical_minimum,
+            logical_maximum,
+            physical_minimum: None,
+            physical_maximum: None,
+            unit: None,
+            unit_exponent: None,
+            string_index: None,
+            designator_index: None,
+            flags: MainItemFlags::default(),
+            collections: vec![],
+        }
+    }
+
+    /// Returns the collections this field is in, starting with the
+    /// outermost (usually Application) collection down to the
+    /// collection that immediately contains this field.
+    pub fn collection_path(&self) -> &[Collection] {
+        &self.collections
+    }
+
+    /// Returns the number of collections this field is nested in, i.e.
+    /// the length of the [collection path](VariableField::collection_path).
+    pub fn depth(&self) -> usize {
+        self.collections.len()
+    }
+
+    /// Returns the inclusive range of bits of this field in the report
+    /// bytes, including the Report ID byte (if any). This is the same
+    /// range as [VariableField::bits], see its documentation.
+    ///
+    /// Returns [None] if this field has no bits, i.e. a Report Size of zero.
+    pub fn absolute_bits(&self) -> Option<RangeInclusive<usize>> {
+        if self.bits.is_empty() {
+            None
+        } else {
+            Some(self.bits.start..=self.bits.end - 1)
+        }
+    }
+
+    /// Returns true if this field contains signed values,
+    /// i.e. the LogicalMinimum is less than zero.
+    pub fn is_signed(&self) -> bool {
+        self.logical_minimum < LogicalMinimum(0)
+    }
+
+    /// Returns true if this field is an on/off control, i.e. it is a
+    /// single bit or its logical range is 0..=1. For example a button
+    /// or a keyboard modifier key.
+    pub fn is_boolean(&self) -> bool {
+        self.bits.len() == 1 || self.logical_range() == (0..=1)
+    }
+
+    /// Returns the difference between the [LogicalMaximum] and the
+    /// [LogicalMinimum], e.g. 255 for a logical range of 0..=255.
+    pub fn logical_span(&self) -> i64 {
+        let (min, max) = self.logical_range().into_inner();
+        max - min
+    }
+
+    /// The logical range of this field as [i64]
+    fn logical_range(&self) -> RangeInclusive<i64> {
+        logical_range(self.logical_minimum, self.logical_maximum)
+    }
+
+    /// Returns true if the value is within the inclusive
+    /// [LogicalMinimum]..=[LogicalMaximum] range of this field.
+    pub fn is_in_range(&self, value: i64) -> bool {
+        self.logical_range().contains(&value)
+    }
+
+    /// Returns true if the value is this field's null state, i.e. the
+    /// field [has a null state](VariableField::has_null_state) and the
+    /// value is outside the logical range.
+    pub fn is_null(&self, value: i64) -> bool {
+        self.flags.has_null_state && !self.is_in_range(value)
+    }
+
+    /// Clamp the value to the inclusive [LogicalMinimum]..=[LogicalMaximum]
+    /// range of this field, e.g. before sending a user-supplied value to the
+    /// device.
+    ///
+    /// Unlike [i64::clamp] this does not panic where a (buggy) report
+    /// descriptor has a minimum larger than the maximum, the maximum
+    /// takes precedence.
+    pub fn clamp(&self, value: i64) -> i64 {
+        let (min, max) = self.logical_range().into_inner();
+        value.max(min).min(max)
+    }
+
+    /// Returns the resolution of this field in logical units (counts) per
+    /// physical [Unit], taking the [UnitExponent] into account. For example
+    /// a touchscreen axis with a [Unit] of centimeters returns counts per cm.
+    ///
+    /// Returns [None] if the field does not have a physical range and unit
+    /// or if the physical range is zero.
+    pub fn resolution(&self) -> Option<f64> {
+        let (pmin, pmax) = self.physical_minimum.zip(self.physical_maximum)?;
+        let unit = self.unit?;
+        if matches!(unit.system(), UnitSystem::None) {
+            return None;
+        }
+        let (lmin, lmax) = logical_range(self.logical_minimum, self.logical_maximum).into_inner();
+        let logical_span = lmax - lmin;
+        let (pmin, pmax) = physical_range(pmin, pmax).into_inner();
+        let physical_span = pmax - pmin;
+        if physical_span == 0 {
+            return None;
+        }
+
+        let physical_span = physical_span as f64 * self.exponent_scale();
+
+        Some(logical_span as f64 / physical_span)
+    }
+
+    /// Convert the logical value to its physical value by mapping the
+    /// logical range onto the (signed) physical range and applying the
+    /// [UnitExponent]. For example a joystick axis with a logical range
+    /// of 0..=254 and a physical range of -90..=90 degrees maps 127 to 0.
+    ///
+    /// A field without a physical range but with a [UnitExponent] uses
+    /// the exponent as plain scale for the logical value, e.g. a
+    /// dimensionless percentage with an exponent of -2 maps 50 to 0.5.
+    ///
+    /// Returns [None] if the field has neither a physical range nor a
+    /// [UnitExponent] or if the logical range is zero.
+    ///
+    /// The calculation is done in [i128] and [f64] so it does not overflow
+    /// for full 32-bit logical and physical ranges.
+    pub fn physical_value(&self, value: i64) -> Option<f64> {
+        let Some((pmin, pmax)) = self.physical_minimum.zip(self.physical_maximum) else {
+            return self
+                .unit_exponent
+                .map(|_| value as f64 * self.exponent_scale());
+        };
+        let (lmin, lmax) = logical_range(self.logical_minimum, self.logical_maximum).into_inner();
+        let logical_span = i128::from(lmax) - i128::from(lmin);
+        if logical_span == 0 {
+            return None;
+        }
+        let (pmin, pmax) = physical_range(pmin, pmax).into_inner();
+        let physical_span = i128::from(pmax) - i128::from(pmin);
+        let offset = i128::from(value) - i128::from(lmin);
+        let physical = pmin as f64 + offset as f64 * physical_span as f64 / logical_span as f64;
+
+        Some(physical * self.exponent_scale())
+    }
+
+    /// Convert the physical value to the nearest logical value, the inverse
+    /// of [VariableField::physical_value]. The result is clamped to the
+    /// logical range, see [VariableField::clamp].
+    ///
+    /// If the field does not have a physical range or the physical range is
+    /// zero, the physical range is the logical range (Section 6.2.2.7).
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(brightness: &VariableField) {
+    /// let half = brightness.logical_from_physical(50.0);
+    /// # }
+    /// ```
+    pub fn logical_from_physical(&self, physical: f64) -> i64 {
+        let (lmin, lmax) = logical_range(self.logical_minimum, self.logical_maximum).into_inner();
+        let physical = physical / self.exponent_scale();
+        let logical = match self.physical_minimum.zip(self.physical_maximum) {
+            Some((pmin, pmax)) if pmin != PhysicalMinimum(0) || pmax != PhysicalMaximum(0) => {
+                let (pmin, pmax) = physical_range(pmin, pmax).into_inner();
+                let physical_span = (i128::from(pmax) - i128::from(pmin)) as f64;
+                let logical_span = (i128::from(lmax) - i128::from(lmin)) as f64;
+                if physical_span == 0.0 {
+                    physical
+                } else {
+                    lmin as f64 + (physical - pmin as f64) * logical_span / physical_span
+                }
+            }
+            _ => physical,
+        };
+        // f64::round() is not available in core, the cast saturates
+        let logical = if logical < 0.0 {
+            (logical - 0.5) as i64
+        } else {
+            (logical + 0.5) as i64
+        };
+        self.clamp(logical)
+    }
+
+    /// Returns the given physical value with this field's [Unit], e.g.
+    /// `"12.30 cm"` for a physical value of 12.3, a unit of centimeters and
+    /// a unit exponent of -2. The value is expected to have the [UnitExponent]
+    /// applied already as done by [VariableField::physical_value], negative
+    /// exponents only determine the number of decimal places.
+    ///
+    /// A field without a [Unit] but with a [UnitExponent] returns just the
+    /// value, e.g. `"0.50"`. Returns [None] if the field has neither.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(field: &VariableField, bytes: &[u8]) {
+    /// let value = field.extract(bytes).unwrap();
+    /// if let Some(s) = field.physical_value(value).and_then(|p| field.unit_display(p)) {
+    ///     println!("{s}");
+    /// }
+    /// # }
+    /// ```
+    pub fn unit_display(&self, physical: f64) -> Option<String> {
+        let unit = self.unit.map(|u| format!("{u}")).unwrap_or_default();
+        if unit.is_empty() && self.unit_exponent.is_none() {
+            return None;
+        }
+        let exponent = self.unit_exponent.map(|e| e.exponent()).unwrap_or(0);
+        let precision = if exponent < 0 { -exponent as usize } else { 0 };
+        if unit.is_empty() {
+            Some(format!("{physical:.precision$}"))
+        } else {
+            Some(format!("{physical:.precision$} {unit}"))
+        }
+    }
+
+    /// Returns 10 to the power of the [UnitExponent], or 1 if there is none.
+    fn exponent_scale(&self) -> f64 {
+        // f64::powi() is not available in core
+        let exponent = self.unit_exponent.map(|e| e.exponent()).unwrap_or(0);
+        let scale = (0..exponent.unsigned_abs()).fold(1.0, |acc, _| acc * 10.0);
+        if exponent < 0 {
+            1.0 / scale
+        } else {
+            scale
+        }
+    }
+
+    /// Extract this field's value as [u32] from a report's bytes.
+    /// The value is extracted as its correct bit size but upcasted
+    /// if need be into a [u32]. IOW it is safe to call this function
+    /// on e.g. an 8 bit unsigned field in the report.
+    ///
+    /// Check [VariableField::is_signed] first to see if you should be
+    /// using [VariableField::extract_i32] instead.
+    pub fn extract_u32(&self, bytes: &[u8]) -> Result<u32> {
+        ensure!(bytes.len() * 8 >= self.bits.end, ParserError::OutOfBounds);
+        if let Some(report_id) = self.report_id {
+            if ReportId(bytes[0]) != report_id {
+                return Err(ParserError::MismatchingReportId);
+            }
+        }
+
+        extract_bits(bytes, &self.bits, false).map(|v| v as u32)
+    }
+
+    /// Extract this field's value as [i32] from a report's bytes.
+    /// The value is extracted as its correct bit size but upcasted
+    /// if need be into a [i32]. IOW it is safe to call this function
+    /// on e.g. an 8 bit signed field in the report.
+    ///
+    /// Check [VariableField::is_signed] first to see if you should be
+    /// using [VariableField::extract_u32] instead.
+    pub fn extract_i32(&self, bytes: &[u8]) -> Result<i32> {
+        ensure!(bytes.len() * 8 >= self.bits.end, ParserError::OutOfBounds);
+        if let Some(report_id) = self.report_id {
+            if ReportId(bytes[0]) != report_id {
+                return Err(ParserError::MismatchingReportId);
+            }
+        }
+
+        extract_bits(bytes, &self.bits, true).map(|v| v as i32)
+    }
+
+    /// Extract this field's value as [i64] from a report's bytes.
+    /// The value is extracted as signed or unsigned value depending on
+    /// [VariableField::is_signed], the [i64] can represent both.
+    ///
+    /// The bytes are little-endian as sent by the device, see
+    /// [VariableField::extract_with_order] otherwise.
+    pub fn extract(&self, bytes: &[u8]) -> Result<i64> {
+        if self.is_signed() {
+            self.extract_i32(bytes).map(i64::from)
+        } else {
+            self.extract_u32(bytes).map(i64::from)
+        }
+    }
+
+    /// Extract this field's value as [i64] like [VariableField::extract]
+    /// but with the given [ByteOrder] of the value, e.g. for data captured
+    /// by tools that store values big-endian.
+    ///
+    /// For [ByteOrder::BigEndian] the field's size must be a multiple of
+    /// 8 bits, otherwise [ParserError::InvalidReport] is returned.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(field: &VariableField, bytes: &[u8]) {
+    /// let value = field.extract_with_order(bytes, ByteOrder::BigEndian).unwrap();
+    /// # }
+    /// ```
+    pub fn extract_with_order(&self, bytes: &[u8], order: ByteOrder) -> Result<i64> {
+        if order == ByteOrder::LittleEndian {
+            return self.extract(bytes);
+        }
+        let nbits = self.bits.len();
+        ensure!(
+            nbits.is_multiple_of(8),
+            ParserError::InvalidReport {
+                message: format!(
+                    "Big-endian extraction requires a multiple of 8 bits, field has {nbits} bits"
+                ),
+            }
+        );
+        let value = self.extract_u32(bytes)?.swap_bytes() >> (32 - nbits);
+        let value = if self.is_signed() {
+            i64::from(value.twos_comp(nbits))
+        } else {
+            i64::from(value)
+        };
+        Ok(value)
+    }
+}
+
+/// Wrapper around the commonly used [UsageMinimum] and [UsageMaximum].
+#[derive(Clone, Debug)]
+pub struct UsageRange {
+    usage_page: UsagePage,
+    minimum: UsageMinimum,
+    maximum: UsageMaximum,
+}
+
+impl UsageRange {
+    /// The [UsageMinimum]. Note that in reports and report descriptors
+    /// the Usage Minimum may or may not include the Usage Page. The
+    /// minimum returned here always includes the [UsagePage].
+    pub fn minimum(&self) -> UsageMinimum {
+        self.minimum
+    }
+
+    /// The [UsageMaximum]. Note that in reports and report descriptors
+    /// the Usage Maximum may or may not include the Usage Page. The
+    /// maximum returned here always includes the [UsagePage].
+    pub fn maximum(&self) -> UsageMaximum {
+        self.maximum
+    }
+
+    /// If the given usage falls within this usage range (i.e. it is of the
+    /// same [UsagePage] and it within the inclusive [UsageMinimum]/[UsageMaximum])
+    /// return the provided usage as [Option].
+    pub fn lookup_usage<'a>(&self, usage: &'a Usage) -> Option<&'a Usage> {
+        if usage.usage_page == self.usage_page
+            && usage.usage_id >= self.minimum.usage_id()
+            && usage.usage_id <= self.maximum.usage_id()
+        {
+            Some(usage)
+        } else {
+            None
+        }
+    }
+
+    /// Look up the given [UsageId] and return the corresponding
+    /// [Usage], if any. The [UsageId] is assumed to be in the same
+    /// [UsagePage] as this range, use [lookup_usage()][Self::lookup_usage] if you need
+    /// a check for the [UsagePage] as well.
+    pub fn lookup_id(&self, id: UsageId) -> Option<Usage> {
+        if id >= self.minimum.usage_id() && id <= self.maximum.usage_id() {
+            Some(Usage::from_page_and_id(self.usage_page, id))
+        } else {
+            None
+        }
+    }
+}
+
+/// The usages of a main item as compiled by the parser: either a list of
+/// [Usage]s or a Usage Minimum/Maximum range that is not expanded in memory.
+#[derive(Clone, Debug)]
+enum UsageList {
+    List(Vec<Usage>),
+    /// `len` consecutive usages starting at `minimum`, see [Usages::get]
+    Range {
+        usage_page: UsagePage,
+        minimum: u32,
+        len: usize,
+    },
+}
+
+impl UsageList {
+    fn as_usages(&self) -> Usages<'_> {
+        match self {
+            UsageList::List(usages) => Usages(UsagesInner::List(usages)),
+            UsageList::Range {
+                usage_page,
+                minimum,
+                len,
+            } => Usages(UsagesInner::Range {
+                usage_page: *usage_page,
+                minimum: *minimum,
+                len: *len,
+            }),
+        }
+    }
+
+    fn into_vec(self) -> Vec<Usage> {
+        match self {
+            UsageList::List(usages) => usages,
+            UsageList::Range { .. } => self.as_usages().to_vec(),
+        }
+    }
+}
+
+/// A view of the usages of a [Field], see [ArrayField::usages].
+///
+/// An [ArrayField] declared with a Usage Minimum and Usage Maximum
+/// stores only that range, its usages are computed on access. Use
+/// [to_vec()](Usages::to_vec) where a list of all usages is needed.
+///
+/// ```
+/// # use hidreport::*;
+/// # fn func(field: &ArrayField) {
+/// for usage in field.usages() {
+///     println!("{usage:?}");
+/// }
+/// if field.usages().contains(&Usage::from((0x07, 0x04))) {
+///     println!("This array has the 'a' key");
+/// }
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Usages<'a>(UsagesInner<'a>);
+
+#[derive(Clone, Copy, Debug)]
+enum UsagesInner<'a> {
+    List(&'a [Usage]),
+    Range {
+        usage_page: UsagePage,
+        minimum: u32,
+        len: usize,
+    },
+}
+
+impl<'a> Usages<'a> {
+    /// The number of usages
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            UsagesInner::List(usages) => usages.len(),
+            UsagesInner::Range { len, .. } => *len,
+        }
+    }
+
+    /// Returns true if there are no usages
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the usage at the given index, if any
+    pub fn get(&self, index: usize) -> Option<Usage> {
+        match &self.0 {
+            UsagesInner::List(usages) => usages.get(index).copied(),
+            UsagesInner::Range {
+                usage_page,
+                minimum,
+                len,
+            } => (index < *len).then(|| Usage {
+                usage_page: *usage_page,
+                usage_id: UsageId((*minimum as usize + index) as u16),
+            }),
+        }
+    }
+
+    /// Returns the first usage, if any
+    pub fn first(&self) -> Option<Usage> {
+        self.get(0)
+    }
+
+    /// Returns the last usage, if any
+    pub fn last(&self) -> Option<Usage> {
+        self.len().checked_sub(1).and_then(|idx| self.get(idx))
+    }
+
+    /// Returns the index of the given usage, if any. For a Usage
+    /// Minimum/Maximum range this does not iterate over the usages.
+    pub fn position(&self, usage: &Usage) -> Option<usize> {
+        match &self.0 {
+            UsagesInner::List(usages) => usages.iter().position(|u| u == usage),
+            UsagesInner::Range {
+                usage_page,
+                minimum,
+                len,
+            } => {
+                if usage.usage_page != *usage_page {
+                    return None;
+                }
+                let index = usize::from(usage.usage_id.0.wrapping_sub(*minimum as u16));
+                (index < *len).then_some(index)
+            }
+        }
+    }
+
+    /// Returns true if the given usage is one of these usages
+    pub fn contains(&self, usage: &Usage) -> bool {
+        self.position(usage).is_some()
+    }
+
+    /// Returns an iterator over the usages
+    pub fn iter(&self) -> UsagesIter<'a> {
+        UsagesIter {
+            usages: *self,
+            indices: 0..self.len(),
+        }
+    }
+
+    /// Returns all usages as [Vec]
+    pub fn to_vec(&self) -> Vec<Usage> {
+        self.iter().collect()
+    }
+
+    /// Returns the usages as slice if they are stored as list
+    fn as_slice(&self) -> Option<&'a [Usage]> {
+        match &self.0 {
+            UsagesInner::List(usages) => Some(*usages),
+            UsagesInner::Range { .. } => None,
+        }
+    }
+}
+
+impl PartialEq for Usages<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl PartialEq<[Usage]> for Usages<'_> {
+    fn eq(&self, other: &[Usage]) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter().copied())
+    }
+}
+
+impl<'a> IntoIterator for Usages<'a> {
+    type Item = Usage;
+    type IntoIter = UsagesIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over [Usages], see [Usages::iter].
+#[derive(Clone, Debug)]
+pub struct UsagesIter<'a> {
+    usages: Usages<'a>,
+    indices: Range<usize>,
+}
+
+impl Iterator for UsagesIter<'_> {
+    type Item = Usage;
+
+    fn next(&mut self) -> Option<Usage> {
+        self.indices.next().and_then(|idx| self.usages.get(idx))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for UsagesIter<'_> {
+    fn next_back(&mut self) -> Option<Usage> {
+        self.indices
+            .next_back()
+            .and_then(|idx| self.usages.get(idx))
+    }
+}
+
+impl ExactSizeIterator for UsagesIter<'_> {}
+
+/// An [ArrayField] represents a group of physical controls,
+/// see section 6.2.2.5.
+///
+/// > An array provides an alternate means for
+/// > describing the data returned from a group of
+/// > buttons. Arrays are more efficient, if less flexible
+/// > than variable items. Rather than returning a single
+/// > bit for each button in the group, an array returns an
+/// > index in each field that corresponds to the pressed
+/// > button
+#[derive(Clone, Debug)]
+pub struct ArrayField {
+    id: FieldId,
+    report_id: Option<ReportId>,
+    flags: MainItemFlags,
+    /// The bits of this field within the report. If the report has a
+    /// [ReportId], the first byte is the Report ID and these bits start
+    /// at bit 8, i.e. they index into the report bytes as sent by the device.
+    pub bits: Range<usize>,
+    usages: UsageList,
+    /// The number of elements in this array. Unlike a [VariableField],
+    /// the Report Count of an array is not split into separate fields,
+    /// this field's [bits](ArrayField::bits) are Report Size times Report
+    /// Count, see [element_count](ArrayField::element_count) and
+    /// [element_bits](ArrayField::element_bits).
+    pub report_count: ReportCount,
+    pub logical_minimum: LogicalMinimum,
+    pub logical_maximum: LogicalMaximum,
+    pub physical_minimum: Option<PhysicalMinimum>,
+    pub physical_maximum: Option<PhysicalMaximum>,
+    pub unit: Option<Unit>,
+    pub unit_exponent: Option<UnitExponent>,
+    string_index: Option<StringIndex>,
+    string_range: Option<RangeInclusive<StringIndex>>,
+    designator_index: Option<DesignatorIndex>,
+    designator_range: Option<RangeInclusive<DesignatorIndex>>,
+    pub collections: Vec<Collection>,
+}
+
+impl ArrayField {
+    /// Returns the flags of the main item that created this field.
+    pub fn flags(&self) -> MainItemFlags {
+        self.flags
+    }
+
+    /// Returns the String Index of this field, if any. This is an index
+    /// into the device's string descriptors (Section 6.2.2.8).
+    pub fn string_index(&self) -> Option<StringIndex> {
+        self.string_index
+    }
+
+    /// Returns the String Minimum and String Maximum of this field, if any.
+    /// The string indices in this range are assigned to this field's usages
+    /// in order (Section 6.2.2.8).
+    pub fn string_range(&self) -> Option<RangeInclusive<StringIndex>> {
+        self.string_range.clone()
+    }
+
+    /// Returns the Designator Index of this field, if any. This is an index
+    /// into the device's Physical Descriptor sets (Section 6.2.2.8).
+    pub fn designator(&self) -> Option<DesignatorIndex> {
+        self.designator_index
+    }
+
+    /// Returns the Designator Minimum and Designator Maximum of this field,
+    /// if any. The designators in this range are assigned to this field's
+    /// usages in order (Section 6.2.2.8).
+    pub fn designator_range(&self) -> Option<RangeInclusive<DesignatorIndex>> {
+        self.designator_range.clone()
+    }
+
+    /// Returns true if this field's values are relative to the previous
+    /// value, false if they are absolute.
+    pub fn is_relative(&self) -> bool {
+        self.flags.is_relative
+    }
+
+    /// Returns the set of usages for this field. This is the
+    /// inclusive range of [UsageMinimum]`..=`[UsageMaximum]
+    /// as defined for this field. A Usage Minimum/Maximum range is
+    /// not expanded in memory, see [Usages].
+    ///
+    /// In most cases it's better to use [usage_range()](Self::usage_range)
+    /// instead.
+    pub fn usages(&self) -> Usages<'_> {
+        self.usages.as_usages()
+    }
+
+    /// Returns the [UsageRange] for this field, i.e. the first and last
+    /// of its [usages](ArrayField::usages). For an array with a Usage
+    /// Minimum and Usage Maximum this is that range and a lookup via
+    /// [UsageRange::lookup_usage] does not need to search the usages.
+    pub fn usage_range(&self) -> UsageRange {
+        let min = self.usages().first().unwrap();
+        let max = self.usages().last().unwrap();
+
+        UsageRange {
+            usage_page: min.usage_page,
+            minimum: UsageMinimum::from(min),
+            maximum: UsageMaximum::from(max),
+        }
+    }
+
+    /// Returns true if this field contains signed values,.
+    /// i.e. the LogicalMinimum is less than zero.
+    /// ```
+    /// # use hidreport::ArrayField;
+    /// # fn func(field: &ArrayField, bytes: &[u8]) {
+    /// if field.is_signed() {
+    ///     println!("Signed values: {:?}", field.extract_i32(bytes).unwrap());
+    /// } else {
+    ///     println!("Unsigned values: {:?}", field.extract_u32(bytes).unwrap());
+    /// }
+    ///
+    /// # }
+    /// ```
+    pub fn is_signed(&self) -> bool {
+        self.logical_minimum < LogicalMinimum(0)
+    }
+
+    /// Returns the number of elements in this array, i.e. the maximum
+    /// number of usages that can be active at the same time, e.g. 6 for
+    /// the keys of a boot protocol keyboard.
+    pub fn element_count(&self) -> usize {
+        usize::from(self.report_count)
+    }
+
+    /// Returns the size in bits of each element in this array, i.e. the
+    /// Report Size. The [bits](ArrayField::bits) of this field are
+    /// [element_count](ArrayField::element_count) times this size.
+    pub fn element_bits(&self) -> usize {
+        self.bits.len() / self.element_count()
+    }
+
+    /// Extract this field's values as [u32]s from a report's bytes.
+    /// The values are extracted at their correct bit size but upcasted
+    /// if need be into a [u32]. IOW it is safe to call this function
+    /// on e.g. an 8 bit unsigned field in the report.
+    ///
+    /// Check [ArrayField::is_signed] first to see if you should be
+    /// using [ArrayField::extract_i32] instead.
+    pub fn extract_u32(&self, bytes: &[u8]) -> Result<Vec<u32>> {
+        if let Some(report_id) = self.report_id {
+            if bytes.first().map(|b| ReportId(*b)) != Some(report_id) {
+                return Err(ParserError::MismatchingReportId);
+            }
+        }
+        let values: Result<Vec<u32>> = (0..self.element_count())
+            .map(|idx| self.extract_one_u32(bytes, idx))
+            .collect();
+
+        values
+    }
+
+    /// Extract this field's values as [i32]s from a report's bytes.
+    /// The values are extracted at their correct bit size but upcasted
+    /// if need be into a [i32]. IOW it is safe to call this function
+    /// on e.g. an 8 bit signed field in the report.
+    ///
+    /// Check [ArrayField::is_signed] first to see if you should be
+    /// using [ArrayField::extract_u32] instead.
+    pub fn extract_i32(&self, bytes: &[u8]) -> Result<Vec<i32>> {
+        if let Some(report_id) = self.report_id {
+            if bytes.first().map(|b| ReportId(*b)) != Some(report_id) {
+                return Err(ParserError::MismatchingReportId);
+            }
+        }
+
+        let values: Result<Vec<i32>> = (0..self.element_count())
+            .map(|idx| self.extract_one_i32(bytes, idx))
+            .collect();
+
+        values
+    }
+
+    /// Extract a single value from this array. See [ArrayField::extract_u32].
+    ///
+    /// The index must be less than [Self::report_count].
+    pub fn extract_one_u32(&self, bytes: &[u8], idx: usize) -> Result<u32> {
+        if idx >= self.element_count() {
+            return Err(ParserError::OutOfBounds);
+        }
+        ensure!(bytes.len() * 8 >= self.bits.end, ParserError::OutOfBounds);
+        if let Some(report_id) = self.report_id {
+            if ReportId(bytes[0]) != report_id {
+                return Err(ParserError::MismatchingReportId);
+            }
+        }
+
+        let element_bits = self.element_bits();
+        let offset = self.bits.start + element_bits * idx;
+        let bits = offset..offset + element_bits;
+        extract_bits(bytes, &bits, false).map(|v| v as u32)
+    }
+
+    /// Extract a single value from this array. See [ArrayField::extract_i32].
+    ///
+    /// The index must be less than [Self::report_count].
+    pub fn extract_one_i32(&self, bytes: &[u8], idx: usize) -> Result<i32> {
+        if idx >= self.element_count() {
+            return Err(ParserError::OutOfBounds);
+        }
+        ensure!(bytes.len() * 8 >= self.bits.end, ParserError::OutOfBounds);
+        if let Some(report_id) = self.report_id {
+            if ReportId(bytes[0]) != report_id {
+                return Err(ParserError::MismatchingReportId);
+            }
+        }
+
+        let element_bits = self.element_bits();
+        let offset = self.bits.start + element_bits * idx;
+        let bits = offset..offset + element_bits;
+        extract_bits(bytes, &bits, true).map(|v| v as i32)
+    }
+
+    /// Extract a single value from this array as [i64], signed or unsigned
+    /// depending on [ArrayField::is_signed].
+    fn extract_one(&self, bytes: &[u8], idx: usize) -> Result<i64> {
+        if self.is_signed() {
+            self.extract_one_i32(bytes, idx).map(i64::from)
+        } else {
+            self.extract_one_u32(bytes, idx).map(i64::from)
+        }
+    }
+
+    /// Returns the usage an array element value refers to, if any. The
+    /// value is an index into the [usages](ArrayField::usages) relative
+    /// to the [LogicalMinimum], e.g. on a keyboard the value 0x04 is the
+    /// usage Keyboard A. Values outside the logical range do not refer to
+    /// any usage.
+    ///
+    /// Note that the usage ID 0 is reserved, an element referring to it
+    /// (e.g. Keyboard "No Event") is an unused element.
+    ///
+    /// ```
+    /// # use hidreport::*;
+    /// # fn func(field: &ArrayField, bytes: &[u8]) {
+    /// for value in field.extract_u32(bytes).unwrap() {
+    ///     if let Some(usage) = field.usage_for_value(i64::from(value)) {
+    ///         if u16::from(usage.usage_id) != 0 {
+    ///             println!("{usage:?} is pressed");
+    ///         }
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn usage_for_value(&self, value: i64) -> Option<Usage> {
+        let (minimum, maximum) =
+            logical_range(self.logical_minimum, self.logical_maximum).into_inner();
+        if value < minimum || value > maximum {
+            return None;
+        }
+        self.usages().get((value - minimum) as usize)
+    }
+
+    /// Returns the usage of an array element value like
+    /// [usage_for_value](ArrayField::usage_for_value) but [None] for the
+    /// reserved usage ID 0 of unused elements.
+    fn active_usage(&self, value: i64) -> Option<Usage> {
+        self.usage_for_value(value)
+            .filter(|usage| u16::from(usage.usage_id) != 0)
+    }
+}
+
+/// A [ConstantField] is one that represents a [hid::MainItem]
+/// with Constant data, see Section 6.2.2.4.
+///
+/// > Constant indicates the item is a static read-only field in a
+/// > report and cannot be modified (written) by the
+/// > host.
+///
+/// Data in a [ConstantField] should be ignored by the caller, it
+/// is merely used as padding, usually to align the subsequent
+/// value on a byte boundary.
+#[derive(Clone, Debug)]
+pub struct ConstantField {
+    id: FieldId,
+    report_id: Option<ReportId>,
+    /// The bits of this field within the report. If the report has a
+    /// [ReportId], the first byte is the Report ID and these bits start
+    /// at bit 8, i.e. they index into the report bytes as sent by the device.
+    pub bits: Range<usize>,
+    usages: Vec<Usage>,
+    /// The global state at the time of the main item, used by
+    /// [ReportDescriptor::to_bytes] to recreate the original items.
+    globals: Globals,
+}
+
+impl ConstantField {
+    /// Create a new [ConstantField] for the given bits.
+    ///
+    /// The bits must include the Report ID byte (if any), see
+    /// [ConstantField::bits].
+    pub fn new(bits: Range<usize>) -> Self {
+        ConstantField {
+            id: FieldId(bits.start as u32),
+            report_id: None,
+            bits,
+            usages: vec![],
+            globals: Globals::default(),
+        }
+    }
+
+    pub fn usages(&self) -> &[Usage] {
+        &self.usages
+    }
+}
+
+/// A unique (within this report descriptor) identifier for a collection.
+///
+/// A device may have multiple collections that are otherwise identical
+/// (in particular logical collections), the collection ID serves
+/// to identify whether two fields are part of the same collection.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CollectionId(usize);
+
+/// Collections group [Fields](Field) together into logical or physical
+/// groups.
+///
+/// For example, a set of buttons and x/y axes may be grouped
+/// together to represent a Mouse device.
+/// Each [Field] may belong to a number of collections.
+///
+/// ```
+/// # use hidreport::*;
+/// # fn func(field: &VariableField) {
+/// let collection = field.collections.first().unwrap();
+/// match collection.collection_type() {
+///     CollectionType::Physical => println!("This field is part of a physical collection"),
+///     _ => {},
+/// }
+/// # }
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct Collection {
+    id: CollectionId,
+    collection_type: CollectionType,
+    usages: Vec<Usage>,
+}
+
+impl Collection {
+    /// Returns the unique ID for this collection
+    pub fn id(&self) -> &CollectionId {
+        &self.id
+    }
+    /// Return the type of this collection (e.g. physical, logical, ...)
+    pub fn collection_type(&self) -> CollectionType {
+        self.collection_type
+    }
+
+    /// Returns the usages assigned to this collection
+    pub fn usages(&self) -> &[Usage] {
+        &self.usages
+    }
+}
+
+impl PartialEq for Collection {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Collection {}
+
+impl Hash for Collection {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+/// Returns the sorted and distinct Report IDs of the given reports.
+fn report_ids(reports: &[RDescReport]) -> Vec<ReportId> {
+    reports
+        .iter()
+        .filter_map(|r| r.id)
+        .collect::<BTreeSet<ReportId>>()
+        .into_iter()
+        .collect()
+}
+
+/// Returns the size in bytes of the largest of the given reports.
+fn max_report_size(reports: &[RDescReport]) -> usize {
+    reports.iter().map(|r| r.size_in_bytes()).max().unwrap_or(0)
+}
+
+/// Returns the first [VariableField] of the report with the Battery Strength
+/// usage, if any.
+fn battery_strength_field<R: Report + ?Sized>(report: &R) -> Option<&VariableField> {
+    report.fields().iter().find_map(|f| match f {
+        Field::Variable(v) if v.usage == pages::usages::BATTERY_STRENGTH => Some(v),
+        _ => None,
+    })
+}
+
+/// Returns the Report IDs that are used by more than one of the given reports.
+fn duplicate_report_ids(reports: &[RDescReport]) -> Vec<Option<ReportId>> {
+    let mut seen = BTreeSet::new();
+    reports
+        .iter()
+        .filter_map(|r| (!seen.insert(r.id)).then_some(r.id))
+        .collect()
+}
+
+/// Returns true if the top-level collection of the report's first
+/// non-constant field is an Application collection with the given usage.
+fn is_application(report: &RDescReport, usage: Usage) -> bool {
+    report
+        .fields
+        .iter()
+        .find_map(|f| f.collections().first())
+        .is_some_and(|c| {
+            c.collection_type() == CollectionType::Application && c.usages().contains(&usage)
+        })
+}
+
+/// Returns the [VariableField] at exactly the given bits, if any.
+fn variable_at(report: &RDescReport, bits: Range<usize>) -> Option<&VariableField> {
+    report.fields.iter().find_map(|f| match f {
+        Field::Variable(v) if v.bits == bits => Some(v),
+        _ => None,
+    })
+}
+
+fn is_boot_keyboard_report(report: &RDescReport) -> bool {
+    if report.id.is_some() || !is_application(report, pages::usages::KEYBOARD) {
+        return false;
+    }
+    let has_modifiers = (0..8).all(|i| {
+        variable_at(report, i..i + 1).is_some_and(|v| {
+            v.usage == Usage::from_page_and_id(pages::KEYBOARD, UsageId(0xe0 + i as u16))
+        })
+    });
+    let has_reserved = report
+        .fields
+        .iter()
+        .any(|f| matches!(f, Field::Constant(c) if c.bits == (8..16)));
+    let has_keys = report.fields.iter().any(|f| match f {
+        Field::Array(a) => {
+            a.bits == (16..64)
+                && a.report_count == ReportCount(6)
+                && a.usages().iter().all(|u| u.usage_page == pages::KEYBOARD)
+        }
+        _ => false,
+    });
+    has_modifiers && has_reserved && has_keys
+}
+
+fn is_boot_mouse_report(report: &RDescReport) -> bool {
+    if report.id.is_some() || !is_application(report, pages::usages::MOUSE) {
+        return false;
+    }
+    let has_buttons = (0..3).all(|i| {
+        variable_at(report, i..i + 1).is_some_and(|v| {
+            v.usage == Usage::from_page_and_id(pages::BUTTON, UsageId(1 + i as u16))
+        })
+    });
+    let has_axis = |bits: Range<usize>, usage: Usage| {
+        variable_at(report, bits).is_some_and(|v| v.usage == usage && v.flags.is_relative)
+    };
+    has_buttons && has_axis(8..16, pages::usages::X) && has_axis(16..24, pages::usages::Y)
+}
+
+/// Appends the differences between the old and new reports of one direction.
+fn diff_reports(
+    direction: Direction,
+    old: &[RDescReport],
+    new: &[RDescReport],
+    diffs: &mut Vec<DescriptorDiff>,
+) {
+    fn find(reports: &[RDescReport], id: Option<ReportId>) -> Option<&RDescReport> {
+        reports.iter().find(|r| r.id == id)
+    }
+    for report in old {
+        if find(new, report.id).is_none() {
+            diffs.push(DescriptorDiff::ReportRemoved {
+                direction,
+                report_id: report.id,
+            });
+        }
+    }
+    for report in new {
+        if find(old, report.id).is_none() {
+            diffs.push(DescriptorDiff::ReportAdded {
+                direction,
+                report_id: report.id,
+            });
+        }
+    }
+
+    // Key each non-constant field by its first usage and the number of
+    // fields with that usage before it.
+    fn keyed(report: &RDescReport) -> Vec<((Usage, usize), &Field)> {
+        let mut fields: Vec<((Usage, usize), &Field)> = vec![];
+        for field in report.fields.iter() {
+            let Some(usage) = field.usages().first().filter(|_| !field.is_constant()) else {
+                continue;
+            };
+            let n = fields.iter().filter(|((u, _), _)| *u == usage).count();
+            fields.push(((usage, n), field));
+        }
+        fields
+    }
+    for old_report in old {
+        let Some(new_report) = find(new, old_report.id) else {
+            continue;
+        };
+        let report_id = old_report.id;
+        let old_fields = keyed(old_report);
+        let new_fields = keyed(new_report);
+        for (key, old_field) in &old_fields {
+            let usage = key.0;
+            match new_fields.iter().find(|(k, _)| k == key) {
+                None => diffs.push(DescriptorDiff::FieldRemoved {
+                    direction,
+                    report_id,
+                    usage,
+                }),
+                Some((_, new_field)) => {
+                    let (old_range, new_range) =
+                        (old_field.logical_range(), new_field.logical_range());
+                    if old_range != new_range {
+                        diffs.push(DescriptorDiff::LogicalRangeChanged {
+                            direction,
+                            report_id,
+                            usage,
+                            old: old_range,
+                            new: new_range,
+                        });
+                    }
+                    let (old_range, new_range) =
+                        (old_field.physical_range(), new_field.physical_range());
+                    if old_range != new_range {
+                        diffs.push(DescriptorDiff::PhysicalRangeChanged {
+                            direction,
+                            report_id,
+                            usage,
+                            old: old_range,
+                            new: new_range,
+                        });
+                    }
+                }
+            }
+        }
+        for (key, _) in &new_fields {
+            if !old_fields.iter().any(|(k, _)| k == key) {
+                diffs.push(DescriptorDiff::FieldAdded {
+                    direction,
+                    report_id,
+                    usage: key.0,
+                });
+            }
+        }
+    }
+}
+
+/// A difference between two report descriptors, see [ReportDescriptor::diff].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum DescriptorDiff {
+    /// A report only exists in the new report descriptor
+    ReportAdded {
+        direction: Direction,
+        report_id: Option<ReportId>,
+    },
+    /// A report only exists in the old report descriptor
+    ReportRemoved {
+        direction: Direction,
+        report_id: Option<ReportId>,
+    },
+    /// A field with this usage only exists in the new report
+    FieldAdded {
+        direction: Direction,
+        report_id: Option<ReportId>,
+        usage: Usage,
+    },
+    /// A field with this usage only exists in the old report
+    FieldRemoved {
+        direction: Direction,
+        report_id: Option<ReportId>,
+        usage: Usage,
+    },
+    /// The logical range of the field with this usage changed
+    LogicalRangeChanged {
+        direction: Direction,
+        report_id: Option<ReportId>,
+        usage: Usage,
+        old: RangeInclusive<i64>,
+        new: RangeInclusive<i64>,
+    },
+    /// The physical range of the field with this usage changed, [None]
+    /// if the field does not have a physical range
+    PhysicalRangeChanged {
+        direction: Direction,
+        report_id: Option<ReportId>,
+        usage: Usage,
+        old: Option<RangeInclusive<i64>>,
+        new: Option<RangeInclusive<i64>>,
+    },
+}
+
+/// Options for [ReportDescriptor::parse_with_options].
+///
+/// ```
+/// # use hidreport::*;
+/// let options = ParseOptions::default().strict(true);
+/// assert!(options.strict);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ParseOptions {
+    /// Reject report descriptors that fail the checks of
+    /// [ReportDescriptor::parse_validated] instead of parsing them as best
+    /// as possible. In addition, in strict mode a data main item without
+    /// a Logical Minimum, Logical Maximum, Report Size or Report Count is
+    /// an error, otherwise those default to zero like in most other HID
+    /// implementations.
+    pub strict: bool,
+}
+
+impl ParseOptions {
+    /// Set the [strict](ParseOptions::strict) option.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+}
+
+/// A non-fatal issue found while parsing a report descriptor,
+/// see [ReportDescriptor::warnings].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ParseWarning {
+    /// More than one report of the same direction uses this Report ID.
+    DuplicateReportId { report_id: Option<ReportId> },
+    /// The Input, Output or Feature data item at this offset has a
+    /// Report Count of zero and was skipped.
+    ZeroReportCount { offset: usize },
+    /// The Input, Output or Feature data item at this offset has a
+    /// Logical Maximum that cannot be represented in its Report Size,
+    /// e.g. a logical range of 0..=1000 in 8 bits. Values above the
+    /// largest representable value cannot be sent by the device.
+    ///
+    /// The `field` is the [FieldId] of the first affected [Field] of
+    /// that data item.
+    LogicalMaximumTooLarge { offset: usize, field: FieldId },
+}
+
+#[derive(Debug)]
+pub enum ParserError {
+    InvalidData {
+        offset: usize,
+        message: String,
+    },
+    OutOfBounds,
+    MismatchingReportId,
+    InvalidReport {
+        message: String,
+    },
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+}
+
+impl core::fmt::Display for ParserError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParserError::InvalidData { offset, message } => {
+                write!(f, "Invalid data at offset {offset}: {message}")
+            }
+            ParserError::OutOfBounds => write!(f, "Parsing would lead to out-of-bounds"),
+            ParserError::MismatchingReportId => write!(f, "Mismatching Report ID"),
+            ParserError::InvalidReport { message } => write!(f, "Invalid report: {message}"),
+            #[cfg(feature = "std")]
+            ParserError::Io(e) => write!(f, "I/O error: {e}"),
+        }
+    }
+}
+
+impl core::error::Error for ParserError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            ParserError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for ParserError {
+    fn from(e: std::io::Error) -> ParserError {
+        ParserError::Io(e)
+    }
+}
+
+/// Converts into an [std::io::Error] of kind
+/// [InvalidData](std::io::ErrorKind::InvalidData), except for
+/// [ParserError::Io] which is returned as-is.
+#[cfg(feature = "std")]
+impl From<ParserError> for std::io::Error {
+    fn from(e: ParserError) -> std::io::Error {
+        match e {
+            ParserError::Io(e) => e,
+            e => std::io::Error::new(std::io::ErrorKind::InvalidData, e),
+        }
+    }
+}
+
+type Result<T> = core::result::Result<T, ParserError>;
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Globals {
+    usage_page: Option<UsagePage>,
+    logical_minimum: Option<LogicalMinimum>,
+    logical_maximum: Option<LogicalMaximum>,
+    physical_minimum: Option<PhysicalMinimum>,
+    physical_maximum: Option<PhysicalMaximum>,
+    unit_exponent: Option<UnitExponent>,
+    unit: Option<Unit>,
+    report_size: Option<ReportSize>,
+    report_id: Option<ReportId>,
+    report_count: Option<ReportCount>,
+}
+
+/// Special struct for the [Locals] because the usage_page
+/// is optional for those, unlike our [Usage] struct which is
+/// the finalized one.
+#[derive(Clone, Copy, Debug)]
+struct LocalUsage {
+    usage_page: Option<UsagePage>,
+    usage_id: UsageId,
+    /// The global Usage Page at the time of this Usage item, used
+    /// if the Usage item does not have a Usage Page of its own.
+    global_usage_page: Option<UsagePage>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Locals {
+    usage: Vec<LocalUsage>,
+    // FIXME: needs the same LocalUsage treatment
+    usage_minimum: Option<UsageMinimum>,
+    usage_maximum: Option<UsageMaximum>,
+    designator_index: Option<DesignatorIndex>,
+    designator_minimum: Option<DesignatorMinimum>,
+    designator_maximum: Option<DesignatorMaximum>,
+    string_index: Option<StringIndex>,
+    string_minimum: Option<StringMinimum>,
+    string_maximum: Option<StringMaximum>,
+    delimiter: Option<Delimiter>,
+}
+
+#[derive(Debug)]
+struct Stack {
+    globals: Vec<Globals>,
+    // Push and Pop only apply to the global items (Section 6.2.2.7),
+    // the local items are reset after each main item instead.
+    locals: Locals,
+    pub collections: Vec<Collection>,
+}
+
+impl Stack {
+    fn new() -> Self {
+        Stack {
+            globals: vec![Globals::default()],
+            locals: Locals::default(),
+            collections: vec![],
+        }
+    }
+
+    fn push(&mut self) {
+        let current = self.globals.last().unwrap();
+        self.globals.push(*current);
+    }
+
+    fn pop(&mut self) -> Result<()> {
+        // The bottom of the stack is the initial state which was never
+        // pushed and thus must not be popped
+        ensure!(
+            self.globals.len() > 1,
+            ParserError::InvalidData {
+                offset: 0,
+                message: "Pop without matching Push".into(),
+            }
+        );
+        self.globals.pop();
+        Ok(())
+    }
+
+    fn reset_locals(&mut self) {
+        self.locals = Locals::default();
+    }
+
+    fn globals(&mut self) -> &mut Globals {
+        self.globals.last_mut().unwrap()
+    }
+
+    fn locals(&mut self) -> &mut Locals {
+        &mut self.locals
+    }
+
+    // Should be globals and globals_mut but i'd have to
+    // update the update_stack macro for that.
+    fn globals_const(&self) -> &Globals {
+        self.globals.last().unwrap()
+    }
+
+    fn locals_const(&self) -> &Locals {
+        &self.locals
+    }
+}
+
+/// The maximum number of usages and the maximum Report Count of a main
+/// item, see `HID_MAX_USAGES` in the Linux kernel.
+const MAX_USAGES: usize = 12288;
+
+/// The maximum Report Size in bits of a main item.
+const MAX_REPORT_SIZE: usize = 256;
+
+/// The maximum number of bits of a main item.
+const MAX_ITEM_BITS: u32 = (MAX_USAGES * MAX_REPORT_SIZE) as u32;
+
+/// The boot protocol keyboard report descriptor, see Appendix B.1.
+const BOOT_KEYBOARD_RDESC: &[u8] = &[
+    0x05, 0x01, // Usage Page (Generic Desktop)
+    0x09, 0x06, // Usage (Keyboard)
+    0xa1, 0x01, // Collection (Application)
+    0x05, 0x07, //  Usage Page (Keyboard)
+    0x19, 0xe0, //  Usage Minimum (224)
+    0x29, 0xe7, //  Usage Maximum (231)
+    0x15, 0x00, //  Logical Minimum (0)
+    0x25, 0x01, //  Logical Maximum (1)
+    0x75, 0x01, //  Report Size (1)
+    0x95, 0x08, //  Report Count (8)
+    0x81, 0x02, //  Input (Data,Var,Abs)
+    0x95, 0x01, //  Report Count (1)
+    0x75, 0x08, //  Report Size (8)
+    0x81, 0x01, //  Input (Cnst,Arr,Abs)
+    0x95, 0x05, //  Report Count (5)
+    0x75, 0x01, //  Report Size (1)
+    0x05, 0x08, //  Usage Page (LED)
+    0x19, 0x01, //  Usage Minimum (1)
+    0x29, 0x05, //  Usage Maximum (5)
+    0x91, 0x02, //  Output (Data,Var,Abs)
+    0x95, 0x01, //  Report Count (1)
+    0x75, 0x03, //  Report Size (3)
+    0x91, 0x01, //  Output (Cnst,Arr,Abs)
+    0x95, 0x06, //  Report Count (6)
+    0x75, 0x08, //  Report Size (8)
+    0x15, 0x00, //  Logical Minimum (0)
+    0x25, 0x65, //  Logical Maximum (101)
+    0x05, 0x07, //  Usage Page (Keyboard)
+    0x19, 0x00, //  Usage Minimum (0)
+    0x29, 0x65, //  Usage Maximum (101)
+    0x81, 0x00, //  Input (Data,Arr,Abs)
+    0xc0, // End Collection
+];
+
+/// The boot protocol mouse report descriptor, see Appendix B.2.
+const BOOT_MOUSE_RDESC: &[u8] = &[
+    0x05, 0x01, // Usage Page (Generic Desktop)
+    0x09, 0x02, // Usage (Mouse)
+    0xa1, 0x01, // Collection (Application)
+    0x09, 0x01, //  Usage (Pointer)
+    0xa1, 0x00, //  Collection (Physical)
+    0x05, 0x09, //   Usage Page (Button)
+    0x19, 0x01, //   Usage Minimum (1)
+    0x29, 0x03, //   Usage Maximum (3)
+    0x15, 0x00, //   Logical Minimum (0)
+    0x25, 0x01, //   Logical Maximum (1)
+    0x95, 0x03, //   Report Count (3)
+    0x75, 0x01, //   Report Size (1)
+    0x81, 0x02, //   Input (Data,Var,Abs)
+    0x95, 0x01, //   Report Count (1)
+    0x75, 0x05, //   Report Size (5)
+    0x81, 0x01, //   Input (Cnst,Arr,Abs)
+    0x05, 0x01, //   Usage Page (Generic Desktop)
+    0x09, 0x30, //   Usage (X)
+    0x09, 0x31, //   Usage (Y)
+    0x15, 0x81, //   Logical Minimum (-127)
+    0x25, 0x7f, //   Logical Maximum (127)
+    0x75, 0x08, //   Report Size (8)
+    0x95, 0x02, //   Report Count (2)
+    0x81, 0x06, //   Input (Data,Var,Rel)
+    0xc0, //  End Collection
+    0xc0, // End Collection
+];
+
+fn compile_usages(globals: &Globals, locals: &Locals) -> Result<UsageList> {
+    // Prefer UsageMinimum/Maximum over Usage because the latter may be set from an earlier call
+    match locals.usage_minimum {
+        Some(_) => {
+            let Some(min) = locals.usage_minimum else {
+                return Err(ParserError::InvalidData {
+                    offset: 0,
+                    message: "Missing UsageMinimum in locals".into(),
+                });
+            };
+            let Some(max) = locals.usage_maximum else {
+                return Err(ParserError::InvalidData {
+                    offset: 0,
+                    message: "Missing UsageMaximum in locals".into(),
+                });
+            };
+            let Some(usage_page) = globals.usage_page else {
+                return Err(ParserError::InvalidData {
+                    offset: 0,
+                    message: "Missing UsagePage in globals".into(),
+                });
+            };
+            let min: u32 = min.into();
+            let max: u32 = max.into();
+            ensure!(
+                min <= max,
+                ParserError::InvalidData {
+                    offset: 0,
+                    message: format!(
+                        "Usage Minimum 0x{min:x} is greater than Usage Maximum 0x{max:x}"
+                    ),
+                }
+            );
+            ensure!(
+                max - min < MAX_USAGES as u32,
+                ParserError::InvalidData {
+                    offset: 0,
+                    message: format!(
+                        "Usage Minimum 0x{min:x} to Usage Maximum 0x{max:x} exceeds {MAX_USAGES} usages"
+                    ),
+                }
+            );
+
+            // Stored as range, large ranges are common for arrays
+            // (keyboards, consumer controls)
+            Ok(UsageList::Range {
+                usage_page: UsagePage(usage_page.into()),
+                minimum: min,
+                len: (max - min) as usize + 1,
+            })
+        }
+        None => {
+            // A Usage without Usage Page uses the Usage Page at the time of
+            // the Usage item, so a list of usages may interleave different
+            // Usage Pages. Like the Linux kernel we re-assign the final
+            // Usage Page to the trailing usages in case the descriptor has
+            // a Usage Page *after* the Usage.
+            let mut usages = Vec::with_capacity(locals.usage.len());
+            let mut is_trailing = true;
+            for usage in locals.usage.iter().rev() {
+                let usage_page = match usage {
+                    // local item's Usage had a Usage Page included
+                    LocalUsage {
+                        usage_page: Some(up),
+                        ..
+                    } => *up,
+                    // Usage Page comes from the global item
+                    LocalUsage {
+                        usage_page: None,
+                        global_usage_page,
+                        ..
+                    } => {
+                        let up = if is_trailing && *global_usage_page != globals.usage_page {
+                            globals.usage_page
+                        } else {
+                            is_trailing = false;
+                            *global_usage_page
+                        };
+                        let Some(up) = up else {
+                            return Err(ParserError::InvalidData {
+                                offset: 0,
+                                message: "Missing UsagePage in globals".into(),
+                            });
+                        };
+                        up
+                    }
+                };
+                usages.push(Usage {
+                    usage_page,
+                    usage_id: usage.usage_id,
+                });
+            }
+            usages.reverse();
+            Ok(UsageList::List(usages))
+        }
+    }
+}
+
+fn handle_main_item(
+    item: &MainItem,
+    stack: &mut Stack,
+    base_id: u32,
+    options: ParseOptions,
+) -> Result<Vec<Field>> {
+    let globals = stack.globals_const();
+    let locals = stack.locals_const();
+
+    let report_id = globals.report_id;
+
+    let (is_constant, is_variable, flags) = match item {
+        MainItem::Input(i) => (
+            i.is_constant(),
+            i.is_variable(),
+            MainItemFlags::from_item(i, false),
+        ),
+        MainItem::Output(i) => (
+            i.is_constant(),
+            i.is_variable(),
+            MainItemFlags::from_item(i, i.is_volatile()),
+        ),
+        MainItem::Feature(i) => (
+            i.is_constant(),
+            i.is_variable(),
+            MainItemFlags::from_item(i, i.is_volatile()),
+        ),
+        _ => panic!("Invalid item for handle_main_item()"),
+    };
+
+    if options.strict && !is_constant {
+        for (name, is_set) in [
+            ("Logical Minimum", globals.logical_minimum.is_some()),
+            ("Logical Maximum", globals.logical_maximum.is_some()),
+            ("Report Size", globals.report_size.is_some()),
+            ("Report Count", globals.report_count.is_some()),
+        ] {
+            ensure!(
+                is_set,
+                ParserError::InvalidData {
+                    offset: 0,
+                    message: format!("Missing {name} for main item"),
+                }
+            );
+        }
+    }
+
+    let bit_offset = 0;
+    // We have HID report descriptors in the wild that do not set a report size/count/whatever.
+    // Since the most important implementations so far have been in C-like languages, they
+    // will likely default to zero for unset values (unlike our None).
+    // Let's do this here to be as compatible as possible.
+    let report_size = globals.report_size.unwrap_or(ReportSize(0));
+    let report_count = globals.report_count.unwrap_or(ReportCount(0));
+
+    if report_count == ReportCount(0) || report_size == ReportSize(0) {
+        return Ok(vec![]);
+    }
+
+    // Same limits as the Linux kernel, anything bigger is a broken descriptor
+    ensure!(
+        usize::from(report_size) <= MAX_REPORT_SIZE,
+        ParserError::InvalidData {
+            offset: 0,
+            message: format!("Report Size {report_size} exceeds {MAX_REPORT_SIZE} bits"),
+        }
+    );
+    ensure!(
+        usize::from(report_count) <= MAX_USAGES,
+        ParserError::InvalidData {
+            offset: 0,
+            message: format!("Report Count {report_count} exceeds {MAX_USAGES}"),
+        }
+    );
+
+    let nbits = usize::from(report_size)
+        .checked_mul(usize::from(report_count))
+        .ok_or(ParserError::OutOfBounds)?;
+
+    if is_constant {
+        let bits = bit_offset..(bit_offset + nbits);
+
+        let field = ConstantField {
+            id: FieldId(base_id + bit_offset as u32),
+            bits,
+            report_id,
+            usages: vec![],
+            globals: *globals,
+        };
+        return Ok(vec![Field::Constant(field)]);
+    }
+
+    let logical_minimum = globals.logical_minimum.unwrap_or(LogicalMinimum(0));
+    let logical_maximum = globals.logical_maximum.unwrap_or(LogicalMaximum(0));
+
+    // Some report descriptors are missing either phys min or max, assume zero
+    // where one of them is not None
+    let physical_maximum: Option<PhysicalMaximum>;
+    let physical_minimum: Option<PhysicalMinimum>;
+    if globals.physical_minimum.is_some() != globals.physical_maximum.is_some() {
+        physical_maximum = globals.physical_maximum.or(Some(PhysicalMaximum(0)));
+        physical_minimum = globals.physical_minimum.or(Some(PhysicalMinimum(0)));
+    } else {
+        physical_maximum = globals.physical_maximum;
+        physical_minimum = globals.physical_minimum;
+    }
+
+    let unit = globals.unit;
+    let unit_exponent = globals.unit_exponent;
+    let string_index = locals.string_index;
+    let designator_index = locals.designator_index;
+    let string_range = locals
+        .string_minimum
+        .zip(locals.string_maximum)
+        .map(|(min, max)| StringIndex(min.0)..=StringIndex(max.0));
+    let designator_range = locals
+        .designator_minimum
+        .zip(locals.designator_maximum)
+        .map(|(min, max)| DesignatorIndex(min.0)..=DesignatorIndex(max.0));
+
+    let usages = compile_usages(globals, locals)?;
+    ensure!(
+        !usages.as_usages().is_empty(),
+        "Missing Usages for main item"
+    );
+
+    // This may be an empty vec
+    let collections = stack.collections.clone();
+    let fields: Vec<Field> = if is_variable {
+        let mut bit_offset = 0;
+        Range {
+            start: 0,
+            end: usize::from(report_count),
+        }
+        .map(|c| {
+            let nbits = usize::from(report_size);
+            let bits = bit_offset..(bit_offset + nbits);
+            bit_offset += nbits;
+
+            let usage = usages
+                .as_usages()
+                .get(c)
+                .or_else(|| usages.as_usages().last())
+                .unwrap();
+            // Like the usages, a designator range is assigned in order and
+            // the last designator repeats for any remaining fields
+            let designator_index = designator_index.or_else(|| {
+                designator_range.as_ref().map(|range| {
+                    let index = range.start().0.saturating_add(c as u32);
+                    DesignatorIndex(index.min(range.end().0))
+                })
+            });
+            let field = VariableField {
+                id: FieldId(base_id + bit_offset as u32),
+                usage,
+                bits,
+                logical_minimum,
+                logical_maximum,
+                physical_minimum,
+                physical_maximum,
+                unit,
+                unit_exponent,
+                string_index,
+                designator_index,
+                flags,
+                collections: collections.clone(),
+                report_id,
+            };
+            Field::Variable(field)
+        })
+        .collect()
+    } else {
+        let bit_offset = 0;
+        let bits = bit_offset..(bit_offset + nbits);
+
+        let field = ArrayField {
+            id: FieldId(base_id + bit_offset as u32),
+            usages,
+            bits,
+            logical_minimum,
+            logical_maximum,
+            physical_minimum,
+            physical_maximum,
+            unit,
+            unit_exponent,
+            string_index,
+            string_range,
+            designator_index,
+            designator_range,
+            flags,
+            collections,
+            report_id,
+            report_count,
+        };
+
+        vec![Field::Array(field)]
+    };
+
+    Ok(fields)
+}
+
+macro_rules! update_stack {
+    ($stack:ident, $class:ident, $which:ident, $from:ident) => {
+        //println!("Updating {} with value {:?}", stringify!($which), &$from);
+        let state = $stack.$class();
+        state.$which = Some($from);
+    };
+}
+
+fn parse_report_descriptor(bytes: &[u8]) -> Result<ReportDescriptor> {
+    ReportDescriptor::parse_with_options(bytes, ParseOptions::default())
+}
+
+/// Returns true if the field's logical maximum can be represented in the
+/// field's bits (or each array element's bits), as signed value if the
+/// logical minimum is negative. Constant fields always fit.
+fn fits_logical_maximum(field: &Field) -> bool {
+    let nbits = match field {
+        Field::Variable(v) => v.bits.len(),
+        Field::Array(a) => a.element_bits(),
+        Field::Constant(_) => return true,
+    };
+    let (min, max) = field.logical_range().into_inner();
+    match nbits {
+        0 => max <= 0,
+        // Logical values are at most 32 bits, anything wider fits
+        33.. => true,
+        n if min < 0 => max < 1 << (n - 1),
+        n => max < 1 << n,
+    }
+}
+
+fn build_report_descriptor(
+    items: &hid::ReportDescriptorItems,
+    options: ParseOptions,
+) -> Result<ReportDescriptor> {
+    let mut stack = Stack::new();
+    let mut rdesc = ReportDescriptor::default();
+
+    for rdesc_item in items {
+        //println!("Handling offset {}", rdesc_item.offset());
+        let item = rdesc_item.item();
+        match item.item_type() {
+            ItemType::Main(MainItem::Collection(i)) => {
+                let globals = stack.globals_const();
                 let locals = stack.locals_const();
                 // This may be an empty vec
                 let usages = match compile_usages(globals, locals) {
@@ -1287,268 +4678,4074 @@ fn parse_report_descriptor(bytes: &[u8]) -> Result<ReportDescriptor> {
                             message,
                         })
                     }
-                    Err(e) => return Err(e),
+                    Err(e) => return Err(e),
+                };
+                let c = Collection {
+                    id: CollectionId(rdesc_item.offset()),
+                    collection_type: i,
+                    usages: usages.into_vec(),
+                };
+                stack.collections.push(c);
+                stack.reset_locals();
+            }
+            ItemType::Main(MainItem::EndCollection) => {
+                if stack.collections.pop().is_none() {
+                    return Err(ParserError::InvalidData {
+                        offset: rdesc_item.offset(),
+                        message: "Too many EndCollection".into(),
+                    });
+                };
+                stack.reset_locals();
+            }
+            ItemType::Main(item) => {
+                // Devices in the wild have data items with a Report Count of
+                // zero (libinput issue 944), skip those with a warning.
+                let is_data = match &item {
+                    MainItem::Input(i) => i.is_data(),
+                    MainItem::Output(i) => i.is_data(),
+                    MainItem::Feature(i) => i.is_data(),
+                    _ => false,
+                };
+                if is_data && stack.globals_const().report_count == Some(ReportCount(0)) {
+                    rdesc.warnings.push(ParseWarning::ZeroReportCount {
+                        offset: rdesc_item.offset(),
+                    });
+                }
+                // The field IDs are base_id plus the bit offset within the
+                // main item, make sure that cannot overflow
+                let base_id = rdesc_item
+                    .offset()
+                    .checked_mul(8)
+                    .and_then(|id| u32::try_from(id).ok())
+                    .filter(|id| id.checked_add(MAX_ITEM_BITS).is_some());
+                let Some(base_id) = base_id else {
+                    return Err(ParserError::InvalidData {
+                        offset: rdesc_item.offset(),
+                        message: "Report descriptor is too large".into(),
+                    });
+                };
+                let mut fields = match handle_main_item(&item, &mut stack, base_id, options) {
+                    Ok(fields) => fields,
+                    Err(ParserError::InvalidData { message, .. }) => {
+                        return Err(ParserError::InvalidData {
+                            offset: rdesc_item.offset(),
+                            message,
+                        })
+                    }
+                    Err(e) => return Err(e),
+                };
+                stack.reset_locals();
+
+                if let Some(field) = fields.iter().find(|f| !fits_logical_maximum(f)) {
+                    rdesc.warnings.push(ParseWarning::LogicalMaximumTooLarge {
+                        offset: rdesc_item.offset(),
+                        field: field.id(),
+                    });
+                }
+
+                // Report descriptors with a ReportCount or ReportSize of 0 (or those missing)
+                // will have an empty fields list. These exist in the wild.
+                if !fields.is_empty() {
+                    // Now update the returned field(s) and push them into the right report
+                    let direction = match item {
+                        MainItem::Input(_) => Direction::Input,
+                        MainItem::Output(_) => Direction::Output,
+                        MainItem::Feature(_) => Direction::Feature,
+                        _ => panic!("Invalid item for handle_main_item()"),
+                    };
+
+                    let reports: &mut Vec<RDescReport> = match direction {
+                        Direction::Input => &mut rdesc.input_reports,
+                        Direction::Output => &mut rdesc.output_reports,
+                        Direction::Feature => &mut rdesc.feature_reports,
+                    };
+
+                    let report_id = fields.first().unwrap().report_id();
+                    if reports
+                        .iter()
+                        .any(|r| r.id.is_some() != report_id.is_some())
+                    {
+                        return Err(ParserError::InvalidData {
+                            offset: rdesc_item.offset(),
+                            message: format!(
+                                "{direction:?} reports both with and without a Report ID"
+                            ),
+                        });
+                    }
+                    let report = match report_id {
+                        None => reports.first_mut(),
+                        Some(id) => reports
+                            .iter_mut()
+                            .find(|r| r.id.is_some() && &r.id.unwrap() == id),
+                    };
+
+                    // A report's fields must all be in the same top-level collection,
+                    // otherwise two collections define the same Report ID
+                    if let Some(report) = &report {
+                        let application = |fields: &[Field]| {
+                            fields
+                                .iter()
+                                .find_map(|f| f.collections().first())
+                                .map(|c| c.id().clone())
+                        };
+                        if let (Some(existing), Some(new)) =
+                            (application(&report.fields), application(&fields))
+                        {
+                            ensure!(
+                                existing == new,
+                                ParserError::InvalidData {
+                                    offset: rdesc_item.offset(),
+                                    message: match report_id {
+                                        Some(id) => format!(
+                                            "{direction:?} Report ID {id} is used in more than one collection"
+                                        ),
+                                        None => format!(
+                                            "{direction:?} report without Report ID is used in more than one collection"
+                                        ),
+                                    },
+                                }
+                            );
+                        }
+                    }
+
+                    let report = match report {
+                        None => {
+                            let initial_size = if report_id.is_some() { 8 } else { 0 };
+                            reports.push(RDescReport {
+                                direction,
+                                id: *report_id,
+                                size: initial_size,
+                                fields: vec![],
+                            });
+                            reports.last_mut().unwrap()
+                        }
+                        Some(r) => r,
+                    };
+
+                    // We know which report the fields belong to, let's update the offsets and field id
+                    let offset = report.size;
+                    let nbits: usize = fields.iter().map(|f| f.len()).sum();
+                    report.size = offset.checked_add(nbits).ok_or(ParserError::OutOfBounds)?;
+                    fields.iter_mut().for_each(|f| f.update_bit_offset(offset));
+
+                    report.fields.append(&mut fields);
+                }
+            }
+            ItemType::Long => {}
+            ItemType::Reserved => {}
+            ItemType::Global(GlobalItem::UsagePage { usage_page }) => {
+                update_stack!(stack, globals, usage_page, usage_page);
+            }
+            ItemType::Global(GlobalItem::LogicalMinimum { minimum }) => {
+                update_stack!(stack, globals, logical_minimum, minimum);
+            }
+            ItemType::Global(GlobalItem::LogicalMaximum { maximum }) => {
+                // We don't know if the maximum is signed or unsigned unless we
+                // look at the minimum value and check if that is signed or unsigned.
+                // We default to signed but if the minimum is unsigned, we might have
+                // to re-interpret.
+                let minimum = stack
+                    .globals_const()
+                    .logical_minimum
+                    .unwrap_or(LogicalMinimum(0));
+                let mut maximum = maximum;
+                if minimum < LogicalMinimum(0) {
+                    if let Some(data) = item.data() {
+                        if !data.is_empty() {
+                            maximum = LogicalMaximum(hid::hiddata_signed(&data).unwrap());
+                        }
+                    }
                 };
-                let c = Collection {
-                    id: CollectionId(rdesc_item.offset() as u32),
-                    collection_type: i,
-                    usages,
+                update_stack!(stack, globals, logical_maximum, maximum);
+            }
+            ItemType::Global(GlobalItem::PhysicalMinimum { minimum }) => {
+                update_stack!(stack, globals, physical_minimum, minimum);
+            }
+            ItemType::Global(GlobalItem::PhysicalMaximum { maximum }) => {
+                // We don't know if the maximum is signed or unsigned unless we
+                // look at the minimum value and check if that is signed or unsigned.
+                // We default to signed but if the minimum is unsigned, we might have
+                // to re-interpret.
+                let minimum = stack
+                    .globals_const()
+                    .physical_minimum
+                    .unwrap_or(PhysicalMinimum(0));
+                let mut maximum = maximum;
+                if minimum < PhysicalMinimum(0) {
+                    if let Some(data) = item.data() {
+                        if !data.is_empty() {
+                            maximum = PhysicalMaximum(hid::hiddata_signed(&data).unwrap())
+                        }
+                    }
                 };
-                stack.collections.push(c);
-                stack.reset_locals();
+                update_stack!(stack, globals, physical_maximum, maximum);
+            }
+            ItemType::Global(GlobalItem::UnitExponent { exponent }) => {
+                update_stack!(stack, globals, unit_exponent, exponent);
+            }
+            ItemType::Global(GlobalItem::Unit { unit }) => {
+                update_stack!(stack, globals, unit, unit);
+            }
+            ItemType::Global(GlobalItem::ReportSize { size }) => {
+                update_stack!(stack, globals, report_size, size);
+            }
+            ItemType::Global(GlobalItem::ReportId { id }) => {
+                update_stack!(stack, globals, report_id, id);
+            }
+            ItemType::Global(GlobalItem::ReportCount { count }) => {
+                update_stack!(stack, globals, report_count, count);
+            }
+            ItemType::Global(GlobalItem::Push) => {
+                stack.push();
+            }
+            ItemType::Global(GlobalItem::Pop) => match stack.pop() {
+                Ok(_) => {}
+                Err(ParserError::InvalidData { message, .. }) => {
+                    return Err(ParserError::InvalidData {
+                        offset: rdesc_item.offset(),
+                        message,
+                    })
+                }
+                Err(e) => return Err(e),
+            },
+            ItemType::Global(GlobalItem::Reserved) => {}
+            ItemType::Local(LocalItem::Usage {
+                usage_page,
+                usage_id,
+            }) => {
+                let usage = LocalUsage {
+                    usage_page,
+                    usage_id,
+                    global_usage_page: stack.globals_const().usage_page,
+                };
+                stack.locals().usage.push(usage);
+            }
+            ItemType::Local(LocalItem::UsageMinimum { minimum }) => {
+                update_stack!(stack, locals, usage_minimum, minimum);
+            }
+            ItemType::Local(LocalItem::UsageMaximum { maximum }) => {
+                update_stack!(stack, locals, usage_maximum, maximum);
+            }
+            ItemType::Local(LocalItem::DesignatorIndex { index }) => {
+                update_stack!(stack, locals, designator_index, index);
+            }
+            ItemType::Local(LocalItem::DesignatorMinimum { minimum }) => {
+                update_stack!(stack, locals, designator_minimum, minimum);
+            }
+            ItemType::Local(LocalItem::DesignatorMaximum { maximum }) => {
+                update_stack!(stack, locals, designator_maximum, maximum);
+            }
+            ItemType::Local(LocalItem::StringIndex { index }) => {
+                update_stack!(stack, locals, string_index, index);
+            }
+            ItemType::Local(LocalItem::StringMinimum { minimum }) => {
+                update_stack!(stack, locals, string_minimum, minimum);
+            }
+            ItemType::Local(LocalItem::StringMaximum { maximum }) => {
+                update_stack!(stack, locals, string_maximum, maximum);
+            }
+            ItemType::Local(LocalItem::Delimiter { delimiter }) => {
+                update_stack!(stack, locals, delimiter, delimiter);
+            }
+            ItemType::Local(LocalItem::Reserved { value: _ }) => {}
+        };
+    }
+
+    if let Some(collection) = stack.collections.last() {
+        return Err(ParserError::InvalidData {
+            offset: collection.id.0,
+            message: "Collection without EndCollection".into(),
+        });
+    }
+
+    let duplicates: Vec<Option<ReportId>> = [
+        &rdesc.input_reports,
+        &rdesc.output_reports,
+        &rdesc.feature_reports,
+    ]
+    .iter()
+    .flat_map(|reports| duplicate_report_ids(reports))
+    .collect();
+    rdesc.warnings.extend(
+        duplicates
+            .into_iter()
+            .map(|report_id| ParseWarning::DuplicateReportId { report_id }),
+    );
+
+    Ok(rdesc)
+}
+
+/// Returns the smallest item data for a value that is interpreted as signed
+/// by the parser.
+fn signed_data(v: i32) -> Vec<u8> {
+    if let Ok(v) = i8::try_from(v) {
+        v.to_le_bytes().to_vec()
+    } else if let Ok(v) = i16::try_from(v) {
+        v.to_le_bytes().to_vec()
+    } else {
+        v.to_le_bytes().to_vec()
+    }
+}
+
+/// Returns the smallest item data for a value that is interpreted as unsigned
+/// by the parser.
+fn unsigned_data(v: u32) -> Vec<u8> {
+    if let Ok(v) = u8::try_from(v) {
+        v.to_le_bytes().to_vec()
+    } else if let Ok(v) = u16::try_from(v) {
+        v.to_le_bytes().to_vec()
+    } else {
+        v.to_le_bytes().to_vec()
+    }
+}
+
+/// Compiles a [ReportDescriptor] back into a sequence of HID items.
+///
+/// Global items are only emitted where they differ from the current state,
+/// there is no way to unset a global item so where that's required we
+/// Pop back to the state we Pushed at the start.
+struct Serializer {
+    bytes: Vec<u8>,
+    globals: Globals,
+    collections: Vec<CollectionId>,
+}
+
+impl Serializer {
+    fn new() -> Self {
+        let mut s = Serializer {
+            bytes: vec![],
+            globals: Globals::default(),
+            collections: vec![],
+        };
+        s.item(0xa4, &[]); // Push
+        s
+    }
+
+    /// Append a short item, `prefix` is the tag and type (the lower two bits are the size).
+    fn item(&mut self, prefix: u8, data: &[u8]) {
+        let size = match data.len() {
+            0 => 0,
+            1 => 1,
+            2 => 2,
+            4 => 3,
+            n => panic!("Invalid item data length {n}"),
+        };
+        self.bytes.push(prefix | size);
+        self.bytes.extend_from_slice(data);
+    }
+
+    /// Restore the pristine globals
+    fn reset_globals(&mut self) {
+        self.item(0xb4, &[]); // Pop
+        self.item(0xa4, &[]); // Push
+        self.globals = Globals::default();
+    }
+
+    fn usage_page(&mut self, usage_page: UsagePage) {
+        if self.globals.usage_page != Some(usage_page) {
+            self.item(0x04, &unsigned_data(usage_page.0 as u32));
+            self.globals.usage_page = Some(usage_page);
+        }
+    }
+
+    fn usages(&mut self, usages: &[Usage]) {
+        let Some(first) = usages.first() else {
+            return;
+        };
+        if usages.iter().any(|u| u.usage_page != first.usage_page) {
+            // Mixed usage pages, use extended usages (Section 6.2.2.8)
+            for u in usages {
+                self.item(0x08, &u32::from(u).to_le_bytes());
+            }
+            return;
+        }
+
+        self.usage_page(first.usage_page);
+        let is_range = usages.len() > 1
+            && usages
+                .windows(2)
+                .all(|w| w[0].usage_id.0 as u32 + 1 == w[1].usage_id.0 as u32);
+        if is_range {
+            let last = usages.last().unwrap();
+            self.item(0x18, &unsigned_data(first.usage_id.0 as u32)); // Usage Minimum
+            self.item(0x28, &unsigned_data(last.usage_id.0 as u32)); // Usage Maximum
+        } else {
+            for u in usages {
+                self.item(0x08, &unsigned_data(u.usage_id.0 as u32));
+            }
+        }
+    }
+
+    /// Like [Serializer::usages] but a Usage Minimum/Maximum range is
+    /// written as-is without expanding it.
+    fn array_usages(&mut self, usages: Usages) {
+        match usages.as_slice() {
+            Some(usages) => self.usages(usages),
+            None => {
+                let (Some(first), Some(last)) = (usages.first(), usages.last()) else {
+                    return;
+                };
+                self.usage_page(first.usage_page);
+                self.item(0x18, &unsigned_data(first.usage_id.0 as u32)); // Usage Minimum
+                self.item(0x28, &unsigned_data(last.usage_id.0 as u32)); // Usage Maximum
+            }
+        }
+    }
+
+    /// Close and open collections until the currently open collections match
+    /// the given ones.
+    fn collections(&mut self, collections: &[Collection]) {
+        let common = self
+            .collections
+            .iter()
+            .zip(collections.iter())
+            .take_while(|(a, b)| *a == b.id())
+            .count();
+        while self.collections.len() > common {
+            self.item(0xc0, &[]); // End Collection
+            self.collections.pop();
+        }
+        for c in &collections[common..] {
+            self.usages(c.usages());
+            self.item(0xa0, &[u8::from(&c.collection_type())]);
+            self.collections.push(c.id().clone());
+        }
+    }
+
+    fn report_id(&mut self, report_id: Option<ReportId>) {
+        if report_id.is_none() && self.globals.report_id.is_some() {
+            self.reset_globals();
+        }
+        if let Some(id) = report_id {
+            if self.globals.report_id != Some(id) {
+                self.item(0x84, &[id.0]);
+                self.globals.report_id = Some(id);
+            }
+        }
+    }
+
+    fn report_size_and_count(&mut self, size: ReportSize, count: ReportCount) {
+        if self.globals.report_size != Some(size) {
+            self.item(0x74, &unsigned_data(size.0 as u32));
+            self.globals.report_size = Some(size);
+        }
+        if self.globals.report_count != Some(count) {
+            self.item(0x94, &unsigned_data(count.0 as u32));
+            self.globals.report_count = Some(count);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn values(
+        &mut self,
+        logical_minimum: LogicalMinimum,
+        logical_maximum: LogicalMaximum,
+        physical_minimum: Option<PhysicalMinimum>,
+        physical_maximum: Option<PhysicalMaximum>,
+        unit: Option<Unit>,
+        unit_exponent: Option<UnitExponent>,
+    ) {
+        if (physical_minimum.is_none() && self.globals.physical_minimum.is_some())
+            || (physical_maximum.is_none() && self.globals.physical_maximum.is_some())
+            || (unit.is_none() && self.globals.unit.is_some())
+            || (unit_exponent.is_none() && self.globals.unit_exponent.is_some())
+        {
+            let report_id = self.globals.report_id;
+            self.reset_globals();
+            self.report_id(report_id);
+        }
+
+        // The parser interprets the maximum as signed if the minimum is negative,
+        // so the minimum must always come first.
+        if self.globals.logical_minimum != Some(logical_minimum) {
+            self.item(0x14, &signed_data(logical_minimum.0));
+            self.globals.logical_minimum = Some(logical_minimum);
+        }
+        if self.globals.logical_maximum != Some(logical_maximum) {
+            let data = if logical_minimum.0 < 0 {
+                signed_data(logical_maximum.0)
+            } else {
+                unsigned_data(logical_maximum.0 as u32)
+            };
+            self.item(0x24, &data);
+            self.globals.logical_maximum = Some(logical_maximum);
+        }
+        if let Some(minimum) = physical_minimum {
+            if self.globals.physical_minimum != Some(minimum) {
+                self.item(0x34, &signed_data(minimum.0));
+                self.globals.physical_minimum = Some(minimum);
+            }
+        }
+        if let Some(maximum) = physical_maximum {
+            if self.globals.physical_maximum != Some(maximum) {
+                let negative_minimum = physical_minimum.is_some_and(|m| m.0 < 0);
+                let data = if negative_minimum {
+                    signed_data(maximum.0)
+                } else {
+                    unsigned_data(maximum.0 as u32)
+                };
+                self.item(0x44, &data);
+                self.globals.physical_maximum = Some(maximum);
+            }
+        }
+        if let Some(unit) = unit {
+            if self.globals.unit != Some(unit) {
+                self.item(0x64, &unsigned_data(unit.0));
+                self.globals.unit = Some(unit);
+            }
+        }
+        if let Some(exponent) = unit_exponent {
+            if self.globals.unit_exponent != Some(exponent) {
+                self.item(0x54, &unsigned_data(exponent.0));
+                self.globals.unit_exponent = Some(exponent);
+            }
+        }
+    }
+
+    /// Local items are reset after each main item so these
+    /// are emitted whenever they are set.
+    fn indices(
+        &mut self,
+        string_index: Option<StringIndex>,
+        designator_index: Option<DesignatorIndex>,
+    ) {
+        if let Some(index) = string_index {
+            self.item(0x78, &unsigned_data(index.0)); // String Index
+        }
+        if let Some(index) = designator_index {
+            self.item(0x38, &unsigned_data(index.0)); // Designator Index
+        }
+    }
+
+    fn field(&mut self, field: &Field, direction: Direction) {
+        let prefix = match direction {
+            Direction::Input => 0x80,
+            Direction::Output => 0x90,
+            Direction::Feature => 0xb0,
+        };
+        match field {
+            Field::Constant(f) => {
+                self.report_id(f.report_id);
+                // Padding is usually Report Size 1 with a Report Count of
+                // the number of bits, keep whatever the original had
+                let (size, count) = match (f.globals.report_size, f.globals.report_count) {
+                    (Some(size), Some(count)) if size.0 * count.0 == f.bits.len() => (size, count),
+                    _ => (ReportSize(f.bits.len()), ReportCount(1)),
+                };
+                self.report_size_and_count(size, count);
+                self.item(prefix, &[0x01]); // Cnst,Arr,Abs
+            }
+            Field::Variable(f) => {
+                self.collections(&f.collections);
+                self.report_id(f.report_id);
+                self.values(
+                    f.logical_minimum,
+                    f.logical_maximum,
+                    f.physical_minimum,
+                    f.physical_maximum,
+                    f.unit,
+                    f.unit_exponent,
+                );
+                self.report_size_and_count(ReportSize(f.bits.len()), ReportCount(1));
+                self.usages(core::slice::from_ref(&f.usage));
+                self.indices(f.string_index, f.designator_index);
+                self.item(prefix, &unsigned_data(0x02 | f.flags.bits())); // Data,Var
+            }
+            Field::Array(f) => {
+                self.collections(&f.collections);
+                self.report_id(f.report_id);
+                self.values(
+                    f.logical_minimum,
+                    f.logical_maximum,
+                    f.physical_minimum,
+                    f.physical_maximum,
+                    f.unit,
+                    f.unit_exponent,
+                );
+                self.report_size_and_count(ReportSize(f.element_bits()), f.report_count);
+                self.array_usages(f.usages());
+                self.indices(f.string_index, f.designator_index);
+                if let Some(range) = &f.string_range {
+                    self.item(0x88, &unsigned_data(range.start().0)); // String Minimum
+                    self.item(0x98, &unsigned_data(range.end().0)); // String Maximum
+                }
+                if let Some(range) = &f.designator_range {
+                    self.item(0x48, &unsigned_data(range.start().0)); // Designator Minimum
+                    self.item(0x58, &unsigned_data(range.end().0)); // Designator Maximum
+                }
+                self.item(prefix, &unsigned_data(f.flags.bits())); // Data,Arr
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.collections(&[]);
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Section B.1, Protocol 1 (Keyboard)
+    const BOOT_KEYBOARD: &[u8] = &[
+        0x05, 0x01, 0x09, 0x06, 0xa1, 0x01, 0x05, 0x07, 0x19, 0xe0, 0x29, 0xe7, 0x15, 0x00, 0x25,
+        0x01, 0x75, 0x01, 0x95, 0x08, 0x81, 0x02, 0x95, 0x01, 0x75, 0x08, 0x81, 0x01, 0x95, 0x05,
+        0x75, 0x01, 0x05, 0x08, 0x19, 0x01, 0x29, 0x05, 0x91, 0x02, 0x95, 0x01, 0x75, 0x03, 0x91,
+        0x01, 0x95, 0x06, 0x75, 0x08, 0x15, 0x00, 0x25, 0x65, 0x05, 0x07, 0x19, 0x00, 0x29, 0x65,
+        0x81, 0x00, 0xc0,
+    ];
+
+    // A mouse with Report ID 1 and a battery in Report ID 2 with
+    // a logical range of 0..=200
+    const BATTERY_MOUSE: &[u8] = &[
+        0x05, 0x01, 0x09, 0x02, 0xa1, 0x01, 0x85, 0x01, 0x09, 0x01, 0xa1, 0x00, 0x05, 0x09, 0x19,
+        0x01, 0x29, 0x03, 0x15, 0x00, 0x25, 0x01, 0x95, 0x03, 0x75, 0x01, 0x81, 0x02, 0x95, 0x01,
+        0x75, 0x05, 0x81, 0x01, 0x05, 0x01, 0x09, 0x30, 0x09, 0x31, 0x15, 0x81, 0x25, 0x7f, 0x75,
+        0x08, 0x95, 0x02, 0x81, 0x06, 0xc0, 0x85, 0x02, 0x05, 0x06, 0x09, 0x20, 0x15, 0x00, 0x25,
+        0xc8, 0x75, 0x08, 0x95, 0x01, 0x81, 0x02, 0xc0,
+    ];
+
+    // HID spec B.2 boot mouse with an extra wheel
+    const WHEEL_MOUSE: &[u8] = &[
+        0x05, 0x01, 0x09, 0x02, 0xa1, 0x01, 0x09, 0x01, 0xa1, 0x00, 0x05, 0x09, 0x19, 0x01, 0x29,
+        0x03, 0x15, 0x00, 0x25, 0x01, 0x95, 0x03, 0x75, 0x01, 0x81, 0x02, 0x95, 0x01, 0x75, 0x05,
+        0x81, 0x01, 0x05, 0x01, 0x09, 0x30, 0x09, 0x31, 0x09, 0x38, 0x15, 0x81, 0x25, 0x7f, 0x75,
+        0x08, 0x95, 0x03, 0x81, 0x06, 0xc0, 0xc0,
+    ];
+
+    // Keyboard (Report ID 1) and Consumer Control (Report ID 2)
+    const KEYBOARD_CONSUMER: &[u8] = &[
+        0x05, 0x01, 0x09, 0x06, 0xa1, 0x01, 0x85, 0x01, 0x05, 0x07, 0x19, 0xe0, 0x29, 0xe7, 0x15,
+        0x00, 0x25, 0x01, 0x75, 0x01, 0x95, 0x08, 0x81, 0x02, 0x95, 0x06, 0x75, 0x08, 0x15, 0x00,
+        0x25, 0x65, 0x19, 0x00, 0x29, 0x65, 0x81, 0x00, 0xc0, 0x05, 0x0c, 0x09, 0x01, 0xa1, 0x01,
+        0x85, 0x02, 0x15, 0x00, 0x26, 0xff, 0x03, 0x19, 0x00, 0x2a, 0xff, 0x03, 0x75, 0x10, 0x95,
+        0x01, 0x81, 0x00, 0xc0,
+    ];
+
+    /// Asserts that both report descriptors have the same reports and
+    /// fields, ignoring field and collection IDs.
+    fn assert_equivalent(a: &ReportDescriptor, b: &ReportDescriptor) {
+        fn assert_reports_equivalent(a: &[RDescReport], b: &[RDescReport]) {
+            assert_eq!(a.len(), b.len());
+            for (ra, rb) in a.iter().zip(b.iter()) {
+                assert_eq!(ra.report_id(), rb.report_id());
+                assert_eq!(ra.size_in_bits(), rb.size_in_bits());
+                assert_eq!(ra.fields().len(), rb.fields().len());
+                for (fa, fb) in ra.fields().iter().zip(rb.fields().iter()) {
+                    assert_eq!(fa.bits(), fb.bits());
+                    assert_eq!(fa.usages(), fb.usages());
+                    let ca: Vec<_> = fa
+                        .collections()
+                        .iter()
+                        .map(|c| (c.collection_type(), c.usages()))
+                        .collect();
+                    let cb: Vec<_> = fb
+                        .collections()
+                        .iter()
+                        .map(|c| (c.collection_type(), c.usages()))
+                        .collect();
+                    assert_eq!(ca, cb);
+                    match (fa, fb) {
+                        (Field::Variable(va), Field::Variable(vb)) => {
+                            assert_eq!(va.logical_minimum, vb.logical_minimum);
+                            assert_eq!(va.logical_maximum, vb.logical_maximum);
+                            assert_eq!(va.physical_minimum, vb.physical_minimum);
+                            assert_eq!(va.physical_maximum, vb.physical_maximum);
+                            assert_eq!(va.unit, vb.unit);
+                            assert_eq!(va.unit_exponent, vb.unit_exponent);
+                            assert_eq!(va.string_index, vb.string_index);
+                            assert_eq!(va.designator_index, vb.designator_index);
+                            assert_eq!(va.flags, vb.flags);
+                        }
+                        (Field::Array(aa), Field::Array(ab)) => {
+                            assert_eq!(aa.report_count, ab.report_count);
+                            assert_eq!(aa.logical_minimum, ab.logical_minimum);
+                            assert_eq!(aa.logical_maximum, ab.logical_maximum);
+                            assert_eq!(aa.designator_range, ab.designator_range);
+                        }
+                        (Field::Constant(_), Field::Constant(_)) => {}
+                        _ => panic!("Mismatching field types"),
+                    }
+                }
+            }
+        }
+        assert_reports_equivalent(&a.input_reports, &b.input_reports);
+        assert_reports_equivalent(&a.output_reports, &b.output_reports);
+        assert_reports_equivalent(&a.feature_reports, &b.feature_reports);
+    }
+
+    fn usage(page: u16, id: u16) -> Usage {
+        Usage::from_page_and_id(UsagePage::from(page), UsageId::from(id))
+    }
+
+    #[test]
+    fn fields_where() {
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+
+        // 8 modifier bits plus the key array
+        let fields = rdesc.fields_where(|u| u.usage_page == UsagePage::from(0x07));
+        assert_eq!(fields.len(), 9);
+        assert!(matches!(fields.last().unwrap(), Field::Array(_)));
+
+        // The array field only matches once even though all its usages match
+        let fields = rdesc.fields_where(|u| {
+            u.usage_page == UsagePage::from(0x07) && u16::from(u.usage_id) < 0xe0
+        });
+        assert_eq!(fields.len(), 1);
+        assert!(matches!(fields.first().unwrap(), Field::Array(_)));
+
+        // The LEDs are in the output report
+        let fields = rdesc.fields_where(|u| u.usage_page == UsagePage::from(0x08));
+        assert_eq!(fields.len(), 5);
+        let Field::Variable(numlock) = fields.first().unwrap() else {
+            panic!("Expected a variable field");
+        };
+        assert_eq!(numlock.usage, usage(0x08, 0x01));
+
+        let fields = rdesc.fields_where(|u| u.usage_page == UsagePage::from(0x0D));
+        assert!(fields.is_empty());
+    }
+
+    #[test]
+    fn test_twos_comp() {
+        assert_eq!(5u8.twos_comp(3), -3);
+        assert_eq!(5u8.twos_comp(4), 5);
+        assert_eq!(0xffu8.twos_comp(8), -1);
+
+        assert_eq!(5u16.twos_comp(3), -3);
+        assert_eq!(5u16.twos_comp(4), 5);
+        assert_eq!(0xffffu16.twos_comp(16), -1);
+
+        assert_eq!(5u32.twos_comp(3), -3);
+        assert_eq!(5u32.twos_comp(4), 5);
+        assert_eq!(0xffffffffu32.twos_comp(32), -1);
+    }
+
+    #[test]
+    fn extract() {
+        let bytes: [u8; 4] = [0b1100_1010, 0b1011_1001, 0b10010110, 0b00010101];
+
+        assert_eq!(0, extract_u8(&bytes, &(0..1)));
+        assert_eq!(2, extract_u8(&bytes, &(0..2)));
+        assert_eq!(10, extract_u8(&bytes, &(0..4)));
+
+        assert_eq!(0, extract_i8(&bytes, &(0..1)));
+        assert_eq!(-2, extract_i8(&bytes, &(0..2)));
+        assert_eq!(-6, extract_i8(&bytes, &(0..4)));
+
+        // byte-aligned ranges must not access past the last byte
+        assert_eq!(0b1011_1001, extract_u8(&bytes[..2], &(8..16)));
+        assert_eq!(0b1011_1001_1100_1010, extract_u16(&bytes[..2], &(0..16)));
+
+        assert_eq!(0b1001_1100, extract_u8(&bytes, &(4..12)));
+        assert_eq!(0b1001_1100u8 as i8, extract_i8(&bytes, &(4..12)));
+
+        assert_eq!(0, extract_u16(&bytes, &(0..1)));
+        assert_eq!(2, extract_u16(&bytes, &(0..2)));
+        assert_eq!(10, extract_u16(&bytes, &(0..4)));
+
+        assert_eq!(0, extract_i16(&bytes, &(0..1)));
+        assert_eq!(-2, extract_i16(&bytes, &(0..2)));
+        assert_eq!(-6, extract_i16(&bytes, &(0..4)));
+
+        assert_eq!(0b0110_1011_1001_1100, extract_u16(&bytes, &(4..20)));
+        assert_eq!(0b0110_1011_1001_1100, extract_i16(&bytes, &(4..20)));
+        assert_eq!(
+            0b1011_0101_1100_1110u16 as i16,
+            extract_i16(&bytes, &(5..21))
+        );
+
+        assert_eq!(0b0110_1011_1001_1100, extract_u32(&bytes, &(4..20)));
+        assert_eq!(0b0110_1011_1001_1100, extract_i32(&bytes, &(4..20)));
+        assert_eq!(
+            ((0b1011_0101_1100_1110u16 as i16) as i32),
+            extract_i32(&bytes, &(5..21))
+        );
+
+        assert_eq!(
+            ((0b1011_0101_1100_1110u16 as i16) as i32),
+            extract_i32(&bytes, &(5..21))
+        );
+    }
+
+    #[test]
+    fn battery_percentage() {
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        let field = rdesc.battery_strength_field().unwrap();
+        assert_eq!(field.bits, 8..16);
+
+        assert_eq!(rdesc.battery_percentage(&[0x02, 0]), Some(0));
+        assert_eq!(rdesc.battery_percentage(&[0x02, 100]), Some(50));
+        assert_eq!(rdesc.battery_percentage(&[0x02, 200]), Some(100));
+        // out-of-range values are clamped
+        assert_eq!(rdesc.battery_percentage(&[0x02, 250]), Some(100));
+        // The mouse report does not have the battery
+        assert_eq!(rdesc.battery_percentage(&[0x01, 0x01, 0x02, 0x03]), None);
+        // Short reads
+        assert_eq!(rdesc.battery_percentage(&[0x02]), None);
+        assert_eq!(rdesc.battery_percentage(&[]), None);
+
+        // A second battery in another report
+        let mut bytes = BATTERY_MOUSE.to_vec();
+        bytes.extend_from_slice(&[
+            0x05, 0x06, // Usage Page (Generic Device Controls)
+            0x09, 0x20, // Usage (Battery Strength)
+            0xa1, 0x01, // Collection (Application)
+            0x85, 0x03, //  Report ID (3)
+            0x09, 0x20, //  Usage (Battery Strength)
+            0x15, 0x00, //  Logical Minimum (0)
+            0x25, 0x64, //  Logical Maximum (100)
+            0x75, 0x08, //  Report Size (8)
+            0x95, 0x01, //  Report Count (1)
+            0x81, 0x02, //  Input (Data,Var,Abs)
+            0xc0, //       End Collection
+        ]);
+        let rdesc = ReportDescriptor::try_from(&bytes).unwrap();
+        assert_eq!(
+            rdesc.battery_strength_field().unwrap().report_id,
+            Some(ReportId(2))
+        );
+        assert_eq!(rdesc.battery_percentage(&[0x02, 100]), Some(50));
+        assert_eq!(rdesc.battery_percentage(&[0x03, 25]), Some(25));
+
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        assert!(rdesc.battery_strength_field().is_none());
+        assert_eq!(rdesc.battery_percentage(&[0; 8]), None);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn from_sysfs() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/0003:045E:0040.0004.hid.bin"
+        );
+        let rdesc = ReportDescriptor::from_sysfs(path).unwrap();
+        assert_eq!(rdesc.input_reports().len(), 1);
+        assert_eq!(rdesc.feature_reports().len(), 1);
+
+        let err = ReportDescriptor::from_sysfs("/does/not/exist").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+        // An empty file is not a valid report descriptor
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/0003:5543:004D.000F.hid.bin"
+        );
+        let err = ReportDescriptor::from_sysfs(path).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn to_bytes() {
+        let wacom = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/0003:056A:00E3.0002.hid.bin"
+        ))
+        .unwrap();
+        for bytes in [BOOT_KEYBOARD, BATTERY_MOUSE, &wacom] {
+            let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+            let rdesc2 = ReportDescriptor::try_from(&rdesc.to_bytes()).unwrap();
+            assert_equivalent(&rdesc, &rdesc2);
+        }
+
+        assert_eq!(signed_data(-1), vec![0xff]);
+        assert_eq!(signed_data(128), vec![0x80, 0x00]);
+        assert_eq!(signed_data(-32769), vec![0xff, 0x7f, 0xff, 0xff]);
+        assert_eq!(unsigned_data(255), vec![0xff]);
+        assert_eq!(unsigned_data(256), vec![0x00, 0x01]);
+        assert_eq!(unsigned_data(0x10000), vec![0x00, 0x00, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn output_report_for_usage() {
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        let report = rdesc.output_report_for_usage(usage(0x08, 0x02)).unwrap();
+        assert_eq!(report.size_in_bits(), 8);
+        assert!(report.report_id().is_none());
+
+        // Keys are input only
+        assert!(rdesc.output_report_for_usage(usage(0x07, 0x04)).is_none());
+
+        // The feature report of the Microsoft mouse
+        let bytes = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/0003:045E:0040.0004.hid.bin"
+        ))
+        .unwrap();
+        let rdesc = ReportDescriptor::try_from(&bytes).unwrap();
+        let report = rdesc.output_report_for_usage(usage(0x00ff, 0x02)).unwrap();
+        assert_eq!(report.size_in_bits(), 8);
+
+        // A padding field with a usage cannot be set
+        let mut rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        let Field::Constant(padding) = &mut rdesc.output_reports[0].fields[5] else {
+            panic!("Expected a constant field");
+        };
+        padding.usages = vec![usage(0x08, 0x06)];
+        assert!(rdesc.output_report_for_usage(usage(0x08, 0x06)).is_none());
+        assert!(rdesc.output_report_for_usage(usage(0x08, 0x05)).is_some());
+    }
+
+    #[test]
+    fn duplicate_report_ids() {
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        assert!(!rdesc.has_duplicate_report_ids());
+        assert!(rdesc.warnings().is_empty());
+
+        let report = |id: u8| RDescReport {
+            id: Some(ReportId(id)),
+            size: 8,
+            fields: vec![],
+            ..Default::default()
+        };
+        let rdesc = ReportDescriptor {
+            input_reports: vec![report(1), report(2)],
+            output_reports: vec![report(1)],
+            ..Default::default()
+        };
+        assert!(!rdesc.has_duplicate_report_ids());
+
+        let rdesc = ReportDescriptor {
+            feature_reports: vec![report(1), report(2), report(1)],
+            ..Default::default()
+        };
+        assert!(rdesc.has_duplicate_report_ids());
+        assert_eq!(
+            super::duplicate_report_ids(&rdesc.feature_reports),
+            vec![Some(ReportId(1))]
+        );
+    }
+
+    #[test]
+    fn decode_input() {
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        // Trailing byte is ignored
+        let (report_id, values) = rdesc
+            .decode_input(&[0x01, 0b101, 0xff, 0x05, 0xaa])
+            .unwrap();
+        assert_eq!(report_id, Some(ReportId(1)));
+        assert_eq!(
+            values,
+            vec![
+                (usage(0x09, 0x01), 1),
+                (usage(0x09, 0x02), 0),
+                (usage(0x09, 0x03), 1),
+                (usage(0x01, 0x30), -1),
+                (usage(0x01, 0x31), 5),
+            ]
+        );
+
+        let (report_id, values) = rdesc.decode_input(&[0x02, 0xc8]).unwrap();
+        assert_eq!(report_id, Some(ReportId(2)));
+        assert_eq!(values, vec![(usage(0x06, 0x20), 200)]);
+
+        assert!(matches!(
+            rdesc.decode_input(&[0x03, 0x00]),
+            Err(ParserError::MismatchingReportId)
+        ));
+        assert!(matches!(
+            rdesc.decode_input(&[0x01, 0x00]),
+            Err(ParserError::OutOfBounds)
+        ));
+        assert!(matches!(
+            rdesc.decode_input(&[]),
+            Err(ParserError::OutOfBounds)
+        ));
+
+        // Left Shift and the 'a' key
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        let (report_id, values) = rdesc
+            .decode_input(&[0b10, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00])
+            .unwrap();
+        assert_eq!(report_id, None);
+        assert_eq!(values[0], (usage(0x07, 0xe0), 0));
+        assert_eq!(values[1], (usage(0x07, 0xe1), 1));
+        assert_eq!(values[8], (usage(0x07, 0x04), 1));
+        // The unused key slots are not reported
+        assert_eq!(values.len(), 9);
+
+        // An idle keyboard only reports the modifiers
+        let values = rdesc.input_reports()[0].extract_all(&[0; 8]).unwrap();
+        assert_eq!(values.len(), 8);
+        assert!(!values.contains_key(&usage(0x07, 0x00)));
+    }
+
+    #[test]
+    fn application_usages() {
+        let rdesc = ReportDescriptor::try_from(KEYBOARD_CONSUMER).unwrap();
+        assert_eq!(
+            rdesc.application_usages(),
+            vec![usage(0x01, 0x06), usage(0x0c, 0x01)]
+        );
+
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        let field = rdesc
+            .fields_where(|u| *u == usage(0x01, 0x30))
+            .into_iter()
+            .find_map(|f| match f {
+                Field::Variable(v) => Some(v),
+                _ => None,
+            })
+            .unwrap();
+        let path: Vec<(CollectionType, &[Usage])> = field
+            .collection_path()
+            .iter()
+            .map(|c| (c.collection_type(), c.usages()))
+            .collect();
+        assert_eq!(
+            path,
+            vec![
+                (CollectionType::Application, &[usage(0x01, 0x02)][..]),
+                (CollectionType::Physical, &[usage(0x01, 0x01)][..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn absolute_bits() {
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        let report = rdesc.input_reports().first().unwrap();
+        assert_eq!(report.report_id(), &Some(ReportId(1)));
+        let Field::Variable(button) = report.fields().first().unwrap() else {
+            panic!("Expected a variable field");
+        };
+        assert_eq!(button.absolute_bits(), Some(8..=8));
+        assert_eq!(button.bits, 8..9);
+
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        let report = rdesc.input_reports().first().unwrap();
+        let Field::Variable(modifier) = report.fields().first().unwrap() else {
+            panic!("Expected a variable field");
+        };
+        assert_eq!(modifier.absolute_bits(), Some(0..=0));
+
+        let empty = VariableField::new(
+            usage(0x01, 0x30),
+            8..8,
+            LogicalMinimum(0),
+            LogicalMaximum(1),
+        );
+        assert_eq!(empty.absolute_bits(), None);
+    }
+
+    #[test]
+    fn validate() {
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        for report in rdesc.input_reports() {
+            report.validate().unwrap();
+        }
+
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        let report = rdesc.input_reports().first().unwrap();
+        report.validate().unwrap();
+
+        // Move the reserved byte into the last modifier bit
+        let mut fields = report.fields().to_vec();
+        let Field::Constant(reserved) = &mut fields[8] else {
+            panic!("Expected the reserved byte");
+        };
+        reserved.bits = 7..15;
+        let overlapping = RDescReport {
+            id: None,
+            size: report.size_in_bits(),
+            fields,
+            ..Default::default()
+        };
+        match overlapping.validate() {
+            Err(ParserError::InvalidReport { message }) => {
+                assert_eq!(message, "field at bits 7..15 overlaps field at bits 7..8")
             }
-            ItemType::Main(MainItem::EndCollection) => {
-                if stack.collections.pop().is_none() {
-                    return Err(ParserError::InvalidData {
-                        offset: rdesc_item.offset(),
-                        message: "Too many EndCollection".into(),
-                    });
-                };
-                stack.reset_locals();
+            r => panic!("Unexpected result {r:?}"),
+        }
+
+        let short = RDescReport {
+            id: None,
+            size: report.size_in_bits() + 8,
+            fields: report.fields().to_vec(),
+            ..Default::default()
+        };
+        assert!(short.validate().is_err());
+    }
+
+    #[test]
+    fn split_by_application() {
+        let rdesc = ReportDescriptor::try_from(KEYBOARD_CONSUMER).unwrap();
+        let split = rdesc.split_by_application();
+        assert_eq!(split.len(), 2);
+
+        let keyboard = &split[0];
+        assert_eq!(keyboard.application_usages(), vec![usage(0x01, 0x06)]);
+        assert_eq!(keyboard.input_reports().len(), 1);
+        assert_eq!(keyboard.input_reports()[0].report_id(), &Some(ReportId(1)));
+
+        let consumer = &split[1];
+        assert_eq!(consumer.application_usages(), vec![usage(0x0c, 0x01)]);
+        assert_eq!(consumer.input_reports().len(), 1);
+        assert_eq!(consumer.input_reports()[0].report_id(), &Some(ReportId(2)));
+
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        let split = rdesc.split_by_application();
+        assert_eq!(split.len(), 1);
+        assert_eq!(split[0].input_reports().len(), 1);
+        assert_eq!(split[0].output_reports().len(), 1);
+    }
+
+    #[test]
+    fn report_builder() {
+        let report = ReportBuilder::new(Some(ReportId(3)))
+            .add_variable(usage(0x01, 0x30), 12, -2047..=2047)
+            .add_constant(4)
+            .build();
+        assert_eq!(report.report_id(), &Some(ReportId(3)));
+        assert_eq!(report.size_in_bits(), 24);
+        assert_eq!(report.size_in_bytes(), 3);
+        report.validate().unwrap();
+
+        let Field::Variable(x) = &report.fields()[0] else {
+            panic!("Expected a variable field");
+        };
+        assert_eq!(x.bits, 8..20);
+        assert_eq!(x.extract(&[3, 0xff, 0x07]).unwrap(), 2047);
+        assert_eq!(x.extract(&[3, 0x01, 0x08]).unwrap(), -2047);
+        assert!(matches!(
+            x.extract(&[2, 0x00, 0x00]),
+            Err(ParserError::MismatchingReportId)
+        ));
+
+        let report = ReportBuilder::new(None)
+            .add_variable(usage(0x09, 0x01), 1, 0..=1)
+            .build();
+        assert_eq!(report.size_in_bits(), 1);
+        assert_eq!(report.size_in_bytes(), 1);
+    }
+
+    #[test]
+    fn pop_underflow() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0xa4, // Push
+            0xb4, // Pop
+            0xb4, // Pop
+            0x09, 0x02, // Usage (Mouse)
+        ];
+        match ReportDescriptor::try_from(bytes) {
+            Err(ParserError::InvalidData { offset, message }) => {
+                assert_eq!(offset, 4);
+                assert_eq!(message, "Pop without matching Push");
             }
-            ItemType::Main(item) => {
-                let mut fields =
-                    match handle_main_item(&item, &mut stack, (rdesc_item.offset() * 8) as u32) {
-                        Ok(fields) => fields,
-                        Err(ParserError::InvalidData { message, .. }) => {
-                            return Err(ParserError::InvalidData {
-                                offset: rdesc_item.offset(),
-                                message,
-                            })
-                        }
-                        Err(e) => return Err(e),
-                    };
-                stack.reset_locals();
+            r => panic!("Unexpected result {r:?}"),
+        }
+
+        assert!(ReportDescriptor::try_from(&[0xb4u8][..]).is_err());
+    }
+
+    #[test]
+    fn locals_with_push_pop() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x02, // Usage (Mouse)
+            0xa1, 0x01, // Collection (Application)
+            0x15, 0x81, // Logical Minimum (-127)
+            0x25, 0x7f, // Logical Maximum (127)
+            0x75, 0x08, // Report Size (8)
+            0x95, 0x01, // Report Count (1)
+            0x09, 0x30, // Usage (X)
+            0xa4, // Push
+            0x09, 0x31, // Usage (Y)
+            0x95, 0x02, // Report Count (2)
+            0x81, 0x06, // Input (Data,Var,Rel)
+            0xb4, // Pop
+            0x09, 0x38, // Usage (Wheel)
+            0x81, 0x06, // Input (Data,Var,Rel)
+            0xc0, // End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        let report = rdesc.input_reports().first().unwrap();
+        let usages: Vec<Usage> = report
+            .fields()
+            .iter()
+            .flat_map(|f| f.usages().to_vec())
+            .collect();
+        // The Usage (X) before the Push is used by the first main item
+        // and does not reappear after the Pop
+        assert_eq!(
+            usages,
+            vec![usage(0x01, 0x30), usage(0x01, 0x31), usage(0x01, 0x38)]
+        );
+        assert_eq!(report.size_in_bits(), 24);
+    }
+
+    #[test]
+    fn report_ids() {
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        assert_eq!(rdesc.input_report_ids(), vec![ReportId(1), ReportId(2)]);
+        assert!(rdesc.output_report_ids().is_empty());
+        assert!(rdesc.feature_report_ids().is_empty());
+
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        assert!(rdesc.input_report_ids().is_empty());
+        assert!(rdesc.output_report_ids().is_empty());
+
+        let report = |id: u8| RDescReport {
+            id: Some(ReportId(id)),
+            size: 8,
+            fields: vec![],
+            ..Default::default()
+        };
+        let rdesc = ReportDescriptor {
+            feature_reports: vec![report(3), report(1), report(3)],
+            ..Default::default()
+        };
+        assert_eq!(rdesc.feature_report_ids(), vec![ReportId(1), ReportId(3)]);
+    }
+
+    #[test]
+    fn max_report_size() {
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        assert_eq!(rdesc.max_input_report_size(), 4);
+        assert_eq!(rdesc.max_output_report_size(), 0);
+        assert_eq!(rdesc.max_feature_report_size(), 0);
+
+        let report = |id: u8, size: usize| RDescReport {
+            id: Some(ReportId(id)),
+            size,
+            fields: vec![],
+            ..Default::default()
+        };
+        let rdesc = ReportDescriptor {
+            input_reports: vec![report(1, 24), report(2, 72)],
+            output_reports: vec![report(1, 20)],
+            ..Default::default()
+        };
+        assert_eq!(rdesc.max_input_report_size(), 9);
+        assert_eq!(rdesc.max_output_report_size(), 3);
+        assert_eq!(rdesc.max_feature_report_size(), 0);
+    }
+
+    #[test]
+    fn interleaved_usage_pages() {
+        let bytes: &[u8] = &[
+            0x05, 0x0c, // Usage Page (Consumer)
+            0x09, 0x01, // Usage (Consumer Control)
+            0xa1, 0x01, // Collection (Application)
+            0x05, 0x07, // Usage Page (Keyboard/Keypad)
+            0x09, 0x04, // Usage (a)
+            0x05, 0x0c, // Usage Page (Consumer)
+            0x09, 0xe9, // Usage (Volume Up)
+            0x05, 0x07, // Usage Page (Keyboard/Keypad)
+            0x09, 0x05, // Usage (b)
+            0x15, 0x00, // Logical Minimum (0)
+            0x25, 0x02, // Logical Maximum (2)
+            0x75, 0x08, // Report Size (8)
+            0x95, 0x03, // Report Count (3)
+            0x81, 0x00, // Input (Data,Arr,Abs)
+            0x05, 0x08, // Usage Page (LED)
+            0x09, 0x01, // Usage (Num Lock)
+            0x05, 0x09, // Usage Page (Button)
+            0x75, 0x01, // Report Size (1)
+            0x95, 0x01, // Report Count (1)
+            0x25, 0x01, // Logical Maximum (1)
+            0x81, 0x02, // Input (Data,Var,Abs)
+            0x95, 0x07, // Report Count (7)
+            0x81, 0x01, // Input (Cnst,Arr,Abs)
+            0xc0, // End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        let report = rdesc.input_reports().first().unwrap();
+        let Field::Array(array) = &report.fields()[0] else {
+            panic!("Expected an array field");
+        };
+        assert_eq!(usize::from(array.report_count), 3);
+        assert_eq!(
+            array.usages().to_vec(),
+            [usage(0x07, 0x04), usage(0x0c, 0xe9), usage(0x07, 0x05)]
+        );
+
+        // A Usage Page after the Usage applies to that Usage
+        let Field::Variable(button) = &report.fields()[1] else {
+            panic!("Expected a variable field");
+        };
+        assert_eq!(button.usage, usage(0x09, 0x01));
+    }
+
+    #[test]
+    fn as_button() {
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        let report = rdesc.input_reports().first().unwrap();
+        let buttons: Vec<u16> = report
+            .fields()
+            .iter()
+            .flat_map(|f| f.usages())
+            .filter_map(|u| u.as_button())
+            .collect();
+        assert_eq!(buttons, vec![1, 2, 3]);
+
+        assert_eq!(usage(0x01, 0x30).as_button(), None);
+    }
+
+    #[test]
+    fn usage_hash() {
+        use std::collections::HashSet;
+
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        let usages: HashSet<Usage> = rdesc
+            .input_reports()
+            .iter()
+            .flat_map(|r| r.fields())
+            .flat_map(|f| f.usages().to_vec())
+            .collect();
+        assert!(usages.contains(&usage(0x01, 0x30)));
+        assert!(usages.contains(&usage(0x06, 0x20)));
+        // Same id, different page
+        assert!(!usages.contains(&usage(0x09, 0x30)));
+        assert!(!usages.contains(&usage(0x01, 0x20)));
+
+        let pages: HashSet<UsagePage> = usages.iter().map(|u| u.usage_page).collect();
+        assert_eq!(pages.len(), 3);
+        let ids: HashSet<UsageId> = usages.iter().map(|u| u.usage_id).collect();
+        assert!(ids.contains(&UsageId(0x31)));
+
+        let collections: HashSet<&Collection> = rdesc
+            .input_reports()
+            .iter()
+            .flat_map(|r| r.fields())
+            .flat_map(|f| f.collections())
+            .collect();
+        // Application and Physical of the mouse
+        assert_eq!(collections.len(), 2);
+    }
+
+    #[test]
+    fn fields_for_usage() {
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        let fields = rdesc.fields_for_usage(usage(0x01, 0x30));
+        assert_eq!(fields.len(), 1);
+        let Field::Variable(x) = fields[0] else {
+            panic!("Expected a variable field");
+        };
+        assert_eq!(x.usage, usage(0x01, 0x30));
+        assert_eq!(x.bits, 16..24);
+
+        assert!(rdesc.fields_for_usage(usage(0x01, 0x32)).is_empty());
+
+        // Key 'a' is one of the keyboard's array usages
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        let fields = rdesc.fields_for_usage(usage(0x07, 0x04));
+        assert_eq!(fields.len(), 1);
+        assert!(matches!(fields[0], Field::Array(_)));
+    }
+
+    #[test]
+    fn value_for_usage() {
+        let rdesc = ReportDescriptor::try_from(WHEEL_MOUSE).unwrap();
+        let wheel = usage(0x01, 0x38);
+        // Button 1, x = 1, y = -1, wheel = -2
+        let bytes = [0x01, 0x01, 0xff, 0xfe];
+        assert_eq!(rdesc.value_for_usage(wheel, &bytes).unwrap(), Some(-2));
+        assert_eq!(
+            rdesc.value_for_usage(usage(0x01, 0x30), &bytes).unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            rdesc.value_for_usage(usage(0x09, 0x01), &bytes).unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            rdesc.value_for_usage(usage(0x01, 0x32), &bytes).unwrap(),
+            None
+        );
+        assert!(matches!(
+            rdesc.value_for_usage(wheel, &bytes[..3]),
+            Err(ParserError::OutOfBounds)
+        ));
+
+        // Battery Strength is in report 2, not report 1
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        let battery = usage(0x06, 0x20);
+        assert_eq!(
+            rdesc
+                .value_for_usage(battery, &[0x01, 0x00, 0x00, 0x00])
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            rdesc.value_for_usage(battery, &[0x02, 0x64]).unwrap(),
+            Some(100)
+        );
+        assert!(matches!(
+            rdesc.value_for_usage(battery, &[0x03, 0x64]),
+            Err(ParserError::MismatchingReportId)
+        ));
+    }
+
+    #[test]
+    fn string_and_designator_index() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x05, // Usage (Gamepad)
+            0xa1, 0x01, // Collection (Application)
+            0x05, 0x09, // Usage Page (Button)
+            0x09, 0x01, // Usage (Button 1)
+            0x79, 0x04, // String Index (4)
+            0x39, 0x02, // Designator Index (2)
+            0x15, 0x00, // Logical Minimum (0)
+            0x25, 0x01, // Logical Maximum (1)
+            0x75, 0x01, // Report Size (1)
+            0x95, 0x01, // Report Count (1)
+            0x81, 0x02, // Input (Data,Var,Abs)
+            0x09, 0x02, // Usage (Button 2)
+            0x81, 0x02, // Input (Data,Var,Abs)
+            0x95, 0x06, // Report Count (6)
+            0x81, 0x01, // Input (Cnst,Arr,Abs)
+            0xc0, // End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        let report = rdesc.input_reports().first().unwrap();
+        let Field::Variable(button1) = &report.fields()[0] else {
+            panic!("Expected a variable field");
+        };
+        assert_eq!(button1.string_index(), Some(StringIndex(4)));
+        assert_eq!(button1.designator(), Some(DesignatorIndex(2)));
+
+        // Local items do not carry over to the next main item
+        let Field::Variable(button2) = &report.fields()[1] else {
+            panic!("Expected a variable field");
+        };
+        assert_eq!(button2.string_index(), None);
+        assert_eq!(button2.designator(), None);
+
+        let copy = ReportDescriptor::try_from(&rdesc.to_bytes()).unwrap();
+        assert_equivalent(&rdesc, &copy);
+    }
+
+    #[test]
+    fn display() {
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        let expected = "\
+Input Report (ID 1), 32 bits
+  bits  8..9    Variable Button/Button 1, logical 0..=1
+  bits  9..10   Variable Button/Button 2, logical 0..=1
+  bits 10..11   Variable Button/Button 3, logical 0..=1
+  bits 11..16   Constant
+  bits 16..24   Variable Generic Desktop/X, logical -127..=127
+  bits 24..32   Variable Generic Desktop/Y, logical -127..=127
+Input Report (ID 2), 16 bits
+  bits  8..16   Variable Generic Device Controls/Battery Strength, logical 0..=200
+";
+        assert_eq!(format!("{rdesc}"), expected);
+
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        let output = format!("{rdesc}");
+        assert!(output.contains(
+            "  bits 16..64   Array[6] Keyboard/Keypad/0x0000..=Keyboard/Keypad/0x0065, logical 0..=101\n"
+        ));
+        assert!(output.contains("Output Report, 8 bits\n"));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn from_reader() {
+        let rdesc = ReportDescriptor::from_reader(std::io::Cursor::new(BATTERY_MOUSE)).unwrap();
+        let expected = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        assert_equivalent(&rdesc, &expected);
+
+        struct FailingReader;
+        impl std::io::Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("broken pipe"))
+            }
+        }
+        match ReportDescriptor::from_reader(FailingReader) {
+            Err(ParserError::Io(e)) => assert_eq!(e.to_string(), "broken pipe"),
+            r => panic!("Unexpected result {r:?}"),
+        }
+    }
+
+    #[test]
+    fn clamp() {
+        let report = ReportBuilder::new(None)
+            .add_variable(usage(0x01, 0x30), 8, 0..=255)
+            .add_variable(usage(0x01, 0x31), 8, -127..=127)
+            .build();
+        let Field::Variable(x) = &report.fields()[0] else {
+            panic!("Expected a variable field");
+        };
+        assert_eq!(x.clamp(500), 255);
+        assert_eq!(x.clamp(-1), 0);
+        assert_eq!(x.clamp(100), 100);
+        assert!(x.is_in_range(0));
+        assert!(x.is_in_range(255));
+        assert!(!x.is_in_range(256));
+        assert!(!x.is_in_range(-1));
+
+        let Field::Variable(y) = &report.fields()[1] else {
+            panic!("Expected a variable field");
+        };
+        assert_eq!(y.clamp(-500), -127);
+        assert_eq!(y.clamp(500), 127);
+        assert!(y.is_in_range(-127));
+        assert!(!y.is_in_range(-128));
+    }
+
+    #[test]
+    fn resolution() {
+        // 20cm wide touchscreen with 4096 steps
+        let mut x = VariableField::new(
+            usage(0x01, 0x30),
+            0..16,
+            LogicalMinimum(0),
+            LogicalMaximum(4096),
+        );
+        assert_eq!(x.resolution(), None);
+
+        x.physical_minimum = Some(PhysicalMinimum(0));
+        x.physical_maximum = Some(PhysicalMaximum(2000));
+        assert_eq!(x.resolution(), None);
+
+        x.unit = Some(Unit(0x11)); // cm
+        x.unit_exponent = Some(UnitExponent(0x0e)); // -2
+        assert_eq!(x.resolution(), Some(204.8));
+
+        x.physical_maximum = Some(PhysicalMaximum(0));
+        assert_eq!(x.resolution(), None);
+
+        // The Wacom touchscreen in our test data
+        let bytes = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/0003:056A:00E3.0002.hid.bin"
+        ))
+        .unwrap();
+        let rdesc = ReportDescriptor::try_from(&bytes).unwrap();
+        let Field::Variable(x) = rdesc.fields_for_usage(usage(0x01, 0x30))[0] else {
+            panic!("Expected a variable field");
+        };
+        // 2631 counts across 26.312cm
+        let res = x.resolution().unwrap();
+        assert!((res - 2631.0 / 26.312).abs() < 1e-9);
+    }
+
+    #[test]
+    fn null_state() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x05, // Usage (Gamepad)
+            0xa1, 0x01, // Collection (Application)
+            0x09, 0x39, // Usage (Hat Switch)
+            0x15, 0x00, // Logical Minimum (0)
+            0x25, 0x07, // Logical Maximum (7)
+            0x75, 0x04, // Report Size (4)
+            0x95, 0x01, // Report Count (1)
+            0x81, 0x42, // Input (Data,Var,Abs,Null)
+            0x09, 0x30, // Usage (X)
+            0x81, 0x02, // Input (Data,Var,Abs)
+            0xc0, // End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        let Field::Variable(hat) = rdesc.fields_for_usage(usage(0x01, 0x39))[0] else {
+            panic!("Expected a variable field");
+        };
+        assert!(hat.has_null_state());
+        assert!(hat.is_null(8));
+        assert!(!hat.is_null(7));
+        assert!(!hat.is_null(0));
+        assert!(hat.is_null(hat.extract(&[0x08]).unwrap()));
+
+        let Field::Variable(x) = rdesc.fields_for_usage(usage(0x01, 0x30))[0] else {
+            panic!("Expected a variable field");
+        };
+        assert!(!x.has_null_state());
+        assert!(!x.is_null(8));
+
+        let copy = ReportDescriptor::try_from(&rdesc.to_bytes()).unwrap();
+        assert_equivalent(&rdesc, &copy);
+    }
+
+    #[test]
+    fn is_relative() {
+        let rdesc = ReportDescriptor::try_from(WHEEL_MOUSE).unwrap();
+        let Field::Variable(x) = rdesc.fields_for_usage(usage(0x01, 0x30))[0] else {
+            panic!("Expected a variable field");
+        };
+        assert!(x.is_relative());
+        let Field::Variable(button) = rdesc.fields_for_usage(usage(0x09, 0x01))[0] else {
+            panic!("Expected a variable field");
+        };
+        assert!(!button.is_relative());
+
+        let bytes = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/0003:056A:00E3.0002.hid.bin"
+        ))
+        .unwrap();
+        let rdesc = ReportDescriptor::try_from(&bytes).unwrap();
+        let Field::Variable(x) = rdesc.fields_for_usage(usage(0x01, 0x30))[0] else {
+            panic!("Expected a variable field");
+        };
+        assert!(!x.is_relative());
+
+        let copy = ReportDescriptor::try_from(&rdesc.to_bytes()).unwrap();
+        assert_equivalent(&rdesc, &copy);
+    }
+
+    #[test]
+    fn main_item_flags() {
+        let bytes: &[u8] = &[
+            0x06, 0x00, 0xff, // Usage Page (Vendor Defined 0xff00)
+            0x09, 0x01, // Usage (0x01)
+            0xa1, 0x01, // Collection (Application)
+            0x15, 0x00, // Logical Minimum (0)
+            0x26, 0xff, 0x00, // Logical Maximum (255)
+            0x75, 0x08, // Report Size (8)
+            0x95, 0x01, // Report Count (1)
+            0x09, 0x02, // Usage (0x02)
+            0xb2, 0x82, 0x01, // Feature (Data,Var,Abs,Vol,Buf)
+            0x09, 0x03, // Usage (0x03)
+            0xb1, 0x3a, // Feature (Data,Var,Abs,Wrap,NonLin,NoPref)
+            0x09, 0x04, // Usage (0x04)
+            0x81, 0x86, // Input (Data,Var,Rel), bit 7 is reserved for Input
+            0xc0, // End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        let Field::Variable(f) = rdesc.fields_for_usage(usage(0xff00, 0x02))[0] else {
+            panic!("Expected a variable field");
+        };
+        assert_eq!(
+            f.flags(),
+            MainItemFlags {
+                is_volatile: true,
+                is_buffered_bytes: true,
+                ..Default::default()
+            }
+        );
+
+        let Field::Variable(f) = rdesc.fields_for_usage(usage(0xff00, 0x03))[0] else {
+            panic!("Expected a variable field");
+        };
+        assert_eq!(
+            f.flags(),
+            MainItemFlags {
+                wraps: true,
+                is_nonlinear: true,
+                has_no_preferred_state: true,
+                ..Default::default()
+            }
+        );
+
+        let Field::Variable(f) = rdesc.fields_for_usage(usage(0xff00, 0x04))[0] else {
+            panic!("Expected a variable field");
+        };
+        assert_eq!(
+            f.flags(),
+            MainItemFlags {
+                is_relative: true,
+                ..Default::default()
+            }
+        );
+
+        let copy = ReportDescriptor::try_from(&rdesc.to_bytes()).unwrap();
+        assert_equivalent(&rdesc, &copy);
+    }
+
+    #[test]
+    fn feature_report_decode_encode() {
+        let bytes: &[u8] = &[
+            0x06, 0x00, 0xff, // Usage Page (Vendor Defined 0xff00)
+            0x09, 0x01, // Usage (0x01)
+            0xa1, 0x01, // Collection (Application)
+            0x85, 0x03, // Report ID (3)
+            0x09, 0x02, // Usage (0x02)
+            0x15, 0x00, // Logical Minimum (0)
+            0x26, 0xff, 0x00, // Logical Maximum (255)
+            0x75, 0x08, // Report Size (8)
+            0x95, 0x01, // Report Count (1)
+            0x81, 0x02, // Input (Data,Var,Abs)
+            0x09, 0x10, // Usage (0x10) - sensitivity
+            0x15, 0x01, // Logical Minimum (1)
+            0x25, 0x0a, // Logical Maximum (10)
+            0x75, 0x04, // Report Size (4)
+            0xb1, 0x02, // Feature (Data,Var,Abs)
+            0x09, 0x11, // Usage (0x11) - LED enabled
+            0x15, 0x00, // Logical Minimum (0)
+            0x25, 0x01, // Logical Maximum (1)
+            0x75, 0x01, // Report Size (1)
+            0xb1, 0x02, // Feature (Data,Var,Abs)
+            0x75, 0x03, // Report Size (3)
+            0xb1, 0x03, // Feature (Cnst,Var,Abs)
+            0x09, 0x12, // Usage (0x12) - trim
+            0x16, 0x18, 0xfc, // Logical Minimum (-1000)
+            0x26, 0xe8, 0x03, // Logical Maximum (1000)
+            0x75, 0x10, // Report Size (16)
+            0xb1, 0x02, // Feature (Data,Var,Abs)
+            0xc0, // End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+
+        // Sensitivity 7, LED on, trim -2
+        let data = [0x03, 0x17, 0xfe, 0xff];
+        let report = rdesc.feature_report_for(&data).unwrap();
+        assert_eq!(report.report_id(), &Some(ReportId(3)));
+        assert_eq!(report.size_in_bytes(), 4);
+        let values = report.decode(&data).unwrap();
+        assert_eq!(
+            values,
+            vec![
+                (usage(0xff00, 0x10), 7),
+                (usage(0xff00, 0x11), 1),
+                (usage(0xff00, 0x12), -2),
+            ]
+        );
+        assert_eq!(report.encode(&values).unwrap(), data);
+
+        // Out of range values are clamped, missing usages are zero
+        let bytes = report
+            .encode(&[(usage(0xff00, 0x12), 5000), (usage(0xff00, 0x10), 0)])
+            .unwrap();
+        assert_eq!(bytes, [0x03, 0x01, 0xe8, 0x03]);
+
+        // The input report with the same ID is a different report
+        assert_eq!(rdesc.find_feature_report(&data).unwrap().size_in_bytes(), 4);
+        assert!(matches!(
+            report.decode(&[0x03, 0x17]),
+            Err(ParserError::OutOfBounds)
+        ));
+        assert!(matches!(
+            rdesc.feature_report_for(&[0x03, 0x17]),
+            Err(ParserError::OutOfBounds)
+        ));
+        assert!(matches!(
+            rdesc.feature_report_for(&[0x04, 0, 0, 0]),
+            Err(ParserError::MismatchingReportId)
+        ));
+        let input = rdesc.input_reports().first().unwrap();
+        assert_eq!(
+            input.decode(&[0x03, 0x2a]).unwrap(),
+            vec![(usage(0xff00, 0x02), 0x2a)]
+        );
+        assert!(matches!(
+            input.decode(&[0x04, 0x2a]),
+            Err(ParserError::MismatchingReportId)
+        ));
+
+        // Arrays encode usages with nonzero values into the elements
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        let input = rdesc.input_reports().first().unwrap();
+        let bytes = input.encode(&[
+            (usage(0x07, 0xe1), 1),
+            (usage(0x07, 0x04), 1),
+            (usage(0x07, 0x05), 0),
+            (usage(0x07, 0x06), 1),
+        ]);
+        assert_eq!(
+            bytes.unwrap(),
+            [0b10, 0x00, 0x04, 0x06, 0x00, 0x00, 0x00, 0x00]
+        );
+
+        // A 64-bit field parses but cannot be encoded
+        let wide: &[u8] = &[
+            0x06, 0x00, 0xff, // Usage Page (Vendor Defined 0xFF00)
+            0x09, 0x01, //       Usage (Vendor Usage 1)
+            0x15, 0x00, //       Logical Minimum (0)
+            0x25, 0x01, //       Logical Maximum (1)
+            0x75, 0x40, //       Report Size (64)
+            0x95, 0x01, //       Report Count (1)
+            0xb1, 0x02, //       Feature (Data,Var,Abs)
+        ];
+        let rdesc = ReportDescriptor::try_from(wide).unwrap();
+        let report = rdesc.feature_reports().first().unwrap();
+        assert!(matches!(
+            report.encode(&[(usage(0xff00, 0x01), 1)]),
+            Err(ParserError::InvalidReport { .. })
+        ));
+        assert_eq!(report.encode(&[]).unwrap(), [0; 8]);
+    }
 
-                // Report descriptors with a ReportCount or ReportSize of 0 (or those missing)
-                // will have an empty fields list. These exist in the wild.
-                if !fields.is_empty() {
-                    // Now update the returned field(s) and push them into the right report
-                    let direction = match item {
-                        MainItem::Input(_) => Direction::Input,
-                        MainItem::Output(_) => Direction::Output,
-                        MainItem::Feature(_) => Direction::Feature,
-                        _ => panic!("Invalid item for handle_main_item()"),
-                    };
+    #[test]
+    fn bit_offset_of() {
+        let wheel = usage(0x01, 0x38);
+        let rdesc = ReportDescriptor::try_from(WHEEL_MOUSE).unwrap();
+        assert_eq!(rdesc.bit_offset_of(wheel, Direction::Input), Some(24..=31));
+        assert_eq!(rdesc.bit_offset_of(wheel, Direction::Output), None);
+        assert_eq!(
+            rdesc.bit_offset_of(usage(0x01, 0x32), Direction::Input),
+            None
+        );
 
-                    let reports: &mut Vec<RDescReport> = match direction {
-                        Direction::Input => &mut rdesc.input_reports,
-                        Direction::Output => &mut rdesc.output_reports,
-                        Direction::Feature => &mut rdesc.feature_reports,
-                    };
+        // Includes the Report ID byte
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        assert_eq!(
+            rdesc.bit_offset_of(usage(0x01, 0x30), Direction::Input),
+            Some(16..=23)
+        );
+        assert_eq!(
+            rdesc.bit_offset_of(usage(0x06, 0x20), Direction::Input),
+            Some(8..=15)
+        );
+    }
 
-                    let report_id = fields.first().unwrap().report_id();
-                    let report = match report_id {
-                        None => reports.first_mut(),
-                        Some(id) => reports
-                            .iter_mut()
-                            .find(|r| r.id.is_some() && &r.id.unwrap() == id),
-                    };
+    #[test]
+    fn conflicting_report_ids() {
+        // Two application collections both using Input Report ID 1
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x02, // Usage (Mouse)
+            0xa1, 0x01, // Collection (Application)
+            0x85, 0x01, // Report ID (1)
+            0x09, 0x30, // Usage (X)
+            0x15, 0x81, // Logical Minimum (-127)
+            0x25, 0x7f, // Logical Maximum (127)
+            0x75, 0x08, // Report Size (8)
+            0x95, 0x01, // Report Count (1)
+            0x81, 0x06, // Input (Data,Var,Rel)
+            0xc0, // End Collection
+            0x09, 0x06, // Usage (Keyboard)
+            0xa1, 0x01, // Collection (Application)
+            0x09, 0x31, // Usage (Y)
+            0x81, 0x06, // Input (Data,Var,Rel)
+            0xc0, // End Collection
+        ];
+        let Err(ParserError::InvalidData { offset, message }) = ReportDescriptor::try_from(bytes)
+        else {
+            panic!("Expected an error for a duplicate Report ID");
+        };
+        assert_eq!(offset, 27);
+        assert_eq!(
+            message,
+            "Input Report ID 1 is used in more than one collection"
+        );
 
-                    let report = match report {
-                        None => {
-                            let initial_size = if report_id.is_some() { 8 } else { 0 };
-                            reports.push(RDescReport {
-                                id: *report_id,
-                                size: initial_size,
-                                fields: vec![],
-                            });
-                            reports.last_mut().unwrap()
-                        }
-                        Some(r) => r,
-                    };
+        // The same Report ID in a different direction is fine
+        let mut bytes = bytes.to_vec();
+        bytes[27] = 0xb1; // Feature (Data,Var,Rel)
+        let rdesc = ReportDescriptor::try_from(&bytes).unwrap();
+        assert_eq!(rdesc.input_report_ids(), vec![ReportId(1)]);
+        assert_eq!(rdesc.feature_report_ids(), vec![ReportId(1)]);
+
+        // A Report ID restored by Pop continues the same report
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x02, // Usage (Mouse)
+            0xa1, 0x01, // Collection (Application)
+            0x85, 0x01, // Report ID (1)
+            0x09, 0x30, // Usage (X)
+            0x15, 0x81, // Logical Minimum (-127)
+            0x25, 0x7f, // Logical Maximum (127)
+            0x75, 0x08, // Report Size (8)
+            0x95, 0x01, // Report Count (1)
+            0x81, 0x06, // Input (Data,Var,Rel)
+            0xa4, // Push
+            0x85, 0x02, // Report ID (2)
+            0x09, 0x38, // Usage (Wheel)
+            0x81, 0x06, // Input (Data,Var,Rel)
+            0xb4, // Pop
+            0x09, 0x31, // Usage (Y)
+            0x81, 0x06, // Input (Data,Var,Rel)
+            0xc0, // End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        assert_eq!(rdesc.input_report_ids(), vec![ReportId(1), ReportId(2)]);
+        assert_eq!(
+            rdesc.bit_offset_of(usage(0x01, 0x31), Direction::Input),
+            Some(16..=23)
+        );
 
-                    // We know which report the fields belong to, let's update the offsets and field id
-                    let offset = report.size;
-                    fields.iter_mut().for_each(|f| {
-                        f.update_bit_offset(offset);
-                        report.size += f.len();
-                    });
+        // Input reports with and without a Report ID
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x02, // Usage (Mouse)
+            0xa1, 0x01, // Collection (Application)
+            0x09, 0x30, // Usage (X)
+            0x15, 0x81, // Logical Minimum (-127)
+            0x25, 0x7f, // Logical Maximum (127)
+            0x75, 0x08, // Report Size (8)
+            0x95, 0x01, // Report Count (1)
+            0x81, 0x06, // Input (Data,Var,Rel)
+            0x85, 0x02, // Report ID (2)
+            0x09, 0x31, // Usage (Y)
+            0x81, 0x06, // Input (Data,Var,Rel)
+            0xc0, // End Collection
+        ];
+        let Err(ParserError::InvalidData { offset, message }) = ReportDescriptor::try_from(bytes)
+        else {
+            panic!("Expected an error for a missing Report ID");
+        };
+        assert_eq!(offset, 22);
+        assert_eq!(message, "Input reports both with and without a Report ID");
+    }
 
-                    report.fields.append(&mut fields);
-                }
-            }
-            ItemType::Long => {}
-            ItemType::Reserved => {}
-            ItemType::Global(GlobalItem::UsagePage { usage_page }) => {
-                update_stack!(stack, globals, usage_page, usage_page);
-            }
-            ItemType::Global(GlobalItem::LogicalMinimum { minimum }) => {
-                update_stack!(stack, globals, logical_minimum, minimum);
-            }
-            ItemType::Global(GlobalItem::LogicalMaximum { maximum }) => {
-                // We don't know if the maximum is signed or unsigned unless we
-                // look at the minimum value and check if that is signed or unsigned.
-                // We default to signed but if the minimum is unsigned, we might have
-                // to re-interpret.
-                let minimum = stack
-                    .globals_const()
-                    .logical_minimum
-                    .unwrap_or(LogicalMinimum(0));
-                let mut maximum = maximum;
-                if minimum < LogicalMinimum(0) {
-                    if let Some(data) = item.data() {
-                        if data.len() > 0 {
-                            maximum = LogicalMaximum(hid::hiddata_signed(&data).unwrap());
-                        }
-                    }
-                };
-                update_stack!(stack, globals, logical_maximum, maximum);
-            }
-            ItemType::Global(GlobalItem::PhysicalMinimum { minimum }) => {
-                update_stack!(stack, globals, physical_minimum, minimum);
-            }
-            ItemType::Global(GlobalItem::PhysicalMaximum { maximum }) => {
-                // We don't know if the maximum is signed or unsigned unless we
-                // look at the minimum value and check if that is signed or unsigned.
-                // We default to signed but if the minimum is unsigned, we might have
-                // to re-interpret.
-                let minimum = stack
-                    .globals_const()
-                    .physical_minimum
-                    .unwrap_or(PhysicalMinimum(0));
-                let mut maximum = maximum;
-                if minimum < PhysicalMinimum(0) {
-                    if let Some(data) = item.data() {
-                        if data.len() > 0 {
-                            maximum = PhysicalMaximum(hid::hiddata_signed(&data).unwrap())
-                        }
-                    }
-                };
-                update_stack!(stack, globals, physical_maximum, maximum);
-            }
-            ItemType::Global(GlobalItem::UnitExponent { exponent }) => {
-                update_stack!(stack, globals, unit_exponent, exponent);
-            }
-            ItemType::Global(GlobalItem::Unit { unit }) => {
-                update_stack!(stack, globals, unit, unit);
-            }
-            ItemType::Global(GlobalItem::ReportSize { size }) => {
-                update_stack!(stack, globals, report_size, size);
-            }
-            ItemType::Global(GlobalItem::ReportId { id }) => {
-                update_stack!(stack, globals, report_id, id);
-            }
-            ItemType::Global(GlobalItem::ReportCount { count }) => {
-                update_stack!(stack, globals, report_count, count);
-            }
-            ItemType::Global(GlobalItem::Push) => {
-                stack.push();
-            }
-            ItemType::Global(GlobalItem::Pop) => match stack.pop() {
-                Ok(_) => {}
-                Err(ParserError::InvalidData { message, .. }) => {
-                    return Err(ParserError::InvalidData {
-                        offset: rdesc_item.offset(),
-                        message,
-                    })
-                }
-                Err(e) => return Err(e),
-            },
-            ItemType::Global(GlobalItem::Reserved) => {}
-            ItemType::Local(LocalItem::Usage {
-                usage_page,
-                usage_id,
-            }) => {
-                let usage = LocalUsage {
-                    usage_page,
-                    usage_id,
-                };
-                stack.locals().usage.push(usage);
-            }
-            ItemType::Local(LocalItem::UsageMinimum { minimum }) => {
-                update_stack!(stack, locals, usage_minimum, minimum);
+    #[test]
+    fn led_usages() {
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        let leds = rdesc.led_usages();
+        assert_eq!(leds.len(), 5);
+        assert_eq!(
+            leds[..3],
+            [
+                (usage(0x08, 0x01), 0..=0), // Num Lock
+                (usage(0x08, 0x02), 1..=1), // Caps Lock
+                (usage(0x08, 0x03), 2..=2), // Scroll Lock
+            ]
+        );
+
+        let rdesc = ReportDescriptor::try_from(WHEEL_MOUSE).unwrap();
+        assert!(rdesc.led_usages().is_empty());
+    }
+
+    #[test]
+    fn reports() {
+        let rdesc = ReportDescriptor::try_from(KEYBOARD_CONSUMER).unwrap();
+        assert_eq!(rdesc.len(), 2);
+        assert!(!rdesc.is_empty());
+        let ids: Vec<_> = rdesc.reports().map(|r| *r.report_id()).collect();
+        assert_eq!(ids, vec![Some(ReportId(1)), Some(ReportId(2))]);
+
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        assert_eq!(rdesc.len(), 2);
+        let sizes: Vec<_> = rdesc.reports().map(|r| r.size_in_bytes()).collect();
+        assert_eq!(sizes, vec![8, 1]);
+
+        assert!(ReportDescriptor::default().is_empty());
+        assert_eq!(ReportDescriptor::default().reports().count(), 0);
+    }
+
+    #[test]
+    fn padding_ranges() {
+        let bytes: &[u8] = &[
+            0x05, 0x09, // Usage Page (Button)
+            0x19, 0x01, // Usage Minimum (1)
+            0x29, 0x04, // Usage Maximum (4)
+            0x15, 0x00, // Logical Minimum (0)
+            0x25, 0x01, // Logical Maximum (1)
+            0x75, 0x01, // Report Size (1)
+            0x95, 0x04, // Report Count (4)
+            0x81, 0x02, // Input (Data,Var,Abs)
+            0x75, 0x04, // Report Size (4)
+            0x95, 0x01, // Report Count (1)
+            0x81, 0x01, // Input (Cnst,Arr,Abs)
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x30, // Usage (X)
+            0x15, 0x81, // Logical Minimum (-127)
+            0x25, 0x7f, // Logical Maximum (127)
+            0x75, 0x08, // Report Size (8)
+            0x81, 0x06, // Input (Data,Var,Rel)
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        let report = &rdesc.input_reports[0];
+        assert!(report.padding_ranges().is_empty());
+        assert!(matches!(report.fields()[4], Field::Constant(_)));
+
+        // Without the explicit padding the 4 bits are a gap
+        let mut report = report.clone();
+        report.fields.remove(4);
+        assert_eq!(report.padding_ranges(), vec![4..=7]);
+
+        // Gaps at the start and end of a report with a Report ID
+        let report = RDescReport {
+            id: Some(ReportId(1)),
+            size: 32,
+            fields: vec![Field::Constant(ConstantField::new(12..16))],
+            ..Default::default()
+        };
+        assert_eq!(report.padding_ranges(), vec![8..=11, 16..=31]);
+    }
+
+    #[test]
+    fn parse_validated() {
+        for bytes in [BOOT_KEYBOARD, BATTERY_MOUSE, WHEEL_MOUSE, KEYBOARD_CONSUMER] {
+            assert!(ReportDescriptor::parse_validated(bytes).is_ok());
+        }
+
+        // BATTERY_MOUSE with the battery in Report ID 0
+        let mut bytes = BATTERY_MOUSE.to_vec();
+        let idx = bytes.windows(2).position(|w| w == [0x85, 0x02]).unwrap();
+        bytes[idx + 1] = 0x00;
+        let rdesc = ReportDescriptor::try_from(&bytes).unwrap();
+        assert_eq!(rdesc.input_report_ids(), vec![ReportId(0), ReportId(1)]);
+        let Err(ParserError::InvalidReport { message }) = ReportDescriptor::parse_validated(&bytes)
+        else {
+            panic!("Expected Report ID 0 to fail validation");
+        };
+        assert_eq!(message, "Input report uses the reserved Report ID 0");
+    }
+
+    #[test]
+    fn physical_value() {
+        // A joystick X axis, -90..=90 degrees, with the physical range
+        // in 1, 2 and 4 byte items
+        let physical: [&[u8]; 3] = [
+            &[0x35, 0xa6, 0x45, 0x5a],
+            &[0x36, 0xa6, 0xff, 0x46, 0x5a, 0x00],
+            &[0x37, 0xa6, 0xff, 0xff, 0xff, 0x47, 0x5a, 0x00, 0x00, 0x00],
+        ];
+        for items in physical {
+            let mut bytes = vec![
+                0x05, 0x01, // Usage Page (Generic Desktop)
+                0x09, 0x04, // Usage (Joystick)
+                0xa1, 0x01, // Collection (Application)
+                0x09, 0x30, // Usage (X)
+                0x15, 0x00, // Logical Minimum (0)
+                0x26, 0xfe, 0x00, // Logical Maximum (254)
+            ];
+            bytes.extend_from_slice(items);
+            bytes.extend_from_slice(&[
+                0x65, 0x14, // Unit (Degrees)
+                0x75, 0x08, // Report Size (8)
+                0x95, 0x01, // Report Count (1)
+                0x81, 0x02, // Input (Data,Var,Abs)
+                0xc0, // End Collection
+            ]);
+            let rdesc = ReportDescriptor::try_from(&bytes).unwrap();
+            let Field::Variable(x) = rdesc.fields_for_usage(usage(0x01, 0x30))[0] else {
+                panic!("Expected a variable field");
+            };
+            assert_eq!(x.physical_minimum, Some(PhysicalMinimum(-90)));
+            assert_eq!(x.physical_maximum, Some(PhysicalMaximum(90)));
+            assert_eq!(x.physical_value(0), Some(-90.0));
+            assert_eq!(x.physical_value(127), Some(0.0));
+            assert_eq!(x.physical_value(254), Some(90.0));
+        }
+
+        let mut x = VariableField::new(
+            usage(0x01, 0x30),
+            0..16,
+            LogicalMinimum(-1000),
+            LogicalMaximum(1000),
+        );
+        assert_eq!(x.physical_value(0), None);
+        x.physical_minimum = Some(PhysicalMinimum(-100));
+        x.physical_maximum = Some(PhysicalMaximum(100));
+        x.unit_exponent = Some(UnitExponent(0x0e)); // -2
+        assert_eq!(x.physical_value(500), Some(0.5));
+        assert_eq!(x.physical_value(-1000), Some(-1.0));
+    }
+
+    #[test]
+    fn to_field_map() {
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        let report = rdesc.find_input_report(&[0x02]).unwrap();
+        assert_eq!(
+            report.to_field_map(),
+            vec![FieldInfo {
+                direction: Direction::Input,
+                usage_name: Some("Generic Device Controls/Battery Strength".into()),
+                bit_offset: 8,
+                bit_width: 8,
+                is_signed: false,
+                logical_range: Some(0..=200),
+                unit: None,
+            }]
+        );
+
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        let map = rdesc.output_reports()[0].to_field_map();
+        assert_eq!(map.len(), 6);
+        assert_eq!(map[0].direction, Direction::Output);
+        assert_eq!(map[0].usage_name.as_deref(), Some("LED/Num Lock"));
+        assert_eq!((map[0].bit_offset, map[0].bit_width), (0, 1));
+        assert_eq!(map[5].usage_name, None);
+        assert_eq!((map[5].bit_offset, map[5].bit_width), (5, 3));
+        assert_eq!(map[5].logical_range, None);
+
+        let map = rdesc.input_reports()[0].to_field_map();
+        let keys = map.last().unwrap();
+        assert_eq!(
+            keys.usage_name.as_deref(),
+            Some("Keyboard/Keypad/0x0000..=Keyboard/Keypad/0x0065")
+        );
+        assert_eq!((keys.bit_offset, keys.bit_width), (16, 48));
+
+        let report = ReportBuilder::new(None)
+            .direction(Direction::Feature)
+            .add_constant(8)
+            .build();
+        assert_eq!(report.direction(), Direction::Feature);
+        assert_eq!(report.to_field_map()[0].direction, Direction::Feature);
+    }
+
+    #[test]
+    fn parse_with_resolver() {
+        let bytes: &[u8] = &[
+            0x06, 0x00, 0xff, // Usage Page (Vendor Defined 0xff00)
+            0x09, 0x01, // Usage (0x01)
+            0xa1, 0x01, // Collection (Application)
+            0x09, 0x02, // Usage (0x02)
+            0x09, 0x03, // Usage (0x03)
+            0x15, 0x00, // Logical Minimum (0)
+            0x25, 0x64, // Logical Maximum (100)
+            0x75, 0x08, // Report Size (8)
+            0x95, 0x02, // Report Count (2)
+            0xb1, 0x02, // Feature (Data,Var,Abs)
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x30, // Usage (X)
+            0x95, 0x01, // Report Count (1)
+            0xb1, 0x02, // Feature (Data,Var,Abs)
+            0xc0, // End Collection
+        ];
+        let rdesc = ReportDescriptor::parse_with_resolver(bytes, |page, id| match (page, id) {
+            (0xff00, 0x02) => Some("Acme/Brightness".into()),
+            (0x01, 0x30) => Some("Not used".into()),
+            _ => None,
+        })
+        .unwrap();
+        let expected = "\
+Feature Report, 24 bits
+  bits  0..8    Variable Acme/Brightness, logical 0..=100
+  bits  8..16   Variable Vendor Defined 0xff00/0x0003, logical 0..=100
+  bits 16..24   Variable Generic Desktop/X, logical 0..=100
+";
+        assert_eq!(format!("{rdesc}"), expected);
+        assert_eq!(format!("{}", rdesc.split_by_application()[0]), expected);
+
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        assert!(format!("{rdesc}").contains("Variable Vendor Defined 0xff00/0x0002"));
+    }
+
+    #[test]
+    fn logical_range_32_bit() {
+        let bytes: &[u8] = &[
+            0x05, 0x0d, // Usage Page (Digitizers)
+            0x09, 0x02, // Usage (Pen)
+            0xa1, 0x01, // Collection (Application)
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x30, // Usage (X)
+            0x15, 0x00, // Logical Minimum (0)
+            0x27, 0xff, 0xff, 0xff, 0xff, // Logical Maximum (4294967295)
+            0x35, 0x00, // Physical Minimum (0)
+            0x47, 0xff, 0xff, 0xff, 0xff, // Physical Maximum (4294967295)
+            0x75, 0x20, // Report Size (32)
+            0x95, 0x01, // Report Count (1)
+            0x81, 0x02, // Input (Data,Var,Abs)
+            0xc0, // End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        let Field::Variable(x) = rdesc.fields_for_usage(usage(0x01, 0x30))[0] else {
+            panic!("Expected a variable field");
+        };
+        assert!(!x.is_signed());
+        assert!(x.is_in_range(0xffff_ffff));
+        assert!(!x.is_in_range(-1));
+        assert_eq!(x.clamp(0x1_0000_0000), 0xffff_ffff);
+        assert_eq!(x.physical_value(0xffff_ffff), Some(4294967295.0));
+        assert_eq!(x.physical_value(0x8000_0000), Some(2147483648.0));
+
+        let value = rdesc
+            .value_for_usage(usage(0x01, 0x30), &[0xfe, 0xff, 0xff, 0xff])
+            .unwrap();
+        assert_eq!(value, Some(0xffff_fffe));
+        let report = &rdesc.input_reports[0];
+        assert_eq!(
+            report.encode(&[(usage(0x01, 0x30), 0xffff_fffe)]).unwrap(),
+            [0xfe, 0xff, 0xff, 0xff]
+        );
+        assert_eq!(
+            report.to_field_map()[0].logical_range,
+            Some(0..=0xffff_ffff)
+        );
+        assert!(format!("{rdesc}").contains("logical 0..=4294967295, physical 0..=4294967295"));
+
+        let copy = ReportDescriptor::try_from(&rdesc.to_bytes()).unwrap();
+        assert_equivalent(&rdesc, &copy);
+    }
+
+    #[test]
+    fn diff() {
+        let old = ReportDescriptor::try_from(WHEEL_MOUSE).unwrap();
+        assert!(old.diff(&old).is_empty());
+
+        // Add an 8-bit AC Pan after the wheel
+        let mut bytes = WHEEL_MOUSE.to_vec();
+        let end = bytes.len() - 2;
+        bytes.splice(
+            end..end,
+            [
+                0x05, 0x0c, // Usage Page (Consumer)
+                0x0a, 0x38, 0x02, // Usage (AC Pan)
+                0x95, 0x01, // Report Count (1)
+                0x81, 0x06, // Input (Data,Var,Rel)
+            ],
+        );
+        let new = ReportDescriptor::try_from(&bytes).unwrap();
+        assert_eq!(
+            old.diff(&new),
+            vec![DescriptorDiff::FieldAdded {
+                direction: Direction::Input,
+                report_id: None,
+                usage: usage(0x0c, 0x238),
+            }]
+        );
+        assert_eq!(
+            new.diff(&old),
+            vec![DescriptorDiff::FieldRemoved {
+                direction: Direction::Input,
+                report_id: None,
+                usage: usage(0x0c, 0x238),
+            }]
+        );
+
+        // Change the logical range of X, Y and Wheel
+        let mut bytes = WHEEL_MOUSE.to_vec();
+        let idx = bytes.windows(2).position(|w| w == [0x25, 0x7f]).unwrap();
+        bytes[idx + 1] = 0x7e;
+        let new = ReportDescriptor::try_from(&bytes).unwrap();
+        let diffs = old.diff(&new);
+        assert_eq!(diffs.len(), 3);
+        assert_eq!(
+            diffs[0],
+            DescriptorDiff::LogicalRangeChanged {
+                direction: Direction::Input,
+                report_id: None,
+                usage: usage(0x01, 0x30),
+                old: -127..=127,
+                new: -127..=126,
             }
-            ItemType::Local(LocalItem::UsageMaximum { maximum }) => {
-                update_stack!(stack, locals, usage_maximum, maximum);
+        );
+
+        let old = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        let new = ReportDescriptor::try_from(WHEEL_MOUSE).unwrap();
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                DescriptorDiff::ReportRemoved {
+                    direction: Direction::Input,
+                    report_id: Some(ReportId(1)),
+                },
+                DescriptorDiff::ReportRemoved {
+                    direction: Direction::Input,
+                    report_id: Some(ReportId(2)),
+                },
+                DescriptorDiff::ReportAdded {
+                    direction: Direction::Input,
+                    report_id: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn tree() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x05, // Usage (Gamepad)
+            0xa1, 0x01, // Collection (Application)
+            0x09, 0x01, // Usage (Pointer)
+            0xa1, 0x00, // Collection (Physical)
+            0x09, 0x30, // Usage (X)
+            0x09, 0x31, // Usage (Y)
+            0x15, 0x81, // Logical Minimum (-127)
+            0x25, 0x7f, // Logical Maximum (127)
+            0x75, 0x08, // Report Size (8)
+            0x95, 0x02, // Report Count (2)
+            0x81, 0x02, // Input (Data,Var,Abs)
+            0x09, 0x01, // Usage (Pointer)
+            0xa1, 0x00, // Collection (Physical)
+            0x09, 0x32, // Usage (Z)
+            0x95, 0x01, // Report Count (1)
+            0x81, 0x02, // Input (Data,Var,Abs)
+            0xc0, // End Collection
+            0xc0, // End Collection
+            0x05, 0x09, // Usage Page (Button)
+            0x19, 0x01, // Usage Minimum (1)
+            0x29, 0x04, // Usage Maximum (4)
+            0x15, 0x00, // Logical Minimum (0)
+            0x25, 0x01, // Logical Maximum (1)
+            0x75, 0x01, // Report Size (1)
+            0x95, 0x04, // Report Count (4)
+            0x81, 0x02, // Input (Data,Var,Abs)
+            0x75, 0x04, // Report Size (4)
+            0x95, 0x01, // Report Count (1)
+            0x81, 0x01, // Input (Cnst,Arr,Abs)
+            0xc0, // End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        let depth = |u| match rdesc.fields_for_usage(u)[0] {
+            Field::Variable(v) => v.depth(),
+            _ => panic!("Expected a variable field"),
+        };
+        assert_eq!(depth(usage(0x01, 0x30)), 2);
+        assert_eq!(depth(usage(0x01, 0x32)), 3);
+        assert_eq!(depth(usage(0x09, 0x01)), 1);
+
+        let report = &rdesc.input_reports[0];
+        let root = report.tree();
+        assert!(root.collection.is_none());
+        assert_eq!(root.fields.len(), 1); // padding
+        assert_eq!(root.children.len(), 1);
+
+        let gamepad = &root.children[0];
+        let c = gamepad.collection.unwrap();
+        assert_eq!(c.collection_type(), CollectionType::Application);
+        assert_eq!(c.usages(), &[usage(0x01, 0x05)]);
+        assert_eq!(gamepad.fields.len(), 4); // buttons
+        assert_eq!(gamepad.children.len(), 1);
+
+        let pointer = &gamepad.children[0];
+        let c = pointer.collection.unwrap();
+        assert_eq!(c.collection_type(), CollectionType::Physical);
+        let usages: Vec<_> = pointer
+            .fields
+            .iter()
+            .map(|f| f.usages().first().unwrap())
+            .collect();
+        assert_eq!(usages, vec![usage(0x01, 0x30), usage(0x01, 0x31)]);
+        assert_eq!(pointer.children.len(), 1);
+
+        let nested = &pointer.children[0];
+        assert_eq!(nested.fields[0].usages().to_vec(), [usage(0x01, 0x32)]);
+        assert!(nested.children.is_empty());
+    }
+
+    #[test]
+    fn fields_by_offset() {
+        for bytes in [BOOT_KEYBOARD, BATTERY_MOUSE, WHEEL_MOUSE, KEYBOARD_CONSUMER] {
+            let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+            for report in rdesc.reports() {
+                let starts: Vec<usize> = report.fields().iter().map(|f| f.bits().start).collect();
+                assert!(starts.windows(2).all(|w| w[0] < w[1]));
+                let ids: Vec<FieldId> = report.fields().iter().map(|f| f.id()).collect();
+                let sorted: Vec<FieldId> =
+                    report.fields_by_offset().iter().map(|f| f.id()).collect();
+                assert_eq!(ids, sorted);
             }
-            ItemType::Local(LocalItem::DesignatorIndex { index }) => {
-                update_stack!(stack, locals, designator_index, index);
+        }
+
+        let report = RDescReport {
+            size: 16,
+            fields: vec![
+                Field::Constant(ConstantField::new(8..16)),
+                Field::Constant(ConstantField::new(0..8)),
+            ],
+            ..Default::default()
+        };
+        let starts: Vec<usize> = report
+            .fields_by_offset()
+            .iter()
+            .map(|f| f.bits().start)
+            .collect();
+        assert_eq!(starts, vec![0, 8]);
+    }
+
+    #[test]
+    fn zero_report_count() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x02, // Usage (Mouse)
+            0xa1, 0x01, // Collection (Application)
+            0x09, 0x30, // Usage (X)
+            0x15, 0x81, // Logical Minimum (-127)
+            0x25, 0x7f, // Logical Maximum (127)
+            0x75, 0x08, // Report Size (8)
+            0x95, 0x00, // Report Count (0)
+            0x81, 0x01, // Input (Cnst,Arr,Abs)
+            0x81, 0x06, // Input (Data,Var,Rel)
+            0x81, 0x00, // Input (Data,Arr,Abs)
+            0x09, 0x31, // Usage (Y)
+            0x95, 0x01, // Report Count (1)
+            0x81, 0x06, // Input (Data,Var,Rel)
+            0xc0, // End Collection
+        ];
+        // Lenient parsing skips the data items with a warning
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        assert_eq!(
+            rdesc.warnings(),
+            &[
+                ParseWarning::ZeroReportCount { offset: 18 },
+                ParseWarning::ZeroReportCount { offset: 20 },
+            ]
+        );
+        let report = &rdesc.input_reports[0];
+        assert_eq!(report.fields().len(), 1);
+        assert_eq!(report.fields()[0].bits(), &(0..8));
+        report.validate().unwrap();
+
+        match ReportDescriptor::parse_validated(bytes) {
+            Err(ParserError::InvalidData { offset, message }) => {
+                assert_eq!(offset, 18);
+                assert_eq!(message, "Report Count of 0 for a data main item");
             }
-            ItemType::Local(LocalItem::DesignatorMinimum { minimum }) => {
-                update_stack!(stack, locals, designator_minimum, minimum);
+            r => panic!("Unexpected result {r:?}"),
+        }
+    }
+
+    #[test]
+    fn extract_with_order() {
+        let report = ReportBuilder::new(Some(ReportId(1)))
+            .add_variable(usage(0x01, 0x30), 16, 0..=65535)
+            .add_variable(usage(0x01, 0x31), 16, -32767..=32767)
+            .add_variable(usage(0x01, 0x32), 24, 0..=0xffffff)
+            .add_variable(usage(0x01, 0x33), 4, 0..=15)
+            .add_constant(4)
+            .build();
+        let fields: Vec<&VariableField> = report
+            .fields()
+            .iter()
+            .filter_map(|f| match f {
+                Field::Variable(v) => Some(v),
+                _ => None,
+            })
+            .collect();
+        let bytes = [0x01, 0x12, 0x34, 0xff, 0xfe, 0x01, 0x02, 0x03, 0x0f];
+
+        let x = fields[0];
+        assert_eq!(x.extract(&bytes).unwrap(), 0x3412);
+        assert_eq!(
+            x.extract_with_order(&bytes, ByteOrder::LittleEndian)
+                .unwrap(),
+            0x3412
+        );
+        assert_eq!(
+            x.extract_with_order(&bytes, ByteOrder::BigEndian).unwrap(),
+            0x1234
+        );
+
+        let y = fields[1];
+        assert_eq!(y.extract(&bytes).unwrap(), -257); // 0xfeff
+        assert_eq!(
+            y.extract_with_order(&bytes, ByteOrder::BigEndian).unwrap(),
+            -2 // 0xfffe
+        );
+
+        let z = fields[2];
+        assert_eq!(
+            z.extract_with_order(&bytes, ByteOrder::BigEndian).unwrap(),
+            0x010203
+        );
+
+        let rx = fields[3];
+        assert_eq!(rx.extract(&bytes).unwrap(), 0x0f);
+        assert!(matches!(
+            rx.extract_with_order(&bytes, ByteOrder::BigEndian),
+            Err(ParserError::InvalidReport { .. })
+        ));
+    }
+
+    #[test]
+    fn is_boolean() {
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        let Field::Variable(lshift) = rdesc.fields_for_usage(usage(0x07, 0xe1))[0] else {
+            panic!("Expected a variable field");
+        };
+        assert!(lshift.is_boolean());
+        assert_eq!(lshift.logical_span(), 1);
+
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        let Field::Variable(x) = rdesc.fields_for_usage(usage(0x01, 0x30))[0] else {
+            panic!("Expected a variable field");
+        };
+        assert!(!x.is_boolean());
+        assert_eq!(x.logical_span(), 254);
+
+        let Field::Variable(battery) = rdesc.fields_for_usage(usage(0x06, 0x20))[0] else {
+            panic!("Expected a variable field");
+        };
+        assert!(!battery.is_boolean());
+        assert_eq!(battery.logical_span(), 200);
+
+        // An 8-bit switch with a logical range of 0..=1
+        let switch = VariableField::new(
+            usage(0x01, 0x3d),
+            0..8,
+            LogicalMinimum(0),
+            LogicalMaximum(1),
+        );
+        assert!(switch.is_boolean());
+    }
+
+    #[test]
+    fn explain() {
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        let bytes = [0x01, 0b101, 0xff, 0x05];
+        let report = rdesc.find_input_report(&bytes).unwrap();
+        let expected = "\
+01 05 ff 05
+bits 8-8: Button/Button 1 = 1
+bits 9-9: Button/Button 2 = 0
+bits 10-10: Button/Button 3 = 1
+bits 11-15: Constant
+bits 16-23: Generic Desktop/X = -1
+bits 24-31: Generic Desktop/Y = 5";
+        assert_eq!(report.explain(&bytes).unwrap(), expected);
+        assert!(matches!(
+            report.explain(&[0x02, 0x00, 0x00, 0x00]),
+            Err(ParserError::MismatchingReportId)
+        ));
+        assert!(matches!(
+            report.explain(&bytes[..3]),
+            Err(ParserError::OutOfBounds)
+        ));
+
+        // Left Shift and the 'a' key
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        let bytes = [0b10, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00];
+        let explanation = rdesc.input_reports[0].explain(&bytes).unwrap();
+        let lines: Vec<&str> = explanation.lines().collect();
+        assert_eq!(lines[2], "bits 1-1: Keyboard/Keypad/0x00e1 = 1");
+        assert_eq!(lines[9], "bits 8-15: Constant");
+        assert_eq!(
+            lines[10],
+            "bits 16-23: Array[0] = 4 (Keyboard/Keypad/0x0004)"
+        );
+        assert_eq!(lines[11], "bits 24-31: Array[1] = 0");
+    }
+
+    #[test]
+    fn set_id() {
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        let mut report = rdesc.input_reports[0].clone();
+        report.set_id(Some(ReportId(7)));
+        assert_eq!(report.report_id(), &Some(ReportId(7)));
+        assert_eq!(report.size_in_bits(), 32);
+        assert!(report
+            .fields()
+            .iter()
+            .all(|f| f.report_id() == &Some(ReportId(7))));
+        let bits: Vec<_> = report.fields().iter().map(|f| f.bits().clone()).collect();
+        let orig: Vec<_> = rdesc.input_reports[0]
+            .fields()
+            .iter()
+            .map(|f| f.bits().clone())
+            .collect();
+        assert_eq!(bits, orig);
+        report.validate().unwrap();
+        let Field::Variable(x) = &report.fields()[4] else {
+            panic!("Expected a variable field");
+        };
+        assert_eq!(x.extract(&[0x07, 0x00, 0xff, 0x00]).unwrap(), -1);
+        assert!(matches!(
+            x.extract(&[0x01, 0x00, 0xff, 0x00]),
+            Err(ParserError::MismatchingReportId)
+        ));
+
+        // Removing the Report ID shifts all fields by one byte
+        report.set_id(None);
+        assert_eq!(report.size_in_bits(), 24);
+        assert!(report.fields().iter().all(|f| f.report_id().is_none()));
+        assert_eq!(report.fields()[0].bits(), &(0..1));
+        report.validate().unwrap();
+        let Field::Variable(x) = &report.fields()[4] else {
+            panic!("Expected a variable field");
+        };
+        assert_eq!(x.extract(&[0x00, 0xff, 0x00]).unwrap(), -1);
+
+        // And adding it back restores the original layout
+        report.set_id(Some(ReportId(1)));
+        let bits: Vec<_> = report.fields().iter().map(|f| f.bits().clone()).collect();
+        assert_eq!(bits, orig);
+    }
+
+    #[test]
+    fn set_report_id() {
+        let mut rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        rdesc
+            .set_report_id(Direction::Input, Some(ReportId(2)), Some(ReportId(5)))
+            .unwrap();
+        let ids: Vec<_> = rdesc
+            .input_reports()
+            .iter()
+            .map(|r| *r.report_id())
+            .collect();
+        assert_eq!(ids, [Some(ReportId(1)), Some(ReportId(5))]);
+        assert!(rdesc.input_reports()[1]
+            .fields()
+            .iter()
+            .all(|f| f.report_id() == &Some(ReportId(5))));
+        let copy = ReportDescriptor::parse_validated(&rdesc.to_bytes()).unwrap();
+        assert_equivalent(&rdesc, &copy);
+
+        // Errors leave the report descriptor unchanged
+        for (old, new, message) in [
+            (
+                Some(ReportId(2)),
+                Some(ReportId(3)),
+                "No Input report with Report ID 2",
+            ),
+            (
+                Some(ReportId(1)),
+                Some(ReportId(5)),
+                "Input Report ID 5 is used by more than one report",
+            ),
+            (
+                Some(ReportId(1)),
+                None,
+                "Input reports both with and without a Report ID",
+            ),
+        ] {
+            match rdesc.set_report_id(Direction::Input, old, new) {
+                Err(ParserError::InvalidReport { message: m }) => assert_eq!(m, message),
+                r => panic!("Expected InvalidReport, got {r:?}"),
             }
-            ItemType::Local(LocalItem::DesignatorMaximum { maximum }) => {
-                update_stack!(stack, locals, designator_maximum, maximum);
+            assert_equivalent(&rdesc, &copy);
+        }
+
+        // A single report may gain a Report ID
+        let mut rdesc = ReportDescriptor::boot_mouse();
+        rdesc
+            .set_report_id(Direction::Input, None, Some(ReportId(1)))
+            .unwrap();
+        assert_eq!(rdesc.input_reports()[0].report_id(), &Some(ReportId(1)));
+        assert_eq!(rdesc.input_reports()[0].size_in_bytes(), 4);
+        let copy = ReportDescriptor::parse_validated(&rdesc.to_bytes()).unwrap();
+        assert_equivalent(&rdesc, &copy);
+    }
+
+    #[test]
+    fn pages_constants() {
+        let rdesc = ReportDescriptor::try_from(WHEEL_MOUSE).unwrap();
+        let usages: Vec<Usage> = rdesc.input_reports[0]
+            .fields()
+            .iter()
+            .filter_map(|f| match f {
+                Field::Variable(v) => Some(v.usage),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(usages[0].usage_page, pages::BUTTON);
+        assert!(usages.contains(&pages::usages::X));
+        assert!(usages.contains(&pages::usages::Y));
+        assert!(usages.contains(&pages::usages::WHEEL));
+        assert_eq!(pages::usages::WHEEL.usage_page, pages::GENERIC_DESKTOP);
+        assert!(rdesc
+            .bit_offset_of(pages::usages::WHEEL, Direction::Input)
+            .is_some());
+    }
+
+    #[test]
+    fn inverted_usage_range() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x06, // Usage (Keyboard)
+            0xa1, 0x01, // Collection (Application)
+            0x05, 0x07, //  Usage Page (Keyboard)
+            0x19, 0x10, //  Usage Minimum (0x10)
+            0x29, 0x05, //  Usage Maximum (0x05)
+            0x15, 0x00, //  Logical Minimum (0)
+            0x25, 0x01, //  Logical Maximum (1)
+            0x75, 0x01, //  Report Size (1)
+            0x95, 0x08, //  Report Count (8)
+            0x81, 0x02, //  Input (Data,Var,Abs)
+            0xc0, // End Collection
+        ];
+        match ReportDescriptor::try_from(bytes) {
+            Err(ParserError::InvalidData { offset, message }) => {
+                assert_eq!(offset, 20);
+                assert!(message.contains("Usage Minimum 0x10"), "{message}");
             }
-            ItemType::Local(LocalItem::StringIndex { index }) => {
-                update_stack!(stack, locals, string_index, index);
+            r => panic!("Expected InvalidData, got {r:?}"),
+        }
+    }
+
+    #[test]
+    fn clone_descriptor() {
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        let mut copy = rdesc.clone();
+        assert_eq!(copy.len(), rdesc.len());
+        assert!(copy.diff(&rdesc).is_empty());
+
+        copy.set_report_id(Direction::Input, Some(ReportId(2)), Some(ReportId(3)))
+            .unwrap();
+        copy.feature_reports.clear();
+        assert_eq!(rdesc.input_reports[1].report_id(), &Some(ReportId(2)));
+        assert_eq!(copy.input_reports[1].report_id(), &Some(ReportId(3)));
+        assert!(!copy.diff(&rdesc).is_empty());
+    }
+
+    #[test]
+    fn fuzz_regressions() {
+        // Usage Minimum 0 to Usage Maximum 0xffffff
+        let bytes: &[u8] = &[
+            0x05, 0x01, 0x09, 0x02, 0xa1, 0x01, 0x19, 0x00, 0x2b, 0xff, 0xff, 0xff, 0x00, 0x75,
+            0x01, 0x95, 0x01, 0x81, 0x00, 0xc0,
+        ];
+        assert!(ReportDescriptor::try_from(bytes).is_err());
+
+        // Report Count 0xffffff
+        let bytes: &[u8] = &[
+            0x05, 0x01, 0x09, 0x02, 0xa1, 0x01, 0x09, 0x30, 0x75, 0x01, 0x97, 0xff, 0xff, 0xff,
+            0x00, 0x81, 0x02, 0xc0,
+        ];
+        assert!(ReportDescriptor::try_from(bytes).is_err());
+
+        // Report Size 104, too big to extract
+        let bytes: &[u8] = &[
+            0x06, 0x00, 0xff, 0x09, 0x01, 0xa1, 0x01, 0x85, 0x08, 0x75, 0x68, 0x95, 0x01, 0x09,
+            0x01, 0x81, 0x02, 0xc0,
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        let report = &rdesc.input_reports()[0];
+        let mut data = vec![0u8; report.size_in_bytes()];
+        data[0] = 0x08;
+        let Field::Variable(field) = &report.fields()[0] else {
+            panic!("Expected a variable field");
+        };
+        assert!(matches!(
+            field.extract(&data),
+            Err(ParserError::InvalidReport { .. })
+        ));
+        assert!(matches!(field.extract(&[]), Err(ParserError::OutOfBounds)));
+        assert!(report.decode(&data).is_err());
+
+        // Unit with the reserved Unit System 5
+        let bytes: &[u8] = &[
+            0x05, 0x01, 0x09, 0x02, 0xa1, 0x01, 0x09, 0x30, 0x65, 0x15, 0x75, 0x08, 0x95, 0x01,
+            0x81, 0x02, 0xc0,
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        let _ = format!("{rdesc}");
+        let Field::Variable(field) = &rdesc.input_reports()[0].fields()[0] else {
+            panic!("Expected a variable field");
+        };
+        assert!(matches!(field.unit.unwrap().system(), UnitSystem::None));
+    }
+
+    #[test]
+    fn logical_from_physical() {
+        // A -180..=180 degree rotation in 0..=3600 counts
+        let mut rz = VariableField::new(
+            usage(0x01, 0x35),
+            0..16,
+            LogicalMinimum(0),
+            LogicalMaximum(3600),
+        );
+        rz.physical_minimum = Some(PhysicalMinimum(-180));
+        rz.physical_maximum = Some(PhysicalMaximum(180));
+        rz.unit = Some(Unit(0x14)); // Degrees
+        assert_eq!(rz.logical_from_physical(180.0), 3600);
+        assert_eq!(rz.logical_from_physical(0.0), 1800);
+        assert_eq!(rz.logical_from_physical(-180.0), 0);
+        assert_eq!(rz.logical_from_physical(90.04), 2700);
+        assert_eq!(rz.logical_from_physical(360.0), 3600);
+        for value in [0, 1, 1799, 2700, 3600] {
+            let physical = rz.physical_value(value).unwrap();
+            assert_eq!(rz.logical_from_physical(physical), value);
+        }
+
+        // Unit exponent -2, i.e. the physical range is -1.0..=1.0
+        let mut x = VariableField::new(
+            usage(0x01, 0x30),
+            0..16,
+            LogicalMinimum(-1000),
+            LogicalMaximum(1000),
+        );
+        assert_eq!(x.logical_from_physical(-12.4), -12);
+        x.physical_minimum = Some(PhysicalMinimum(-100));
+        x.physical_maximum = Some(PhysicalMaximum(100));
+        x.unit_exponent = Some(UnitExponent(0x0e));
+        assert_eq!(x.logical_from_physical(0.5), 500);
+        assert_eq!(x.logical_from_physical(-1.0), -1000);
+    }
+
+    #[test]
+    fn usage_pages() {
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        assert_eq!(rdesc.usage_pages(), vec![pages::KEYBOARD, pages::LED]);
+
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        assert_eq!(
+            rdesc.usage_pages(),
+            vec![
+                pages::GENERIC_DESKTOP,
+                pages::GENERIC_DEVICE_CONTROLS,
+                pages::BUTTON
+            ]
+        );
+    }
+
+    #[test]
+    fn unit_display() {
+        let mut x = VariableField::new(
+            usage(0x0d, 0x48),
+            0..16,
+            LogicalMinimum(0),
+            LogicalMaximum(4000),
+        );
+        assert_eq!(x.unit_display(1230.0), None);
+        x.unit = Some(Unit(0x11)); // Centimeter
+        assert_eq!(x.unit_display(1230.0).unwrap(), "1230 cm");
+        x.unit_exponent = Some(UnitExponent(0x0e)); // -2
+        assert_eq!(x.unit_display(12.3).unwrap(), "12.30 cm");
+        x.unit_exponent = Some(UnitExponent(0x01));
+        assert_eq!(x.unit_display(30.0).unwrap(), "30 cm");
+
+        // A logical 500 is physical 1000, i.e. 10 cm
+        let mut x = VariableField::new(
+            usage(0x01, 0x30),
+            0..16,
+            LogicalMinimum(0),
+            LogicalMaximum(1000),
+        );
+        x.physical_minimum = Some(PhysicalMinimum(0));
+        x.physical_maximum = Some(PhysicalMaximum(2000));
+        x.unit = Some(Unit(0x11)); // Centimeter
+        x.unit_exponent = Some(UnitExponent(0x0e)); // -2
+        let physical = x.physical_value(500).unwrap();
+        assert_eq!(x.unit_display(physical).unwrap(), "10.00 cm");
+    }
+
+    #[test]
+    fn from_items() {
+        for bytes in [BOOT_KEYBOARD, BATTERY_MOUSE, WHEEL_MOUSE, KEYBOARD_CONSUMER] {
+            let items = hid::parse_items(bytes).unwrap();
+            let rdesc = ReportDescriptor::from_items(&items).unwrap();
+            let expected = ReportDescriptor::try_from(bytes).unwrap();
+            assert_eq!(rdesc.len(), expected.len());
+            assert!(rdesc.diff(&expected).is_empty());
+            assert_eq!(format!("{rdesc}"), format!("{expected}"));
+        }
+    }
+
+    #[test]
+    fn to_bytes_keeps_globals() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x02, // Usage (Mouse)
+            0xa1, 0x01, // Collection (Application)
+            0x85, 0x01, //  Report ID (1)
+            0x05, 0x09, //  Usage Page (Button)
+            0x19, 0x01, //  Usage Minimum (1)
+            0x29, 0x03, //  Usage Maximum (3)
+            0x15, 0x00, //  Logical Minimum (0)
+            0x25, 0x01, //  Logical Maximum (1)
+            0x75, 0x01, //  Report Size (1)
+            0x95, 0x03, //  Report Count (3)
+            0x81, 0x02, //  Input (Data,Var,Abs)
+            0x95, 0x05, //  Report Count (5)
+            0x81, 0x01, //  Input (Cnst,Arr,Abs)
+            0xc0, // End Collection
+            0x05, 0x0c, // Usage Page (Consumer)
+            0x09, 0x01, // Usage (Consumer Control)
+            0xa1, 0x01, // Collection (Application)
+            0x85, 0x02, //  Report ID (2)
+            0x19, 0x00, //  Usage Minimum (0)
+            0x2a, 0xff, 0x03, //  Usage Maximum (1023)
+            0x26, 0xff, 0x03, //  Logical Maximum (1023)
+            0x75, 0x0a, //  Report Size (10)
+            0x95, 0x01, //  Report Count (1)
+            0x81, 0x00, //  Input (Data,Arr,Abs)
+            0x75, 0x01, //  Report Size (1)
+            0x95, 0x06, //  Report Count (6)
+            0x81, 0x01, //  Input (Cnst,Arr,Abs)
+            0xc0, // End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        let out = rdesc.to_bytes();
+        let copy = ReportDescriptor::try_from(&out).unwrap();
+        assert_equivalent(&rdesc, &copy);
+        assert_eq!(copy.to_bytes(), out);
+
+        let items = hid::parse_items(&out).unwrap();
+        let usage_pages: Vec<u16> = items
+            .iter()
+            .filter_map(|i| match i.item().item_type() {
+                ItemType::Global(GlobalItem::UsagePage { usage_page }) => Some(usage_page.0),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(usage_pages, vec![0x01, 0x09, 0x0c]);
+        // The padding is still Report Size 1 with Report Count 5 and 6
+        let padding: Vec<(usize, usize)> = copy
+            .input_reports
+            .iter()
+            .flat_map(|r| r.fields())
+            .filter_map(|f| match f {
+                Field::Constant(c) => Some((
+                    c.globals.report_size.unwrap().0,
+                    c.globals.report_count.unwrap().0,
+                )),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(padding, vec![(1, 5), (1, 6)]);
+    }
+
+    #[test]
+    fn string_range() {
+        let bytes: &[u8] = &[
+            0x05, 0x0c, // Usage Page (Consumer)
+            0x09, 0x01, // Usage (Consumer Control)
+            0xa1, 0x01, // Collection (Application)
+            0x19, 0xb5, //  Usage Minimum (0xb5)
+            0x29, 0xb7, //  Usage Maximum (0xb7)
+            0x89, 0x10, //  String Minimum (16)
+            0x99, 0x12, //  String Maximum (18)
+            0x15, 0x01, //  Logical Minimum (1)
+            0x25, 0x03, //  Logical Maximum (3)
+            0x75, 0x02, //  Report Size (2)
+            0x95, 0x01, //  Report Count (1)
+            0x81, 0x00, //  Input (Data,Arr,Abs)
+            0x19, 0xb5, //  Usage Minimum (0xb5)
+            0x29, 0xb7, //  Usage Maximum (0xb7)
+            0x81, 0x00, //  Input (Data,Arr,Abs)
+            0x95, 0x04, //  Report Count (4)
+            0x81, 0x01, //  Input (Cnst,Arr,Abs)
+            0xc0, // End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        let fields = rdesc.input_reports()[0].fields();
+        let Field::Array(first) = &fields[0] else {
+            panic!("Expected an array field");
+        };
+        assert_eq!(
+            first.string_range(),
+            Some(StringIndex(16)..=StringIndex(18))
+        );
+        // String Minimum/Maximum are local items
+        let Field::Array(second) = &fields[1] else {
+            panic!("Expected an array field");
+        };
+        assert_eq!(second.string_range(), None);
+
+        let copy = ReportDescriptor::try_from(&rdesc.to_bytes()).unwrap();
+        let Field::Array(first) = &copy.input_reports()[0].fields()[0] else {
+            panic!("Expected an array field");
+        };
+        assert_eq!(
+            first.string_range(),
+            Some(StringIndex(16)..=StringIndex(18))
+        );
+    }
+
+    #[test]
+    fn is_boot_keyboard_and_mouse() {
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        assert!(rdesc.is_boot_keyboard());
+        assert!(!rdesc.is_boot_mouse());
+
+        let rdesc = ReportDescriptor::try_from(WHEEL_MOUSE).unwrap();
+        assert!(rdesc.is_boot_mouse());
+        assert!(!rdesc.is_boot_keyboard());
+
+        // The reserved byte is a data field instead of a constant
+        let mut bytes = BOOT_KEYBOARD.to_vec();
+        let reserved = bytes.windows(2).position(|w| w == [0x81, 0x01]).unwrap();
+        bytes.splice(reserved..reserved + 2, [0x09, 0x01, 0x81, 0x02]);
+        let rdesc = ReportDescriptor::try_from(&bytes).unwrap();
+        assert!(!rdesc.is_boot_keyboard());
+
+        // Keyboard in a report with a Report ID
+        let rdesc = ReportDescriptor::try_from(KEYBOARD_CONSUMER).unwrap();
+        assert!(!rdesc.is_boot_keyboard());
+        // Mouse with a Report ID
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        assert!(!rdesc.is_boot_mouse());
+    }
+
+    #[test]
+    fn huge_report_size_and_count() {
+        for (size, count) in [
+            (&[0x75, 0x20][..], &[0x96, 0xff, 0xff][..]),
+            (
+                &[0x77, 0xff, 0xff, 0xff, 0xff],
+                &[0x97, 0xff, 0xff, 0xff, 0xff],
+            ),
+            (&[0x77, 0xff, 0xff, 0xff, 0x7f], &[0x95, 0x02]),
+        ] {
+            // Data,Var and Cnst and Data,Arr
+            for flags in [0x02, 0x01, 0x00] {
+                let mut bytes = vec![0x05, 0x01, 0x09, 0x02, 0xa1, 0x01, 0x09, 0x30];
+                bytes.extend_from_slice(size);
+                bytes.extend_from_slice(count);
+                bytes.extend_from_slice(&[0x81, flags, 0xc0]);
+                assert!(ReportDescriptor::try_from(&bytes).is_err());
             }
-            ItemType::Local(LocalItem::StringMinimum { minimum }) => {
-                update_stack!(stack, locals, string_minimum, minimum);
+        }
+    }
+
+    #[test]
+    fn total_input_bits() {
+        // 24 bits mouse and 8 bits battery
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        assert_eq!(rdesc.total_input_bits(), 32);
+
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        assert_eq!(rdesc.total_input_bits(), 64);
+    }
+
+    #[test]
+    fn usage_tuple() {
+        let rdesc = ReportDescriptor::try_from(WHEEL_MOUSE).unwrap();
+        let Field::Variable(x) = &rdesc.input_reports[0].fields()[4] else {
+            panic!("Expected a variable field");
+        };
+        assert_eq!(x.usage, (0x01, 0x30));
+        assert_ne!(x.usage, (0x01, 0x31));
+        assert_ne!(x.usage, (0x09, 0x30));
+        assert_eq!(Usage::from((0x01, 0x30)), x.usage);
+        assert_eq!(Usage::from((0x0c, 0x238)), pages::usages::AC_PAN);
+    }
+
+    #[test]
+    fn reports_and_fields() {
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        let layout: Vec<(Direction, Vec<usize>)> = rdesc
+            .reports()
+            .map(|r| {
+                (
+                    r.direction(),
+                    r.fields().iter().map(|f| f.bits().len()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            layout,
+            vec![
+                (Direction::Input, vec![1, 1, 1, 1, 1, 1, 1, 1, 8, 48]),
+                (Direction::Output, vec![1, 1, 1, 1, 1, 3]),
+            ]
+        );
+        for report in rdesc.reports() {
+            for field in report.fields() {
+                assert!(field.bits().end <= report.size_in_bits());
             }
-            ItemType::Local(LocalItem::StringMaximum { maximum }) => {
-                update_stack!(stack, locals, string_maximum, maximum);
+        }
+    }
+
+    #[test]
+    fn consumer_usages() {
+        let rdesc = ReportDescriptor::try_from(KEYBOARD_CONSUMER).unwrap();
+        let report = rdesc.find_input_report(&[0x02]).unwrap();
+        let Field::Array(array) = &report.fields()[0] else {
+            panic!("Expected an array field");
+        };
+        // Usage Maximum 0x3ff is a 2-byte item
+        assert_eq!(array.usages().len(), 0x400);
+        assert_eq!(array.usages().last(), Some(Usage::from((0x0c, 0x3ff))));
+
+        let bytes = [0x02, 0xe9, 0x00];
+        assert_eq!(
+            report.decode(&bytes).unwrap(),
+            vec![(Usage::from((0x0c, 0xe9)), 1)]
+        );
+        assert!(report
+            .explain(&bytes)
+            .unwrap()
+            .contains("Consumer/Volume Increment"));
+        assert_eq!(
+            names::usage_name(&Usage::from((0x0c, 0x238))).unwrap(),
+            "AC Pan"
+        );
+    }
+
+    #[test]
+    fn fido() {
+        let bytes: &[u8] = &[
+            0x06, 0xd0, 0xf1, // Usage Page (FIDO Alliance)
+            0x09, 0x01, // Usage (U2F Authenticator Device)
+            0xa1, 0x01, // Collection (Application)
+            0x09, 0x20, //  Usage (Input Report Data)
+            0x15, 0x00, //  Logical Minimum (0)
+            0x26, 0xff, 0x00, //  Logical Maximum (255)
+            0x75, 0x08, //  Report Size (8)
+            0x95, 0x40, //  Report Count (64)
+            0x81, 0x02, //  Input (Data,Var,Abs)
+            0x09, 0x21, //  Usage (Output Report Data)
+            0x15, 0x00, //  Logical Minimum (0)
+            0x26, 0xff, 0x00, //  Logical Maximum (255)
+            0x75, 0x08, //  Report Size (8)
+            0x95, 0x40, //  Report Count (64)
+            0x91, 0x02, //  Output (Data,Var,Abs)
+            0xc0, // End Collection
+            0x06, 0x00, 0xff, // Usage Page (Vendor Defined 0xff00)
+            0x09, 0x01, // Usage (0x01)
+            0xa1, 0x01, // Collection (Application)
+            0x85, 0x05, //  Report ID (5)
+            0x09, 0x02, //  Usage (0x02)
+            0x75, 0x08, //  Report Size (8)
+            0x95, 0x02, //  Report Count (2)
+            0xb1, 0x02, //  Feature (Data,Var,Abs)
+            0xc0, // End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        assert!(rdesc.is_fido());
+        assert_eq!(rdesc.vendor_usage_pages(), vec![UsagePage(0xff00)]);
+        assert_eq!(
+            rdesc.usage_pages(),
+            vec![pages::FIDO_ALLIANCE, UsagePage(0xff00)]
+        );
+
+        let rdesc = ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap();
+        assert!(!rdesc.is_fido());
+        assert!(rdesc.vendor_usage_pages().is_empty());
+    }
+
+    #[test]
+    fn extract_all_gamepad() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x05, // Usage (Gamepad)
+            0xa1, 0x01, // Collection (Application)
+            0x05, 0x09, //  Usage Page (Button)
+            0x19, 0x01, //  Usage Minimum (1)
+            0x29, 0x04, //  Usage Maximum (4)
+            0x15, 0x00, //  Logical Minimum (0)
+            0x25, 0x01, //  Logical Maximum (1)
+            0x75, 0x01, //  Report Size (1)
+            0x95, 0x04, //  Report Count (4)
+            0x81, 0x02, //  Input (Data,Var,Abs)
+            0x95, 0x04, //  Report Count (4)
+            0x81, 0x03, //  Input (Cnst,Var,Abs)
+            0x05, 0x01, //  Usage Page (Generic Desktop)
+            0x09, 0x30, //  Usage (X)
+            0x09, 0x31, //  Usage (Y)
+            0x09, 0x30, //  Usage (X)
+            0x15, 0x81, //  Logical Minimum (-127)
+            0x25, 0x7f, //  Logical Maximum (127)
+            0x75, 0x08, //  Report Size (8)
+            0x95, 0x03, //  Report Count (3)
+            0x81, 0x02, //  Input (Data,Var,Abs)
+            0x05, 0x09, //  Usage Page (Button)
+            0x19, 0x05, //  Usage Minimum (5)
+            0x29, 0x08, //  Usage Maximum (8)
+            0x15, 0x05, //  Logical Minimum (5)
+            0x25, 0x08, //  Logical Maximum (8)
+            0x95, 0x02, //  Report Count (2)
+            0x81, 0x00, //  Input (Data,Arr,Abs)
+            0xc0, // End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        let report = rdesc.input_reports().first().unwrap();
+
+        let values = report
+            .extract_all(&[0b0101, 0xff, 0x10, 0x20, 0x07, 0x07])
+            .unwrap();
+        assert_eq!(values.len(), 7);
+        assert_eq!(values[&usage(0x09, 1)], 1);
+        assert_eq!(values[&usage(0x09, 2)], 0);
+        assert_eq!(values[&usage(0x09, 3)], 1);
+        assert_eq!(values[&usage(0x09, 4)], 0);
+        assert_eq!(values[&pages::usages::X], -1);
+        assert_eq!(values[&pages::usages::Y], 16);
+        assert_eq!(values[&usage(0x09, 7)], 1);
+
+        let values = report.extract_all(&[0, 0, 0, 0, 0, 0]).unwrap();
+        assert_eq!(values.len(), 6);
+        assert!(!values.contains_key(&usage(0x09, 5)));
+    }
+
+    #[test]
+    fn unbalanced_collections() {
+        // One EndCollection too many
+        let mut bytes = WHEEL_MOUSE.to_vec();
+        bytes.push(0xc0);
+        match ReportDescriptor::try_from(&bytes) {
+            Err(ParserError::InvalidData { offset, message }) => {
+                assert_eq!(offset, WHEEL_MOUSE.len());
+                assert_eq!(message, "Too many EndCollection");
             }
-            ItemType::Local(LocalItem::Delimiter { delimiter }) => {
-                update_stack!(stack, locals, delimiter, delimiter);
+            r => panic!("Unexpected result {r:?}"),
+        }
+
+        // The Application collection is never closed
+        let bytes = &WHEEL_MOUSE[..WHEEL_MOUSE.len() - 1];
+        let offset = WHEEL_MOUSE
+            .windows(2)
+            .position(|w| w == [0xa1, 0x01])
+            .unwrap();
+        match ReportDescriptor::try_from(bytes) {
+            Err(ParserError::InvalidData { offset: o, message }) => {
+                assert_eq!(o, offset);
+                assert_eq!(message, "Collection without EndCollection");
             }
-            ItemType::Local(LocalItem::Reserved { value: _ }) => {}
+            r => panic!("Unexpected result {r:?}"),
+        }
+    }
+
+    #[test]
+    fn report_id_zero_is_no_report_id() {
+        assert!(ReportId::try_from(0).is_err());
+        assert_eq!(ReportId::try_from(2).ok(), Some(ReportId(2)));
+        assert_eq!(u8::from(ReportId(2)), 2);
+
+        // A report with a Report ID never matches a first byte of 0
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        assert!(rdesc.find_input_report(&[0, 0, 0, 0, 0]).is_none());
+        assert!(rdesc.find_input_report(&[2, 0, 0, 0, 0]).is_some());
+
+        // Including a report that uses the reserved Report ID 0
+        let mut bytes = BATTERY_MOUSE.to_vec();
+        let idx = bytes.windows(2).position(|w| w == [0x85, 0x02]).unwrap();
+        bytes[idx + 1] = 0x00;
+        let rdesc = ReportDescriptor::try_from(&bytes).unwrap();
+        assert!(rdesc.find_input_report(&[0, 100]).is_none());
+        assert!(matches!(
+            rdesc.decode_input(&[0, 100]),
+            Err(ParserError::MismatchingReportId)
+        ));
+        assert!(matches!(
+            rdesc.value_for_usage(usage(0x06, 0x20), &[0, 100]),
+            Err(ParserError::MismatchingReportId)
+        ));
+
+        let mut report = ReportBuilder::new(ReportId::try_from(3).ok())
+            .add_constant(8)
+            .build();
+        assert_eq!(report.size_in_bytes(), 2);
+        report.set_id(ReportId::try_from(0).ok());
+        assert_eq!(report.report_id(), &None);
+        assert_eq!(report.size_in_bytes(), 1);
+    }
+
+    #[test]
+    fn boot_descriptors() {
+        let rdesc = ReportDescriptor::boot_keyboard();
+        assert!(rdesc.is_boot_keyboard());
+        assert!(!rdesc.is_boot_mouse());
+        assert_equivalent(&rdesc, &ReportDescriptor::try_from(BOOT_KEYBOARD).unwrap());
+
+        // Left Shift with 'a' and 'b' pressed
+        let report = rdesc.find_input_report(&[0; 8]).unwrap();
+        let values = report
+            .decode(&[0b0000_0010, 0, 0x04, 0x05, 0, 0, 0, 0])
+            .unwrap();
+        assert!(values.contains(&(usage(0x07, 0xe1), 1)));
+        assert!(values.contains(&(usage(0x07, 0xe0), 0)));
+        assert!(values.contains(&(usage(0x07, 0x04), 1)));
+        assert!(values.contains(&(usage(0x07, 0x05), 1)));
+        assert_eq!(rdesc.output_reports()[0].size_in_bits(), 8);
+
+        let rdesc = ReportDescriptor::boot_mouse();
+        assert!(rdesc.is_boot_mouse());
+        assert!(!rdesc.is_boot_keyboard());
+        let report = rdesc.find_input_report(&[0; 3]).unwrap();
+        let values = report.extract_all(&[0b001, 0x05, 0xfe]).unwrap();
+        assert_eq!(values[&usage(0x09, 1)], 1);
+        assert_eq!(values[&usage(0x09, 2)], 0);
+        assert_eq!(values[&pages::usages::X], 5);
+        assert_eq!(values[&pages::usages::Y], -2);
+    }
+
+    #[test]
+    fn designator_range() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x05, // Usage (Gamepad)
+            0xa1, 0x01, // Collection (Application)
+            0x05, 0x09, // Usage Page (Button)
+            0x19, 0x01, // Usage Minimum (1)
+            0x29, 0x04, // Usage Maximum (4)
+            0x49, 0x05, // Designator Minimum (5)
+            0x59, 0x07, // Designator Maximum (7)
+            0x15, 0x00, // Logical Minimum (0)
+            0x25, 0x01, // Logical Maximum (1)
+            0x75, 0x01, // Report Size (1)
+            0x95, 0x04, // Report Count (4)
+            0x81, 0x02, // Input (Data,Var,Abs)
+            0x19, 0x05, // Usage Minimum (5)
+            0x29, 0x08, // Usage Maximum (8)
+            0x49, 0x01, // Designator Minimum (1)
+            0x59, 0x04, // Designator Maximum (4)
+            0x15, 0x05, // Logical Minimum (5)
+            0x25, 0x08, // Logical Maximum (8)
+            0x75, 0x04, // Report Size (4)
+            0x95, 0x01, // Report Count (1)
+            0x81, 0x00, // Input (Data,Arr,Abs)
+            0xc0, // End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        let report = rdesc.input_reports().first().unwrap();
+        let designators: Vec<Option<DesignatorIndex>> = report
+            .fields()
+            .iter()
+            .filter_map(|f| match f {
+                Field::Variable(v) => Some(v.designator()),
+                _ => None,
+            })
+            .collect();
+        // The last designator repeats for the fourth button
+        assert_eq!(
+            designators,
+            vec![
+                Some(DesignatorIndex(5)),
+                Some(DesignatorIndex(6)),
+                Some(DesignatorIndex(7)),
+                Some(DesignatorIndex(7))
+            ]
+        );
+
+        let Field::Array(array) = &report.fields()[4] else {
+            panic!("Expected an array field");
         };
+        assert_eq!(array.designator(), None);
+        assert_eq!(
+            array.designator_range(),
+            Some(DesignatorIndex(1)..=DesignatorIndex(4))
+        );
+
+        let copy = ReportDescriptor::try_from(&rdesc.to_bytes()).unwrap();
+        assert_equivalent(&rdesc, &copy);
     }
 
-    Ok(rdesc)
-}
+    #[test]
+    fn coalesce_padding() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x05, // Usage (Gamepad)
+            0xa1, 0x01, // Collection (Application)
+            0x05, 0x09, // Usage Page (Button)
+            0x19, 0x01, // Usage Minimum (1)
+            0x29, 0x04, // Usage Maximum (4)
+            0x15, 0x00, // Logical Minimum (0)
+            0x25, 0x01, // Logical Maximum (1)
+            0x75, 0x01, // Report Size (1)
+            0x95, 0x04, // Report Count (4)
+            0x81, 0x02, // Input (Data,Var,Abs)
+            0x95, 0x01, // Report Count (1)
+            0x81, 0x01, // Input (Cnst,Arr,Abs)
+            0x81, 0x01, // Input (Cnst,Arr,Abs)
+            0x81, 0x01, // Input (Cnst,Arr,Abs)
+            0x81, 0x01, // Input (Cnst,Arr,Abs)
+            0xc0, // End Collection
+        ];
+        let mut rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        assert_eq!(rdesc.input_reports()[0].fields().len(), 8);
+
+        rdesc.coalesce_padding();
+        let report = &rdesc.input_reports()[0];
+        assert_eq!(report.fields().len(), 5);
+        let Field::Constant(padding) = &report.fields()[4] else {
+            panic!("Expected a constant field");
+        };
+        assert_eq!(padding.bits, 4..8);
+        assert_eq!(report.size_in_bits(), 8);
+        report.validate().unwrap();
+        assert_eq!(report.decode(&[0x0f]).unwrap().len(), 4);
+
+        // The coalesced padding is a single main item
+        let copy = ReportDescriptor::try_from(&rdesc.to_bytes()).unwrap();
+        assert_equivalent(&rdesc, &copy);
+        assert_eq!(copy.input_reports()[0].fields().len(), 5);
+
+        // Nothing left to merge
+        rdesc.coalesce_padding();
+        assert_eq!(rdesc.input_reports()[0].fields().len(), 5);
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[cfg(feature = "std")]
+    #[test]
+    fn parser_error_into_io_error() {
+        let e = ReportDescriptor::try_from(&[0xc0u8][..]).unwrap_err();
+        let message = e.to_string();
+        let e = std::io::Error::from(e);
+        assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(e.to_string(), message);
+        assert!(e.get_ref().unwrap().is::<ParserError>());
+
+        let e = ParserError::from(std::io::Error::from(std::io::ErrorKind::NotFound));
+        let e = std::io::Error::from(e);
+        assert_eq!(e.kind(), std::io::ErrorKind::NotFound);
+    }
 
     #[test]
-    fn test_twos_comp() {
-        assert_eq!(5u8.twos_comp(3), -3);
-        assert_eq!(5u8.twos_comp(4), 5);
-        assert_eq!(0xffu8.twos_comp(8), -1);
+    fn applications() {
+        let rdesc = ReportDescriptor::try_from(KEYBOARD_CONSUMER).unwrap();
+        let applications = rdesc.applications();
+        assert_eq!(applications.len(), 2);
+
+        let keyboard = &applications[0];
+        assert_eq!(keyboard.usage, pages::usages::KEYBOARD);
+        assert_eq!(keyboard.directions, vec![Direction::Input]);
+        assert_eq!(keyboard.report_ids, vec![ReportId(1)]);
+
+        let consumer = &applications[1];
+        assert_eq!(consumer.usage, pages::usages::CONSUMER_CONTROL);
+        assert_eq!(consumer.directions, vec![Direction::Input]);
+        assert_eq!(consumer.report_ids, vec![ReportId(2)]);
+
+        let rdesc = ReportDescriptor::boot_keyboard();
+        let applications = rdesc.applications();
+        assert_eq!(applications.len(), 1);
+        assert_eq!(
+            applications[0].directions,
+            vec![Direction::Input, Direction::Output]
+        );
+        assert!(applications[0].report_ids.is_empty());
+    }
 
-        assert_eq!(5u16.twos_comp(3), -3);
-        assert_eq!(5u16.twos_comp(4), 5);
-        assert_eq!(0xffffu16.twos_comp(16), -1);
+    #[test]
+    fn array_elements() {
+        let rdesc = ReportDescriptor::boot_keyboard();
+        let report = rdesc.input_reports().first().unwrap();
+        let Some(Field::Array(keys)) = report.fields().last() else {
+            panic!("Expected an array field");
+        };
+        assert_eq!(keys.element_count(), 6);
+        assert_eq!(keys.element_bits(), 8);
+        assert_eq!(keys.bits, 16..64);
+        assert_eq!(keys.usages().len(), 0x66);
+
+        // 'a', 'b' and 'c', the remaining elements are unused
+        let bytes = [0, 0, 0x04, 0x05, 0x06, 0, 0, 0];
+        assert_eq!(keys.extract_u32(&bytes).unwrap(), vec![4, 5, 6, 0, 0, 0]);
+        // The modifiers are the first 8 values, the unused elements
+        // are not decoded
+        let values = report.decode(&bytes).unwrap();
+        assert_eq!(values.len(), 8 + 3);
+        assert_eq!(
+            values[8..],
+            [
+                (usage(0x07, 0x04), 1),
+                (usage(0x07, 0x05), 1),
+                (usage(0x07, 0x06), 1)
+            ]
+        );
+    }
 
-        assert_eq!(5u32.twos_comp(3), -3);
-        assert_eq!(5u32.twos_comp(4), 5);
-        assert_eq!(0xffffffffu32.twos_comp(32), -1);
+    #[test]
+    fn large_report_descriptor() {
+        let mut bytes: Vec<u8> = vec![
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x02, // Usage (Mouse)
+            0xa1, 0x01, // Collection (Application)
+        ];
+        // Pad the report descriptor to well beyond 64KB
+        for _ in 0..40000 {
+            bytes.extend([0x05, 0x09]); // Usage Page (Button)
+        }
+        let physical = bytes.len();
+        bytes.extend([
+            0x05, 0x01, //  Usage Page (Generic Desktop)
+            0x09, 0x01, //  Usage (Pointer)
+            0xa1, 0x00, //  Collection (Physical)
+            0x09, 0x30, //   Usage (X)
+            0x15, 0x81, //   Logical Minimum (-127)
+            0x25, 0x7f, //   Logical Maximum (127)
+            0x75, 0x08, //   Report Size (8)
+            0x95, 0x01, //   Report Count (1)
+            0x81, 0x06, //   Input (Data,Var,Rel)
+            0xc0, //  End Collection
+            0xc0, // End Collection
+        ]);
+        assert!(physical > 0x10000);
+
+        let rdesc = ReportDescriptor::try_from(&bytes).unwrap();
+        let field = &rdesc.input_reports()[0].fields()[0];
+        let collections = field.collections();
+        assert_eq!(collections[0].id(), &CollectionId(4));
+        assert_eq!(collections[1].id(), &CollectionId(physical + 4));
+        assert_eq!(field.bits(), &(0..8));
+
+        // Errors have the full offset
+        bytes.push(0xc0);
+        match ReportDescriptor::try_from(&bytes) {
+            Err(ParserError::InvalidData { offset, .. }) => assert_eq!(offset, bytes.len() - 1),
+            r => panic!("Unexpected result {r:?}"),
+        }
     }
 
     #[test]
-    fn extract() {
-        let bytes: [u8; 4] = [0b1100_1010, 0b1011_1001, 0b10010110, 0b00010101];
+    fn variable_and_array_fields() {
+        let rdesc = ReportDescriptor::boot_mouse();
+        let report = rdesc.input_reports().first().unwrap();
+        assert_eq!(report.fields().len(), 6);
+        assert_eq!(report.variable_fields().count(), 5);
+        assert_eq!(report.array_fields().count(), 0);
+        let usages: Vec<Usage> = report.variable_fields().map(|v| v.usage).collect();
+        assert_eq!(
+            usages,
+            vec![
+                usage(0x09, 1),
+                usage(0x09, 2),
+                usage(0x09, 3),
+                pages::usages::X,
+                pages::usages::Y
+            ]
+        );
 
-        assert_eq!(0, extract_u8(&bytes, &(0..1)));
-        assert_eq!(2, extract_u8(&bytes, &(0..2)));
-        assert_eq!(10, extract_u8(&bytes, &(0..4)));
+        let rdesc = ReportDescriptor::boot_keyboard();
+        let report = rdesc.input_reports().first().unwrap();
+        assert_eq!(report.variable_fields().count(), 8);
+        assert_eq!(report.array_fields().count(), 1);
+    }
 
-        assert_eq!(0, extract_i8(&bytes, &(0..1)));
-        assert_eq!(-2, extract_i8(&bytes, &(0..2)));
-        assert_eq!(-6, extract_i8(&bytes, &(0..4)));
+    #[test]
+    fn parse_options() {
+        // Like the boot mouse but without a Logical Minimum for the buttons
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x02, // Usage (Mouse)
+            0xa1, 0x01, // Collection (Application)
+            0x05, 0x09, // Usage Page (Button)
+            0x19, 0x01, // Usage Minimum (1)
+            0x29, 0x03, // Usage Maximum (3)
+            0x25, 0x01, // Logical Maximum (1)
+            0x95, 0x03, // Report Count (3)
+            0x75, 0x01, // Report Size (1)
+            0x81, 0x02, // Input (Data,Var,Abs)
+            0x95, 0x01, // Report Count (1)
+            0x75, 0x05, // Report Size (5)
+            0x81, 0x01, // Input (Cnst,Arr,Abs)
+            0xc0, // End Collection
+        ];
+
+        let rdesc = ReportDescriptor::parse_with_options(bytes, ParseOptions::default()).unwrap();
+        let Field::Variable(button) = &rdesc.input_reports()[0].fields()[0] else {
+            panic!("Expected a variable field");
+        };
+        assert_eq!(button.logical_minimum, LogicalMinimum(0));
+        assert_eq!(button.logical_maximum, LogicalMaximum(1));
 
-        assert_eq!(0b1001_1100, extract_u8(&bytes, &(4..12)));
-        assert_eq!(0b1001_1100u8 as i8, extract_i8(&bytes, &(4..12)));
+        match ReportDescriptor::parse_with_options(bytes, ParseOptions::default().strict(true)) {
+            Err(ParserError::InvalidData { offset, message }) => {
+                assert_eq!(offset, 18);
+                assert_eq!(message, "Missing Logical Minimum for main item");
+            }
+            r => panic!("Unexpected result {r:?}"),
+        }
 
-        assert_eq!(0, extract_u16(&bytes, &(0..1)));
-        assert_eq!(2, extract_u16(&bytes, &(0..2)));
-        assert_eq!(10, extract_u16(&bytes, &(0..4)));
+        // A valid report descriptor parses in both modes
+        for strict in [false, true] {
+            let options = ParseOptions::default().strict(strict);
+            let rdesc = ReportDescriptor::parse_with_options(BOOT_KEYBOARD, options).unwrap();
+            assert!(rdesc.is_boot_keyboard());
+        }
 
-        assert_eq!(0, extract_i16(&bytes, &(0..1)));
-        assert_eq!(-2, extract_i16(&bytes, &(0..2)));
-        assert_eq!(-6, extract_i16(&bytes, &(0..4)));
+        // Strict mode runs the checks of parse_validated(), e.g. for the
+        // reserved Report ID 0
+        let mut bytes = BATTERY_MOUSE.to_vec();
+        let idx = bytes.windows(2).position(|w| w == [0x85, 0x02]).unwrap();
+        bytes[idx + 1] = 0x00;
+        assert!(ReportDescriptor::parse_with_options(&bytes, ParseOptions::default()).is_ok());
+        match ReportDescriptor::parse_with_options(&bytes, ParseOptions::default().strict(true)) {
+            Err(ParserError::InvalidReport { message }) => {
+                assert_eq!(message, "Input report uses the reserved Report ID 0");
+            }
+            r => panic!("Unexpected result {r:?}"),
+        }
+    }
 
-        assert_eq!(0b0110_1011_1001_1100, extract_u16(&bytes, &(4..20)));
-        assert_eq!(0b0110_1011_1001_1100, extract_i16(&bytes, &(4..20)));
+    #[test]
+    fn array_usage_for_value() {
+        let rdesc = ReportDescriptor::boot_keyboard();
+        let report = rdesc.input_reports().first().unwrap();
+        let keys = report.array_fields().next().unwrap();
+        assert_eq!(keys.usage_for_value(0x04), Some(usage(0x07, 0x04))); // Keyboard A
+        assert_eq!(keys.usage_for_value(0x00), Some(usage(0x07, 0x00)));
+        assert_eq!(keys.usage_for_value(0x65), Some(usage(0x07, 0x65)));
+        assert_eq!(keys.usage_for_value(0x66), None);
+        assert_eq!(keys.usage_for_value(-1), None);
+
+        // Values are relative to the Logical Minimum
+        let bytes: &[u8] = &[
+            0x05, 0x09, // Usage Page (Button)
+            0x19, 0x01, // Usage Minimum (1)
+            0x29, 0x04, // Usage Maximum (4)
+            0x15, 0x05, // Logical Minimum (5)
+            0x25, 0x08, // Logical Maximum (8)
+            0x75, 0x04, // Report Size (4)
+            0x95, 0x02, // Report Count (2)
+            0x81, 0x00, // Input (Data,Arr,Abs)
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        let buttons = rdesc.input_reports()[0].array_fields().next().unwrap();
+        assert_eq!(buttons.usage_for_value(5), Some(usage(0x09, 1)));
+        assert_eq!(buttons.usage_for_value(8), Some(usage(0x09, 4)));
+        assert_eq!(buttons.usage_for_value(4), None);
+    }
+
+    #[test]
+    fn unit_exponent_without_unit() {
+        let mut x = VariableField::new(
+            usage(0x85, 0x66), // Battery System/Remaining Capacity
+            0..8,
+            LogicalMinimum(0),
+            LogicalMaximum(100),
+        );
+        x.unit_exponent = Some(UnitExponent(0x0e)); // -2
+        let physical = x.physical_value(50).unwrap();
+        assert_eq!(physical, 0.5);
+        assert_eq!(x.unit_display(physical).unwrap(), "0.50");
+
+        // Unit 0 is the same as no unit
+        x.unit = Some(Unit(0));
+        let physical = x.physical_value(50).unwrap();
+        assert_eq!(physical, 0.5);
+        assert_eq!(x.unit_display(physical).unwrap(), "0.50");
+
+        x.unit_exponent = None;
+        assert_eq!(x.physical_value(50), None);
+        assert_eq!(x.unit_display(50.0), None);
+    }
+
+    #[test]
+    fn split_input_reports() {
+        let rdesc = ReportDescriptor::try_from(KEYBOARD_CONSUMER).unwrap();
+        let keyboard = [0x01, 0x02, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00];
+        let consumer = [0x02, 0xe9, 0x00];
+        let bytes: Vec<u8> = keyboard.iter().chain(consumer.iter()).copied().collect();
+
+        let reports = rdesc.split_input_reports(&bytes).unwrap();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].0.report_id(), &Some(ReportId(1)));
+        assert_eq!(reports[0].1, keyboard);
+        assert_eq!(reports[1].0.report_id(), &Some(ReportId(2)));
+        assert_eq!(reports[1].1, consumer);
+        assert!(reports[1]
+            .0
+            .decode(reports[1].1)
+            .unwrap()
+            .contains(&(usage(0x0c, 0xe9), 1)));
+
+        assert!(rdesc.split_input_reports(&[]).unwrap().is_empty());
+
+        // A dangling partial report
+        assert!(matches!(
+            rdesc.split_input_reports(&bytes[..bytes.len() - 1]),
+            Err(ParserError::OutOfBounds)
+        ));
+        // An unknown Report ID
+        let mut unknown = bytes.clone();
+        unknown.extend([0x03, 0x00]);
+        assert!(matches!(
+            rdesc.split_input_reports(&unknown),
+            Err(ParserError::MismatchingReportId)
+        ));
+    }
+
+    #[test]
+    fn large_usage_range() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x06, // Usage (Keyboard)
+            0xa1, 0x01, // Collection (Application)
+            0x05, 0x07, //  Usage Page (Keyboard)
+            0x19, 0x00, //  Usage Minimum (0)
+            0x2a, 0xff, 0x00, //  Usage Maximum (255)
+            0x15, 0x00, //  Logical Minimum (0)
+            0x26, 0xff, 0x00, //  Logical Maximum (255)
+            0x75, 0x08, //  Report Size (8)
+            0x95, 0x06, //  Report Count (6)
+            0x81, 0x00, //  Input (Data,Arr,Abs)
+            0xc0, // End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        let report = rdesc.input_reports().first().unwrap();
+        let keys = report.array_fields().next().unwrap();
+        assert_eq!(keys.usages().len(), 256);
+        assert_eq!(keys.element_count(), 6);
+
+        let range = keys.usage_range();
+        assert_eq!(range.minimum().usage_id(), UsageId(0));
+        assert_eq!(range.maximum().usage_id(), UsageId(0xff));
+        assert!(range.lookup_usage(&usage(0x07, 0xe0)).is_some());
+        assert!(range.lookup_usage(&usage(0x07, 0x100)).is_none());
+        assert!(range.lookup_usage(&usage(0x0c, 0xe0)).is_none());
+        assert_eq!(keys.usage_for_value(0xff), Some(usage(0x07, 0xff)));
+
+        // The range is not expanded into a list of usages
+        let usages = keys.usages();
+        assert!(usages.as_slice().is_none());
+        assert_eq!(usages.first(), Some(usage(0x07, 0x00)));
+        assert_eq!(usages.last(), Some(usage(0x07, 0xff)));
+        assert_eq!(usages.position(&usage(0x07, 0xe0)), Some(0xe0));
+        assert!(!usages.contains(&usage(0x0c, 0xe0)));
+        assert_eq!(usages.iter().len(), 256);
+        assert_eq!(usages.iter().nth(4), Some(usage(0x07, 0x04)));
+        assert_eq!(usages.iter().next_back(), Some(usage(0x07, 0xff)));
+
+        // and re-serialized as Usage Minimum/Maximum
+        let copy = ReportDescriptor::try_from(&rdesc.to_bytes()).unwrap();
+        assert_equivalent(&rdesc, &copy);
+        let copy_keys = copy.input_reports()[0].array_fields().next().unwrap();
+        assert!(copy_keys.usages().as_slice().is_none());
+    }
+
+    #[test]
+    fn report_of() {
+        let rdesc = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        let x = rdesc.fields_for_usage(pages::usages::X)[0];
+        let report = rdesc.report_of(x).unwrap();
+        assert_eq!(report.report_id(), &Some(ReportId(1)));
+        assert_eq!(report.direction(), Direction::Input);
+        assert_eq!(report.size_in_bits(), 32);
+
+        let keyboard = ReportDescriptor::boot_keyboard();
+        let capslock = keyboard.fields_for_usage(usage(0x08, 0x02))[0];
+        let report = keyboard.report_of(capslock).unwrap();
+        assert_eq!(report.direction(), Direction::Output);
+        assert_eq!(report.size_in_bytes(), 1);
+
+        // A field from a different report descriptor
+        assert!(rdesc.report_of(capslock).is_none());
+
+        // An identical field from a different report descriptor
+        let copy = ReportDescriptor::try_from(BATTERY_MOUSE).unwrap();
+        let copy_x = copy.fields_for_usage(pages::usages::X)[0];
+        assert_eq!(x.id(), copy_x.id());
+        assert!(rdesc.report_of(copy_x).is_none());
+    }
+
+    #[test]
+    fn decode_sub_byte_feature_fields() {
+        let bytes: &[u8] = &[
+            0x06, 0x00, 0xff, // Usage Page (Vendor Defined 0xff00)
+            0x09, 0x01, // Usage (0x01)
+            0xa1, 0x01, // Collection (Application)
+            0x85, 0x05, //  Report ID (5)
+            0x09, 0x02, //  Usage (0x02)
+            0x15, 0x00, //  Logical Minimum (0)
+            0x25, 0x03, //  Logical Maximum (3)
+            0x75, 0x02, //  Report Size (2)
+            0x95, 0x01, //  Report Count (1)
+            0xb1, 0x02, //  Feature (Data,Var,Abs)
+            0x09, 0x03, //  Usage (0x03)
+            0x25, 0x3f, //  Logical Maximum (63)
+            0x75, 0x06, //  Report Size (6)
+            0xb1, 0x02, //  Feature (Data,Var,Abs)
+            0x09, 0x04, //  Usage (0x04)
+            0x15, 0xe0, //  Logical Minimum (-32)
+            0x25, 0x1f, //  Logical Maximum (31)
+            0xb1, 0x02, //  Feature (Data,Var,Abs)
+            0x09, 0x05, //  Usage (0x05)
+            0x15, 0x00, //  Logical Minimum (0)
+            0x25, 0x03, //  Logical Maximum (3)
+            0x75, 0x02, //  Report Size (2)
+            0xb1, 0x02, //  Feature (Data,Var,Abs)
+            0xc0, // End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        let report = rdesc.feature_reports().first().unwrap();
+        assert_eq!(report.size_in_bytes(), 3);
+        let bits: Vec<Range<usize>> = report.fields().iter().map(|f| f.bits().clone()).collect();
+        assert_eq!(bits, vec![8..10, 10..16, 16..22, 22..24]);
+
+        // 0b101101_10: 2 in the low 2 bits and 45 in the upper 6 bits,
+        // 0b10_111110: -2 in the low 6 bits and 2 in the upper 2 bits
+        let data = [0x05, 0b1011_0110, 0b1011_1110];
+        let values = report.decode(&data).unwrap();
         assert_eq!(
-            0b1_0110_1011_1001_110u16 as i16,
-            extract_i16(&bytes, &(5..21))
+            values,
+            vec![
+                (usage(0xff00, 0x02), 2),
+                (usage(0xff00, 0x03), 45),
+                (usage(0xff00, 0x04), -2),
+                (usage(0xff00, 0x05), 2),
+            ]
         );
+        assert_eq!(report.encode(&values).unwrap(), data);
+    }
 
-        assert_eq!(0b0110_1011_1001_1100, extract_u32(&bytes, &(4..20)));
-        assert_eq!(0b0110_1011_1001_1100, extract_i32(&bytes, &(4..20)));
+    #[test]
+    fn byte_map() {
+        let rdesc = ReportDescriptor::boot_mouse();
+        let report = rdesc.input_reports().first().unwrap();
+        let cells = report.byte_map();
+        assert_eq!(cells.len(), 3);
+        assert!(cells.iter().all(|c| !c.is_report_id));
+
+        // 3 buttons and 5 bits of padding
+        let bits: Vec<Range<usize>> = cells[0].fields.iter().map(|(_, b)| b.clone()).collect();
+        assert_eq!(bits, vec![0..1, 1..2, 2..3, 3..8]);
+        assert!(matches!(cells[0].fields[3].0, Field::Constant(_)));
+
+        for (cell, axis) in cells[1..].iter().zip([pages::usages::X, pages::usages::Y]) {
+            assert_eq!(cell.fields.len(), 1);
+            let (Field::Variable(v), bits) = &cell.fields[0] else {
+                panic!("Expected a variable field");
+            };
+            assert_eq!(v.usage, axis);
+            assert_eq!(bits, &(0..8));
+        }
+
+        // A 16-bit field spanning two bytes after the Report ID
+        let rdesc = ReportDescriptor::try_from(KEYBOARD_CONSUMER).unwrap();
+        let cells = rdesc.input_reports()[1].byte_map();
+        assert_eq!(cells.len(), 3);
+        assert!(cells[0].is_report_id);
+        assert!(cells[0].fields.is_empty());
+        assert_eq!(cells[1].fields[0].1, 0..8);
+        assert_eq!(cells[2].fields[0].1, 0..8);
+        assert_eq!(cells[1].fields[0].0.id(), cells[2].fields[0].0.id());
+    }
+
+    #[test]
+    fn signed_array() {
+        let bytes: &[u8] = &[
+            0x05, 0x09, // Usage Page (Button)
+            0x19, 0x01, // Usage Minimum (1)
+            0x29, 0x05, // Usage Maximum (5)
+            0x15, 0xfe, // Logical Minimum (-2)
+            0x25, 0x02, // Logical Maximum (2)
+            0x75, 0x04, // Report Size (4)
+            0x95, 0x02, // Report Count (2)
+            0x81, 0x00, // Input (Data,Arr,Abs)
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        let report = rdesc.input_reports().first().unwrap();
+        let array = report.array_fields().next().unwrap();
+        assert!(array.is_signed());
+
+        // -1 in the first element, 2 in the second
+        let data = [0x2f];
+        assert_eq!(array.extract_i32(&data).unwrap(), vec![-1, 2]);
+        assert_eq!(array.extract_u32(&data).unwrap(), vec![0xf, 2]);
         assert_eq!(
-            ((0b1_0110_1011_1001_110u16 as i16) as i32),
-            extract_i32(&bytes, &(5..21))
+            report.decode(&data).unwrap(),
+            vec![(usage(0x09, 2), 1), (usage(0x09, 5), 1)]
         );
 
+        // -8 is outside the logical range
+        assert_eq!(report.decode(&[0x08]).unwrap(), vec![(usage(0x09, 3), 1)]);
+    }
+
+    #[test]
+    fn resolution_multiplier() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x02, // Usage (Mouse)
+            0xa1, 0x01, // Collection (Application)
+            0x09, 0x01, //  Usage (Pointer)
+            0xa1, 0x00, //  Collection (Physical)
+            0x05, 0x09, //   Usage Page (Button)
+            0x19, 0x01, //   Usage Minimum (1)
+            0x29, 0x03, //   Usage Maximum (3)
+            0x15, 0x00, //   Logical Minimum (0)
+            0x25, 0x01, //   Logical Maximum (1)
+            0x75, 0x01, //   Report Size (1)
+            0x95, 0x03, //   Report Count (3)
+            0x81, 0x02, //   Input (Data,Var,Abs)
+            0x75, 0x05, //   Report Size (5)
+            0x95, 0x01, //   Report Count (1)
+            0x81, 0x01, //   Input (Cnst)
+            0x05, 0x01, //   Usage Page (Generic Desktop)
+            0x09, 0x30, //   Usage (X)
+            0x09, 0x31, //   Usage (Y)
+            0x15, 0x81, //   Logical Minimum (-127)
+            0x25, 0x7f, //   Logical Maximum (127)
+            0x75, 0x08, //   Report Size (8)
+            0x95, 0x02, //   Report Count (2)
+            0x81, 0x06, //   Input (Data,Var,Rel)
+            0xa1, 0x02, //   Collection (Logical)
+            0x09, 0x48, //    Usage (Resolution Multiplier)
+            0x15, 0x00, //    Logical Minimum (0)
+            0x25, 0x01, //    Logical Maximum (1)
+            0x35, 0x01, //    Physical Minimum (1)
+            0x45, 0x08, //    Physical Maximum (8)
+            0x75, 0x02, //    Report Size (2)
+            0x95, 0x01, //    Report Count (1)
+            0xb1, 0x02, //    Feature (Data,Var,Abs)
+            0x09, 0x38, //    Usage (Wheel)
+            0x15, 0x81, //    Logical Minimum (-127)
+            0x25, 0x7f, //    Logical Maximum (127)
+            0x35, 0x00, //    Physical Minimum (0)
+            0x45, 0x00, //    Physical Maximum (0)
+            0x75, 0x08, //    Report Size (8)
+            0x81, 0x06, //    Input (Data,Var,Rel)
+            0xc0, //         End Collection
+            0xa1, 0x02, //   Collection (Logical)
+            0x09, 0x48, //    Usage (Resolution Multiplier)
+            0x15, 0x00, //    Logical Minimum (0)
+            0x25, 0x01, //    Logical Maximum (1)
+            0x35, 0x01, //    Physical Minimum (1)
+            0x45, 0x08, //    Physical Maximum (8)
+            0x75, 0x02, //    Report Size (2)
+            0xb1, 0x02, //    Feature (Data,Var,Abs)
+            0x05, 0x0c, //    Usage Page (Consumer)
+            0x0a, 0x38, 0x02, // Usage (AC Pan)
+            0x15, 0x81, //    Logical Minimum (-127)
+            0x25, 0x7f, //    Logical Maximum (127)
+            0x35, 0x00, //    Physical Minimum (0)
+            0x45, 0x00, //    Physical Maximum (0)
+            0x75, 0x08, //    Report Size (8)
+            0x81, 0x06, //    Input (Data,Var,Rel)
+            0xc0, //         End Collection
+            0x75, 0x04, //   Report Size (4)
+            0xb1, 0x01, //   Feature (Cnst)
+            0xc0, //        End Collection
+            0xc0, //       End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+
+        let scroll = rdesc.scroll_fields();
+        assert_eq!(scroll.len(), 2);
+        assert_eq!(scroll[0].usage, pages::usages::WHEEL);
+        assert_eq!(scroll[0].bits, 24..32);
+        assert_eq!(scroll[1].usage, pages::usages::AC_PAN);
+        assert_eq!(scroll[1].bits, 32..40);
+
+        // One multiplier per Logical collection, each with its scroll field
+        let multipliers = rdesc.resolution_multipliers();
+        assert_eq!(multipliers.len(), 2);
+        for ((multiplier, fields), (bits, usage)) in multipliers
+            .iter()
+            .zip([(0..2, pages::usages::WHEEL), (2..4, pages::usages::AC_PAN)])
+        {
+            assert_eq!(multiplier.usage, pages::usages::RESOLUTION_MULTIPLIER);
+            assert_eq!(multiplier.bits, bits);
+            assert_eq!(fields.len(), 1);
+            assert_eq!(fields[0].usage, usage);
+        }
         assert_eq!(
-            ((0b1_0110_1011_1001_110u16 as i16) as i32),
-            extract_i32(&bytes, &(5..21))
+            names::usage_name(&multipliers[0].0.usage).as_deref(),
+            Some("Resolution Multiplier")
+        );
+        assert!(core::ptr::eq(
+            rdesc.resolution_multiplier_field().unwrap(),
+            multipliers[0].0
+        ));
+
+        let rdesc = ReportDescriptor::try_from(WHEEL_MOUSE).unwrap();
+        assert!(rdesc.resolution_multiplier_field().is_none());
+        assert!(rdesc.resolution_multipliers().is_empty());
+        assert_eq!(rdesc.scroll_fields().len(), 1);
+    }
+
+    #[test]
+    fn try_from_vec() {
+        let owned = BOOT_KEYBOARD.to_vec();
+        let from_ref = ReportDescriptor::try_from(&owned).unwrap();
+        let from_owned = ReportDescriptor::try_from(owned).unwrap();
+        assert_eq!(from_owned.to_bytes(), from_ref.to_bytes());
+        assert!(from_owned.is_boot_keyboard());
+
+        assert!(ReportDescriptor::try_from(vec![0xc0u8]).is_err());
+    }
+
+    #[test]
+    fn logical_maximum_too_large() {
+        let bytes: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x02, // Usage (Mouse)
+            0xa1, 0x01, // Collection (Application)
+            0x09, 0x30, //  Usage (X)
+            0x15, 0x00, //  Logical Minimum (0)
+            0x26, 0xe8, 0x03, // Logical Maximum (1000)
+            0x75, 0x08, //  Report Size (8)
+            0x95, 0x01, //  Report Count (1)
+            0x81, 0x02, //  Input (Data,Var,Abs)
+            0x09, 0x31, //  Usage (Y)
+            0x15, 0x81, //  Logical Minimum (-127)
+            0x25, 0x7f, //  Logical Maximum (127)
+            0x81, 0x02, //  Input (Data,Var,Abs)
+            0x09, 0x38, //  Usage (Wheel)
+            0x15, 0xff, //  Logical Minimum (-1)
+            0x26, 0xff, 0x00, // Logical Maximum (255)
+            0x81, 0x06, //  Input (Data,Var,Rel)
+            0xc0, //       End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+        // X is 0..=1000 in 8 bits, the Wheel is signed so 255 does not fit
+        let fields = rdesc.input_reports()[0].fields();
+        assert_eq!(
+            rdesc.warnings(),
+            &[
+                ParseWarning::LogicalMaximumTooLarge {
+                    offset: 17,
+                    field: fields[0].id(),
+                },
+                ParseWarning::LogicalMaximumTooLarge {
+                    offset: 34,
+                    field: fields[2].id(),
+                },
+            ]
         );
+        let Field::Variable(wheel) = &fields[2] else {
+            panic!("Expected a variable field");
+        };
+        assert_eq!(wheel.usage, pages::usages::WHEEL);
+        let Err(ParserError::InvalidData { offset, message }) =
+            ReportDescriptor::parse_validated(bytes)
+        else {
+            panic!("Expected the X axis to fail validation");
+        };
+        assert_eq!(offset, 17);
+        assert_eq!(message, "Logical Maximum does not fit into the Report Size");
+
+        for bytes in [BOOT_KEYBOARD, BATTERY_MOUSE, WHEEL_MOUSE, KEYBOARD_CONSUMER] {
+            let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+            assert!(rdesc.warnings().is_empty());
+        }
+    }
+
+    #[test]
+    fn guess_device_type() {
+        for (bytes, expected) in [
+            (BOOT_KEYBOARD, DeviceType::Keyboard),
+            (KEYBOARD_CONSUMER, DeviceType::Keyboard),
+            (WHEEL_MOUSE, DeviceType::Mouse),
+            (BATTERY_MOUSE, DeviceType::Mouse),
+        ] {
+            let rdesc = ReportDescriptor::try_from(bytes).unwrap();
+            assert_eq!(rdesc.guess_device_type(), expected);
+        }
+
+        let touchscreen: &[u8] = &[
+            0x05, 0x0d, // Usage Page (Digitizers)
+            0x09, 0x04, // Usage (Touch Screen)
+            0xa1, 0x01, // Collection (Application)
+            0x09, 0x22, //  Usage (Finger)
+            0xa1, 0x02, //  Collection (Logical)
+            0x09, 0x42, //   Usage (Tip Switch)
+            0x15, 0x00, //   Logical Minimum (0)
+            0x25, 0x01, //   Logical Maximum (1)
+            0x75, 0x01, //   Report Size (1)
+            0x95, 0x01, //   Report Count (1)
+            0x81, 0x02, //   Input (Data,Var,Abs)
+            0x75, 0x07, //   Report Size (7)
+            0x81, 0x01, //   Input (Cnst)
+            0x09, 0x51, //   Usage (Contact Identifier)
+            0x25, 0x7f, //   Logical Maximum (127)
+            0x75, 0x08, //   Report Size (8)
+            0x81, 0x02, //   Input (Data,Var,Abs)
+            0x05, 0x01, //   Usage Page (Generic Desktop)
+            0x09, 0x30, //   Usage (X)
+            0x09, 0x31, //   Usage (Y)
+            0x26, 0xff, 0x0f, // Logical Maximum (4095)
+            0x75, 0x10, //   Report Size (16)
+            0x95, 0x02, //   Report Count (2)
+            0x81, 0x02, //   Input (Data,Var,Abs)
+            0xc0, //        End Collection
+            0xc0, //       End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(touchscreen).unwrap();
+        assert_eq!(rdesc.guess_device_type(), DeviceType::Touchscreen);
+
+        // The same with a Mouse application collection is a touchpad in
+        // mouse mode, the Contact Identifier gives it away
+        let mut bytes = vec![0x05, 0x01, 0x09, 0x02, 0xa1, 0x01, 0x05, 0x0d];
+        bytes.extend_from_slice(&touchscreen[6..]);
+        let rdesc = ReportDescriptor::try_from(&bytes).unwrap();
+        assert_eq!(rdesc.guess_device_type(), DeviceType::Touchpad);
+
+        // Buttons and X/Y but a Gamepad application collection
+        let gamepad: &[u8] = &[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x05, // Usage (Gamepad)
+            0xa1, 0x01, // Collection (Application)
+            0x05, 0x09, //  Usage Page (Button)
+            0x19, 0x01, //  Usage Minimum (1)
+            0x29, 0x08, //  Usage Maximum (8)
+            0x15, 0x00, //  Logical Minimum (0)
+            0x25, 0x01, //  Logical Maximum (1)
+            0x75, 0x01, //  Report Size (1)
+            0x95, 0x08, //  Report Count (8)
+            0x81, 0x02, //  Input (Data,Var,Abs)
+            0x05, 0x01, //  Usage Page (Generic Desktop)
+            0x09, 0x30, //  Usage (X)
+            0x09, 0x31, //  Usage (Y)
+            0x15, 0x81, //  Logical Minimum (-127)
+            0x25, 0x7f, //  Logical Maximum (127)
+            0x75, 0x08, //  Report Size (8)
+            0x95, 0x02, //  Report Count (2)
+            0x81, 0x02, //  Input (Data,Var,Abs)
+            0xc0, // End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(gamepad).unwrap();
+        assert_eq!(rdesc.guess_device_type(), DeviceType::Gamepad);
+
+        // A vendor collection that looks like nothing else
+        let vendor: &[u8] = &[
+            0x06, 0x00, 0xff, // Usage Page (Vendor Defined 0xFF00)
+            0x09, 0x01, //       Usage (Vendor Usage 1)
+            0xa1, 0x01, //       Collection (Application)
+            0x15, 0x00, //        Logical Minimum (0)
+            0x26, 0xff, 0x00, //  Logical Maximum (255)
+            0x75, 0x08, //        Report Size (8)
+            0x95, 0x08, //        Report Count (8)
+            0x09, 0x01, //        Usage (Vendor Usage 1)
+            0x81, 0x02, //        Input (Data,Var,Abs)
+            0xc0, //             End Collection
+        ];
+        let rdesc = ReportDescriptor::try_from(vendor).unwrap();
+        assert_eq!(rdesc.guess_device_type(), DeviceType::Vendor);
     }
 }
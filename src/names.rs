@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: MIT
+
+//! A minimal subset of the HID Usage Tables (HUT) for human-readable output.
+//!
+//! This is not intended as a complete list of usages, use the `hut` crate for that.
+
+use crate::{Usage, Usages};
+use alloc::format;
+use alloc::string::String;
+
+/// Returns the name of the given usage page, if known.
+pub(crate) fn usage_page_name(usage_page: u16) -> Option<&'static str> {
+    let name = match usage_page {
+        0x01 => "Generic Desktop",
+        0x02 => "Simulation Controls",
+        0x03 => "VR Controls",
+        0x04 => "Sport Controls",
+        0x05 => "Game Controls",
+        0x06 => "Generic Device Controls",
+        0x07 => "Keyboard/Keypad",
+        0x08 => "LED",
+        0x09 => "Button",
+        0x0A => "Ordinal",
+        0x0B => "Telephony Device",
+        0x0C => "Consumer",
+        0x0D => "Digitizers",
+        0x0E => "Haptics",
+        0x0F => "Physical Input Device",
+        0x10 => "Unicode",
+        0x11 => "SoC",
+        0x12 => "Eye and Head Trackers",
+        0x14 => "Auxiliary Display",
+        0x20 => "Sensors",
+        0x40 => "Medical Instrument",
+        0x41 => "Braille Display",
+        0x59 => "Lighting And Illumination",
+        0x80 => "Monitor",
+        0x81 => "Monitor Enumerated",
+        0x82 => "VESA Virtual Controls",
+        0x84 => "Power",
+        0x85 => "Battery System",
+        0x8C => "Barcode Scanner",
+        0x8D => "Scales",
+        0x8E => "Magnetic Stripe Reader",
+        0x90 => "Camera Control",
+        0x91 => "Arcade",
+        0x92 => "Gaming Device",
+        0xF1D0 => "FIDO Alliance",
+        0xFF00..=0xFFFF => "Vendor Defined",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Returns the name of the given usage, if known.
+pub(crate) fn usage_name(usage: &Usage) -> Option<String> {
+    let usage_page = u16::from(usage.usage_page);
+    let usage_id = u16::from(usage.usage_id);
+    let name = match (usage_page, usage_id) {
+        // Generic Desktop
+        (0x01, 0x01) => "Pointer",
+        (0x01, 0x02) => "Mouse",
+        (0x01, 0x04) => "Joystick",
+        (0x01, 0x05) => "Gamepad",
+        (0x01, 0x06) => "Keyboard",
+        (0x01, 0x07) => "Keypad",
+        (0x01, 0x08) => "Multi-axis Controller",
+        (0x01, 0x30) => "X",
+        (0x01, 0x31) => "Y",
+        (0x01, 0x32) => "Z",
+        (0x01, 0x33) => "Rx",
+        (0x01, 0x34) => "Ry",
+        (0x01, 0x35) => "Rz",
+        (0x01, 0x36) => "Slider",
+        (0x01, 0x37) => "Dial",
+        (0x01, 0x38) => "Wheel",
+        (0x01, 0x39) => "Hat Switch",
+        (0x01, 0x3D) => "Start",
+        (0x01, 0x3E) => "Select",
+        (0x01, 0x48) => "Resolution Multiplier",
+        (0x01, 0x80) => "System Control",
+        // Generic Device Controls
+        (0x06, 0x20) => "Battery Strength",
+        // LED
+        (0x08, 0x01) => "Num Lock",
+        (0x08, 0x02) => "Caps Lock",
+        (0x08, 0x03) => "Scroll Lock",
+        (0x08, 0x04) => "Compose",
+        (0x08, 0x05) => "Kana",
+        // Button
+        (0x09, 0x00) => "No Button Pressed",
+        (0x09, id) => return Some(format!("Button {id}")),
+        // Consumer
+        (0x0C, 0x01) => "Consumer Control",
+        (0x0C, 0x6F) => "Display Brightness Increment",
+        (0x0C, 0x70) => "Display Brightness Decrement",
+        (0x0C, 0xB0) => "Play",
+        (0x0C, 0xB1) => "Pause",
+        (0x0C, 0xB2) => "Record",
+        (0x0C, 0xB3) => "Fast Forward",
+        (0x0C, 0xB4) => "Rewind",
+        (0x0C, 0xB5) => "Scan Next Track",
+        (0x0C, 0xB6) => "Scan Previous Track",
+        (0x0C, 0xB7) => "Stop",
+        (0x0C, 0xB8) => "Eject",
+        (0x0C, 0xCD) => "Play/Pause",
+        (0x0C, 0xE2) => "Mute",
+        (0x0C, 0xE9) => "Volume Increment",
+        (0x0C, 0xEA) => "Volume Decrement",
+        (0x0C, 0x183) => "AL Consumer Control Configuration",
+        (0x0C, 0x18A) => "AL Email Reader",
+        (0x0C, 0x192) => "AL Calculator",
+        (0x0C, 0x194) => "AL Local Machine Browser",
+        (0x0C, 0x221) => "AC Search",
+        (0x0C, 0x223) => "AC Home",
+        (0x0C, 0x224) => "AC Back",
+        (0x0C, 0x225) => "AC Forward",
+        (0x0C, 0x226) => "AC Stop",
+        (0x0C, 0x227) => "AC Refresh",
+        (0x0C, 0x22A) => "AC Bookmarks",
+        (0x0C, 0x238) => "AC Pan",
+        // Digitizers
+        (0x0D, 0x01) => "Digitizer",
+        (0x0D, 0x02) => "Pen",
+        (0x0D, 0x04) => "Touch Screen",
+        (0x0D, 0x05) => "Touch Pad",
+        (0x0D, 0x20) => "Stylus",
+        (0x0D, 0x22) => "Finger",
+        (0x0D, 0x30) => "Tip Pressure",
+        (0x0D, 0x32) => "In Range",
+        (0x0D, 0x42) => "Tip Switch",
+        (0x0D, 0x47) => "Confidence",
+        (0x0D, 0x48) => "Width",
+        (0x0D, 0x49) => "Height",
+        (0x0D, 0x51) => "Contact Identifier",
+        (0x0D, 0x54) => "Contact Count",
+        (0x0D, 0x55) => "Contact Count Maximum",
+        // FIDO Alliance
+        (0xF1D0, 0x01) => "U2F Authenticator Device",
+        (0xF1D0, 0x20) => "Input Report Data",
+        (0xF1D0, 0x21) => "Output Report Data",
+        _ => return None,
+    };
+    Some(name.into())
+}
+
+/// A caller-supplied fallback that names a usage (page, id) without a
+/// built-in name.
+pub(crate) type Resolver<'a> = Option<&'a dyn Fn(u16, u16) -> Option<String>>;
+
+/// Returns a human-readable description of the given usage in the form
+/// `"Page/Usage"`, e.g. `"Generic Desktop/X"`. Where the usage has no
+/// built-in name, the resolver's name (if any) is used as-is, otherwise
+/// unknown values are printed in hex.
+pub(crate) fn describe_usage(usage: &Usage, resolver: Resolver) -> String {
+    let page = u16::from(usage.usage_page);
+    let id = u16::from(usage.usage_id);
+    let usage_name = match usage_name(usage) {
+        Some(name) => name,
+        None => match resolver.and_then(|r| r(page, id)) {
+            Some(name) => return name,
+            None => format!("0x{id:04x}"),
+        },
+    };
+    let page_name = match usage_page_name(page) {
+        Some("Vendor Defined") => format!("Vendor Defined 0x{page:04x}"),
+        Some(name) => name.into(),
+        None => format!("0x{page:04x}"),
+    };
+    format!("{page_name}/{usage_name}")
+}
+
+/// Returns a human-readable description of the given usages as a range
+/// `"First..=Last"`, see [describe_usage]. Empty for an empty list.
+pub(crate) fn describe_usage_range(usages: Usages, resolver: Resolver) -> String {
+    match (usages.first(), usages.last()) {
+        (Some(first), Some(last)) if first != last => {
+            format!(
+                "{}..={}",
+                describe_usage(&first, resolver),
+                describe_usage(&last, resolver)
+            )
+        }
+        (Some(first), _) => describe_usage(&first, resolver),
+        _ => String::new(),
+    }
+}
@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: MIT
+
+//! Constants for commonly used Usage Pages, see the HID Usage Tables (HUT).
+//!
+//! ```
+//! # use hidreport::*;
+//! # fn func(usage: &Usage) {
+//! if usage.usage_page == pages::BUTTON {
+//!     println!("This is a button");
+//! } else if *usage == pages::usages::X {
+//!     println!("This is the X axis");
+//! }
+//! # }
+//! ```
+//!
+//! This is not intended as a complete list, use the `hut` crate for that.
+
+use crate::UsagePage;
+
+pub const GENERIC_DESKTOP: UsagePage = UsagePage(0x01);
+pub const SIMULATION_CONTROLS: UsagePage = UsagePage(0x02);
+pub const GAME_CONTROLS: UsagePage = UsagePage(0x05);
+pub const GENERIC_DEVICE_CONTROLS: UsagePage = UsagePage(0x06);
+pub const KEYBOARD: UsagePage = UsagePage(0x07);
+pub const LED: UsagePage = UsagePage(0x08);
+pub const BUTTON: UsagePage = UsagePage(0x09);
+pub const ORDINAL: UsagePage = UsagePage(0x0A);
+pub const TELEPHONY: UsagePage = UsagePage(0x0B);
+pub const CONSUMER: UsagePage = UsagePage(0x0C);
+pub const DIGITIZERS: UsagePage = UsagePage(0x0D);
+pub const HAPTICS: UsagePage = UsagePage(0x0E);
+pub const SENSORS: UsagePage = UsagePage(0x20);
+pub const BATTERY_SYSTEM: UsagePage = UsagePage(0x85);
+pub const FIDO_ALLIANCE: UsagePage = UsagePage(0xF1D0);
+
+/// Constants for commonly used [Usage](crate::Usage)s, each including its Usage Page.
+pub mod usages {
+    use super::*;
+    use crate::{Usage, UsageId};
+
+    const fn usage(usage_page: UsagePage, id: u16) -> Usage {
+        Usage {
+            usage_page,
+            usage_id: UsageId(id),
+        }
+    }
+
+    // Generic Desktop
+    pub const POINTER: Usage = usage(GENERIC_DESKTOP, 0x01);
+    pub const MOUSE: Usage = usage(GENERIC_DESKTOP, 0x02);
+    pub const JOYSTICK: Usage = usage(GENERIC_DESKTOP, 0x04);
+    pub const GAMEPAD: Usage = usage(GENERIC_DESKTOP, 0x05);
+    pub const KEYBOARD: Usage = usage(GENERIC_DESKTOP, 0x06);
+    pub const KEYPAD: Usage = usage(GENERIC_DESKTOP, 0x07);
+    pub const X: Usage = usage(GENERIC_DESKTOP, 0x30);
+    pub const Y: Usage = usage(GENERIC_DESKTOP, 0x31);
+    pub const Z: Usage = usage(GENERIC_DESKTOP, 0x32);
+    pub const RX: Usage = usage(GENERIC_DESKTOP, 0x33);
+    pub const RY: Usage = usage(GENERIC_DESKTOP, 0x34);
+    pub const RZ: Usage = usage(GENERIC_DESKTOP, 0x35);
+    pub const WHEEL: Usage = usage(GENERIC_DESKTOP, 0x38);
+    pub const HAT_SWITCH: Usage = usage(GENERIC_DESKTOP, 0x39);
+    pub const RESOLUTION_MULTIPLIER: Usage = usage(GENERIC_DESKTOP, 0x48);
+    // Generic Device Controls
+    pub const BATTERY_STRENGTH: Usage = usage(GENERIC_DEVICE_CONTROLS, 0x20);
+    // Consumer
+    pub const CONSUMER_CONTROL: Usage = usage(CONSUMER, 0x01);
+    pub const AC_PAN: Usage = usage(CONSUMER, 0x238);
+    // Digitizers
+    pub const TOUCH_SCREEN: Usage = usage(DIGITIZERS, 0x04);
+    pub const TOUCH_PAD: Usage = usage(DIGITIZERS, 0x05);
+    pub const TIP_SWITCH: Usage = usage(DIGITIZERS, 0x42);
+    pub const CONTACT_IDENTIFIER: Usage = usage(DIGITIZERS, 0x51);
+    // FIDO Alliance
+    pub const U2F_AUTHENTICATOR_DEVICE: Usage = usage(FIDO_ALLIANCE, 0x01);
+}
@@ -6,7 +6,9 @@
 //! In this document and unless stated otherwise, a reference to "Section a.b.c" refers to the
 //! [HID Device Class Definition for HID 1.11](https://www.usb.org/document-library/device-class-definition-hid-111).
 
-use crate::TwosComplement;
+use crate::{ParserError, TwosComplement};
+use alloc::string::String;
+use alloc::{format, vec, vec::Vec};
 
 /// Creates a `From<Foo> for u32` and `From<u32> for Foo` implementation for the given `Foo` type.
 /// Use like this: `impl_from(Foo, Foo, u32)`.
@@ -34,8 +36,8 @@ macro_rules! impl_from {
 /// Use like this: `impl_fmt(Foo, u32)`.
 macro_rules! impl_fmt {
     ($tipo:ty, $to:ty) => {
-        impl std::fmt::Display for $tipo {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        impl core::fmt::Display for $tipo {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
                 let v: $to = self.into();
                 write!(f, "{v}")
             }
@@ -54,7 +56,7 @@ macro_rules! impl_fmt {
 /// let usage = Usage::from_page_and_id(up, uid);
 /// ```
 /// For known named usages see the `hut` crate.
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct UsagePage(pub(crate) u16);
 
 impl_from!(UsagePage, UsagePage, u16);
@@ -121,8 +123,8 @@ pub enum Units {
     Candela { exponent: i8 },
 }
 
-impl std::fmt::Display for Units {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for Units {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let (unit, exp) = match self {
             Units::None => ("", &0i8),
             Units::Centimeter { exponent } => ("cm", exponent),
@@ -210,14 +212,14 @@ impl From<Units> for u32 {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Unit(pub(crate) u32);
 
 impl_from!(Unit, Unit, u32);
 
 impl Unit {
     fn nibbles(&self) -> Vec<u8> {
-        std::ops::Range { start: 0, end: 32 }
+        core::ops::Range { start: 0, end: 32 }
             .step_by(4)
             .map(|shift| ((self.0 & (0b1111 << shift)) >> shift) as u8)
             .collect()
@@ -247,7 +249,8 @@ impl Unit {
     }
 
     /// The [UnitSystem] used by a field.
-    /// Returns [UnitSystem::None] if unset.
+    /// Returns [UnitSystem::None] if unset or if the system is reserved
+    /// or vendor-defined.
     pub fn system(&self) -> UnitSystem {
         match self.nibbles().first() {
             None | Some(0) => UnitSystem::None,
@@ -255,7 +258,8 @@ impl Unit {
             Some(2) => UnitSystem::SIRotation,
             Some(3) => UnitSystem::EnglishLinear,
             Some(4) => UnitSystem::EnglishRotation,
-            Some(n) => panic!("invalid size {n} for the UnitSystem nibble"),
+            // Reserved or vendor-defined
+            Some(_) => UnitSystem::None,
         }
     }
 
@@ -364,8 +368,8 @@ impl Unit {
     }
 }
 
-impl std::fmt::Display for Unit {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for Unit {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let units = self
             .units()
             .unwrap_or_default()
@@ -377,10 +381,13 @@ impl std::fmt::Display for Unit {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct UnitExponent(pub(crate) u32);
 
 impl UnitExponent {
+    /// Returns the signed base-10 exponent, decoding the 4-bit two's
+    /// complement nibble (`0x0F` is -1, `0x08` is -8) as well as
+    /// a normal signed 1, 2 or 4 byte value.
     pub fn exponent(&self) -> i8 {
         // Section 6.2.2.7 lists an example implying that
         // the unit exponent must be a nibble. Real devices
@@ -394,6 +401,11 @@ impl UnitExponent {
             n => n as i8,
         }
     }
+
+    /// Returns the signed base-10 exponent, see [UnitExponent::exponent].
+    pub fn value(&self) -> i8 {
+        self.exponent()
+    }
 }
 
 impl_from!(UnitExponent, UnitExponent, u32);
@@ -405,7 +417,7 @@ pub struct ReportSize(pub(crate) usize);
 impl_from!(ReportSize, ReportSize, usize);
 impl_fmt!(ReportSize, usize);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ReportId(pub(crate) u8);
 
 impl From<&ReportId> for ReportId {
@@ -414,7 +426,39 @@ impl From<&ReportId> for ReportId {
     }
 }
 
-impl_from!(ReportId, ReportId, u8);
+impl From<ReportId> for u8 {
+    fn from(report_id: ReportId) -> u8 {
+        report_id.0
+    }
+}
+
+impl From<&ReportId> for u8 {
+    fn from(report_id: &ReportId) -> u8 {
+        report_id.0
+    }
+}
+
+/// Report ID 0 is reserved and means the report has no Report ID, see
+/// Section 6.2.2.7, so converting 0 into a [ReportId] fails.
+///
+/// ```
+/// # use hidreport::*;
+/// assert_eq!(u8::from(ReportId::try_from(3).unwrap()), 3);
+/// assert!(ReportId::try_from(0).is_err());
+/// ```
+impl TryFrom<u8> for ReportId {
+    type Error = ParserError;
+
+    fn try_from(id: u8) -> Result<Self, Self::Error> {
+        if id == 0 {
+            return Err(ParserError::InvalidReport {
+                message: "Report ID 0 is reserved".into(),
+            });
+        }
+        Ok(ReportId(id))
+    }
+}
+
 impl_fmt!(ReportId, u8);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -435,7 +479,7 @@ impl_fmt!(ReportCount, usize);
 /// let usage = Usage::from_page_and_id(up, uid);
 /// ```
 /// For known named usages see the `hut` crate.
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct UsageId(pub(crate) u16);
 
 impl_from!(UsageId, UsageId, u16);
@@ -473,7 +517,7 @@ impl UsageMaximum {
 impl_from!(UsageMaximum, UsageMaximum, u32);
 impl_fmt!(UsageMaximum, u32);
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct StringIndex(pub(crate) u32);
 
 impl_from!(StringIndex, StringIndex, u32);
@@ -491,7 +535,7 @@ pub struct StringMaximum(pub(crate) u32);
 impl_from!(StringMaximum, StringMaximum, u32);
 impl_fmt!(StringMaximum, u32);
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct DesignatorIndex(pub(crate) u32);
 
 impl_from!(DesignatorIndex, DesignatorIndex, u32);
@@ -634,19 +678,23 @@ mod tests {
     #[test]
     fn unit_exponent() {
         let testvals = vec![
-            (0xf, -1),  // nibble test
-            (0xe, -2),  // nibble test
-            (0x8, -8),  // nibble test
-            (0x7, 7),   // normal or nibble
-            (0x1, 1),   // normal or nibble
-            (0xff, -1), // normal value
-            (0xfd, -3), // normal value
-            (0x10, 16), // normal value
+            (0xf, -1),    // nibble test
+            (0xe, -2),    // nibble test
+            (0x8, -8),    // nibble test
+            (0x7, 7),     // normal or nibble
+            (0x5, 5),     // normal or nibble
+            (0x1, 1),     // normal or nibble
+            (0x0, 0),     // normal or nibble
+            (0xff, -1),   // normal value
+            (0xfd, -3),   // normal value
+            (0x10, 16),   // normal value
+            (0xfffe, -2), // normal 2-byte value
         ];
 
         for (v, expected) in testvals {
             let exponent = UnitExponent::from(v);
             assert_eq!(exponent.exponent(), expected);
+            assert_eq!(exponent.value(), expected);
         }
     }
 }
@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: MIT
+
+//! Check that the parser is usable from a `#![no_std]` crate with
+//! only `alloc` available.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use hidreport::*;
+
+#[test]
+fn parse_without_std() {
+    // HID spec B.2 boot mouse
+    let bytes: Vec<u8> = [
+        0x05, 0x01, 0x09, 0x02, 0xa1, 0x01, 0x09, 0x01, 0xa1, 0x00, 0x05, 0x09, 0x19, 0x01, 0x29,
+        0x03, 0x15, 0x00, 0x25, 0x01, 0x95, 0x03, 0x75, 0x01, 0x81, 0x02, 0x95, 0x01, 0x75, 0x05,
+        0x81, 0x01, 0x05, 0x01, 0x09, 0x30, 0x09, 0x31, 0x15, 0x81, 0x25, 0x7f, 0x75, 0x08, 0x95,
+        0x02, 0x81, 0x06, 0xc0, 0xc0,
+    ]
+    .into();
+    let rdesc = ReportDescriptor::try_from(&bytes).unwrap();
+    assert_eq!(rdesc.input_reports().len(), 1);
+    assert_eq!(rdesc.input_reports()[0].size_in_bytes(), 3);
+    assert!(!rdesc.to_bytes().is_empty());
+}